@@ -1,93 +1,779 @@
-use proc_macro2::{Ident, TokenStream};
-use quote::quote;
-use syn::{DataEnum, DataStruct, Fields, Generics};
+use proc_macro::{Diagnostic, Level};
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{format_ident, quote, quote_spanned};
+use syn::{
+	parse_quote,
+	spanned::Spanned,
+	Attribute,
+	DataEnum,
+	DataStruct,
+	Field,
+	Fields,
+	GenericArgument,
+	Generics,
+	Lit,
+	Meta,
+	NestedMeta,
+	Path,
+	PathArguments,
+	Type,
+};
 
-pub fn actor_struct(name: Ident, s: DataStruct, generics: Generics) -> TokenStream {
+pub fn actor_struct(name: Ident, s: DataStruct, generics: Generics, options: &TypeOptions) -> TokenStream {
 	let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
-	let subfields = match s.fields {
+	let named = matches!(s.fields, Fields::Named(_));
+	let handle_order = actor_handle_order(&name, &generics, &options.handle_order);
+	let counters = actor_counters(&name, &generics, &s.fields);
+	// Each field, with the path segment that names it.
+	let mut fields: Vec<_> = match s.fields {
 		Fields::Named(fields) => fields
 			.named
 			.into_iter()
+			.filter(|field| !is_skipped(field))
 			.map(|field| {
+				warn_recursive(&name, &field);
 				let ident = field.ident.unwrap();
-				quote! {
-					self.#ident.accept(visitor);
-				}
+				(ident.to_string(), (quote! { self.#ident }, field.ty))
 			})
 			.collect(),
-		Fields::Unnamed(fields) => (0..fields.unnamed.len())
-			.map(|field| {
-				quote! {
-					self.#field.accept(visitor);
-				}
+		Fields::Unnamed(fields) => fields
+			.unnamed
+			.into_iter()
+			.enumerate()
+			.filter(|(_, field)| !is_skipped(field))
+			.map(|(index, field)| {
+				warn_recursive(&name, &field);
+				let segment = index.to_string();
+				let index = syn::Index::from(index);
+				(segment, (quote! { self.#index }, field.ty))
 			})
 			.collect(),
 		_ => Vec::new(),
 	};
+	if named && options.order_by_name {
+		fields.sort_by(|(a, _), (b, _)| a.cmp(b));
+	}
+	let (segments, (fields, types)): (Vec<_>, (Vec<_>, Vec<_>)) = fields.into_iter().unzip();
+	let covers = options.sparse.then(|| actor_covers(&name, &generics, &types));
+	let meta = options
+		.meta
+		.then(|| actor_meta(&name, &generics, if named { &segments } else { &[] }));
+	// Disabled `Actor`s skip their whole sub-tree.
+	let (enabled, path_enabled) = match &options.enable_field {
+		Some(field) => (
+			quote! { if !self.#field { return; } },
+			quote! { if !self.#field { return false; } },
+		),
+		None => (TokenStream::new(), TokenStream::new()),
+	};
+	let visit = visit_order(
+		options,
+		quote! {
+			#(if !visitor.skips::<#types>() {
+				#fields.accept(visitor);
+			})*
+		},
+	);
+	let subscriber = options.topics_field.as_ref().map(|field| {
+		quote! {
+			impl #impl_generics send::Subscriber for #name #ty_generics #where_clause {
+				#[inline]
+				fn is_subscribed(&self, topic: send::TopicId) -> bool { self.#field.contains(topic) }
+			}
+		}
+	});
 
 	quote! {
 		unsafe impl #impl_generics send::Actor for #name #ty_generics #where_clause {
 			#[inline]
-			fn accept<T, R>(&mut self, visitor: &mut impl send::ActorVisitor<T, R>) {
-				#(#subfields)*
-								
-				visitor.visit(self);
+			fn accept<_MessageTy, _RootTy>(&mut self, visitor: &mut impl send::ActorVisitor<_MessageTy, _RootTy>) {
+				#enabled
+				if visitor.prunes(self) {
+					return;
+				}
+				#visit
+			}
+
+			#[inline]
+			#[allow(unused_variables)]
+			fn accept_path<_MessageTy, _RootTy>(
+				&mut self, path: &[&str], visitor: &mut impl send::ActorVisitor<_MessageTy, _RootTy>,
+			) -> bool {
+				#path_enabled
+				match path.split_first() {
+					None => {
+						visitor.visit(self);
+						true
+					},
+					Some((segment, rest)) => match *segment {
+						#(#segments => #fields.accept_path(rest, visitor),)*
+						_ => false,
+					},
+				}
 			}
 		}
 
 		impl #impl_generics !send::NotActor for #name #ty_generics #where_clause {}
+
+		#subscriber
+
+		#covers
+
+		#handle_order
+
+		#counters
+
+		#meta
 	}
 }
 
-pub fn actor_enum(name: Ident, e: DataEnum, generics: Generics) -> TokenStream {
+pub fn actor_enum(name: Ident, e: DataEnum, generics: Generics, options: &TypeOptions) -> TokenStream {
 	let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
-	let variants: Vec<_> = e
+	let enum_name = &name;
+	for attr in e
+		.variants
+		.iter()
+		.flat_map(|variant| &variant.fields)
+		.flat_map(|field| &field.attrs)
+		.filter(|attr| attr.path.is_ident("counter"))
+	{
+		Diagnostic::spanned(
+			attr.span().unwrap(),
+			Level::Error,
+			"`counter` is only supported on struct fields",
+		)
+		.emit();
+	}
+	let handle_order = actor_handle_order(&name, &generics, &options.handle_order);
+	// The types of the visited fields of every variant.
+	let mut types = Vec::new();
+	// The arms of `accept`, and of `accept_path` when the path isn't empty.
+	let (variants, paths): (Vec<_>, Vec<_>) = e
 		.variants
 		.into_iter()
 		.map(|variant| {
 			let ident = variant.ident;
 			match variant.fields {
 				Fields::Named(fields) => {
-					let names = fields.named.into_iter().map(|field| field.ident.unwrap());
-					let names_2 = names.clone();
-					quote! {
-						#name::#ident { #(#names,)* } => {
-							#(#names_2.accept(visitor);)*
-						}
+					let (patterns, names): (Vec<_>, Vec<_>) = fields
+						.named
+						.into_iter()
+						.map(|field| {
+							let skipped = is_skipped(&field);
+							if !skipped {
+								warn_recursive(enum_name, &field);
+							}
+							let name = field.ident.unwrap();
+							if skipped {
+								(quote! { #name: _ }, None)
+							} else {
+								(quote! { #name }, Some((name, field.ty)))
+							}
+						})
+						.unzip();
+					let mut names: Vec<_> = names.into_iter().flatten().collect();
+					if options.order_by_name {
+						names.sort_by(|(a, _), (b, _)| a.cmp(b));
 					}
+					let (names, variant_types): (Vec<_>, Vec<_>) = names.into_iter().unzip();
+					let segments = names.iter().map(|name| name.to_string());
+					let accept = quote! {
+						#name::#ident { #(#patterns,)* } => {
+							#(if !visitor.skips::<#variant_types>() {
+								#names.accept(visitor);
+							})*
+						}
+					};
+					types.extend(variant_types);
+					(
+						accept,
+						quote! {
+							#name::#ident { #(#patterns,)* } => match *segment {
+								#(#segments => #names.accept_path(rest, visitor),)*
+								_ => false,
+							},
+						},
+					)
 				},
 				Fields::Unnamed(fields) => {
-					let names = fields
+					let (patterns, names): (Vec<_>, Vec<_>) = fields
 						.unnamed
 						.into_iter()
 						.enumerate()
-						.map(|field| "_".to_string() + &field.0.to_string());
-					let names_2 = names.clone();
-					quote! {
-						#name::#ident(#(#names,)*) => {
-							#(#names_2.accept(visitor);)*
+						.map(|(index, field)| {
+							if is_skipped(&field) {
+								(quote! { _ }, None)
+							} else {
+								warn_recursive(enum_name, &field);
+								let name = format_ident!("_{}", index);
+								(quote! { #name }, Some((index.to_string(), (name, field.ty))))
+							}
+						})
+						.unzip();
+					let (segments, (names, variant_types)): (Vec<_>, (Vec<_>, Vec<_>)) =
+						names.into_iter().flatten().unzip();
+					let accept = quote! {
+						#name::#ident(#(#patterns,)*) => {
+							#(if !visitor.skips::<#variant_types>() {
+								#names.accept(visitor);
+							})*
 						}
-					}
-				},
-				Fields::Unit => quote! {
-					#name::#ident => {},
+					};
+					types.extend(variant_types);
+					(
+						accept,
+						quote! {
+							#name::#ident(#(#patterns,)*) => match *segment {
+								#(#segments => #names.accept_path(rest, visitor),)*
+								_ => false,
+							},
+						},
+					)
 				},
+				Fields::Unit => (
+					quote! {
+						#name::#ident => {},
+					},
+					quote! {
+						#name::#ident => false,
+					},
+				),
 			}
 		})
-		.collect();
+		.unzip();
+	let covers = options.sparse.then(|| actor_covers(&name, &generics, &types));
+	let meta = options.meta.then(|| actor_meta(&name, &generics, &[]));
+	let visit = visit_order(
+		options,
+		quote! {
+			match self {
+				#(#variants)*
+			}
+		},
+	);
+	if let Some(field) = &options.enable_field {
+		Diagnostic::spanned(
+			field.span().unwrap(),
+			Level::Error,
+			"`enable_field` is only supported on structs",
+		)
+		.emit();
+	}
+	if let Some(field) = &options.topics_field {
+		Diagnostic::spanned(
+			field.span().unwrap(),
+			Level::Error,
+			"`topics` is only supported on structs",
+		)
+		.emit();
+	}
 
 	quote! {
 		unsafe impl #impl_generics send::Actor for #name #ty_generics #where_clause {
 			#[inline]
-			fn accept<T, R>(&mut self, visitor: &mut impl send::ActorVisitor<T, R>) {
-				match self {
-					#(#variants)*
+			fn accept<_MessageTy, _RootTy>(&mut self, visitor: &mut impl send::ActorVisitor<_MessageTy, _RootTy>) {
+				if visitor.prunes(self) {
+					return;
+				}
+				#visit
+			}
+
+			#[inline]
+			#[allow(unused_variables)]
+			fn accept_path<_MessageTy, _RootTy>(
+				&mut self, path: &[&str], visitor: &mut impl send::ActorVisitor<_MessageTy, _RootTy>,
+			) -> bool {
+				match path.split_first() {
+					None => {
+						visitor.visit(self);
+						true
+					},
+					Some((segment, rest)) => match self {
+						#(#paths)*
+					},
 				}
-				
-				visitor.visit(self);
 			}
 		}
 
 		impl #impl_generics !send::NotActor for #name #ty_generics #where_clause {}
+
+		#covers
+
+		#handle_order
+
+		#meta
+	}
+}
+
+/// The body of `accept`, visiting the `Actor` itself after its `fields` by default, or before them with
+/// `top_down` or when the visitor asks for it.
+fn visit_order(options: &TypeOptions, fields: TokenStream) -> TokenStream {
+	if options.top_down {
+		quote! {
+			visitor.visit(self);
+			#fields
+		}
+	} else {
+		quote! {
+			if visitor.top_down() {
+				visitor.visit(self);
+				#fields
+			} else {
+				#fields
+				visitor.visit(self);
+			}
+		}
+	}
+}
+
+/// How a field marked with `#[counter(...)]` is updated.
+struct CounterOptions {
+	/// Whether the counter wraps around instead of saturating, with `wrapping` instead of `saturating`.
+	wrapping: bool,
+	/// The message type that adds to the counter, with `increment = "..."`, or `send::Increment<T>`.
+	increment: Option<Path>,
+	/// The message type that subtracts from the counter, with `decrement = "..."`, or `send::Decrement<T>`.
+	decrement: Option<Path>,
+}
+
+/// Parse `#[counter(saturating)]` or `#[counter(wrapping)]` on a field, with optional `increment = "..."` and
+/// `decrement = "..."`. Returns `None` if the field isn't a counter.
+fn counter_options(field: &Field) -> Option<CounterOptions> {
+	let mut options = None;
+	for attr in field.attrs.iter().filter(|attr| attr.path.is_ident("counter")) {
+		let counter = options.get_or_insert(CounterOptions {
+			wrapping: false,
+			increment: None,
+			decrement: None,
+		});
+		let mut mode = false;
+		match attr.parse_meta() {
+			Ok(Meta::List(list)) => {
+				for nested in list.nested {
+					match nested {
+						NestedMeta::Meta(Meta::Path(path)) if path.is_ident("saturating") => mode = true,
+						NestedMeta::Meta(Meta::Path(path)) if path.is_ident("wrapping") => {
+							mode = true;
+							counter.wrapping = true;
+						},
+						NestedMeta::Meta(Meta::NameValue(value))
+							if value.path.is_ident("increment") || value.path.is_ident("decrement") =>
+						{
+							let message = match &value.lit {
+								Lit::Str(message) => message.parse().ok(),
+								_ => None,
+							};
+							if message.is_none() {
+								Diagnostic::spanned(value.lit.span().unwrap(), Level::Error, "Expected a message type")
+									.emit();
+							} else if value.path.is_ident("increment") {
+								counter.increment = message;
+							} else {
+								counter.decrement = message;
+							}
+						},
+						nested => Diagnostic::spanned(
+							nested.span().unwrap(),
+							Level::Error,
+							"Unknown option. Expected `saturating`, `wrapping`, `increment = \"...\"` or `decrement = \
+							 \"...\"`",
+						)
+						.emit(),
+					}
+				}
+			},
+			_ => Diagnostic::spanned(attr.span().unwrap(), Level::Error, "Expected `#[counter(...)]`").emit(),
+		}
+		if !mode {
+			Diagnostic::spanned(
+				attr.span().unwrap(),
+				Level::Error,
+				"Expected `saturating` or `wrapping`",
+			)
+			.emit();
+		}
+	}
+	options
+}
+
+/// Implement `Receiver` for the messages that update the fields marked with `#[counter(...)]`, with one
+/// implementation per message type that updates every counter it applies to.
+fn actor_counters(name: &Ident, generics: &Generics, fields: &Fields) -> TokenStream {
+	let (_, ty_generics, where_clause) = generics.split_for_impl();
+	let mut with_root = generics.clone();
+	with_root.params.push(parse_quote!(_RootTy));
+	let (impl_generics, ..) = with_root.split_for_impl();
+	// Each message type, with the statements that update the counters.
+	let mut messages: Vec<(TokenStream, Vec<TokenStream>)> = Vec::new();
+	for (index, field) in fields.iter().enumerate() {
+		let Some(options) = counter_options(field) else {
+			continue;
+		};
+		let member = match &field.ident {
+			Some(ident) => quote! { #ident },
+			None => {
+				let index = syn::Index::from(index);
+				quote! { #index }
+			},
+		};
+		let ty = &field.ty;
+		let (add, sub) = if options.wrapping {
+			(quote! { wrapping_add }, quote! { wrapping_sub })
+		} else {
+			(quote! { saturating_add }, quote! { saturating_sub })
+		};
+		let increment = options
+			.increment
+			.map_or_else(|| quote! { send::Increment<#ty> }, |path| quote! { #path });
+		let decrement = options
+			.decrement
+			.map_or_else(|| quote! { send::Decrement<#ty> }, |path| quote! { #path });
+		for (message, op) in [(increment, add), (decrement, sub)] {
+			let update = quote! { self.#member = self.#member.#op(message.0); };
+			match messages
+				.iter_mut()
+				.find(|(other, _)| other.to_string() == message.to_string())
+			{
+				Some((_, updates)) => updates.push(update),
+				None => messages.push((message, vec![update])),
+			}
+		}
+	}
+	let impls = messages.into_iter().map(|(message, updates)| {
+		quote! {
+			impl #impl_generics send::Receiver<#message, _RootTy> for #name #ty_generics #where_clause {
+				fn receive(&mut self, message: &mut #message, _: send::Context<Self, _RootTy>) {
+					#(#updates)*
+				}
+			}
+
+			impl #impl_generics send::Handles<#message, _RootTy> for #name #ty_generics #where_clause {
+				#[inline]
+				fn handles() -> bool { true }
+			}
+		}
+	});
+	quote! { #(#impls)* }
+}
+
+/// Implement `HandleOrder` for the message types listed in `handle_order(...)`, with their positions in the list.
+fn actor_handle_order(name: &Ident, generics: &Generics, messages: &[Path]) -> TokenStream {
+	let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+	let positions = 0..messages.len();
+	quote! {
+		#(
+			impl #impl_generics send::HandleOrder<#messages> for #name #ty_generics #where_clause {
+				#[inline]
+				fn position() -> ::std::option::Option<usize> { ::std::option::Option::Some(#positions) }
+			}
+		)*
+	}
+}
+
+/// Implement `Covers` for `#[actor(sparse)]`, so that `Framework::send` can skip the `Actor` where neither it nor the
+/// types of its visited fields handle a message. Fields that aren't `Actor`s are never visited, so they don't count.
+fn actor_covers(name: &Ident, generics: &Generics, types: &[Type]) -> TokenStream {
+	let (_, ty_generics, where_clause) = generics.split_for_impl();
+	let mut with_message = generics.clone();
+	with_message.params.push(parse_quote!(_MessageTy));
+	with_message.params.push(parse_quote!(_RootTy));
+	let (impl_generics, ..) = with_message.split_for_impl();
+	quote! {
+		impl #impl_generics send::Covers<_MessageTy, _RootTy> for #name #ty_generics #where_clause {
+			#[inline]
+			fn covers(visiting: &mut ::std::vec::Vec<::std::any::TypeId>) -> bool {
+				send::covers_fields::<Self, _MessageTy, _RootTy>(visiting, |_visiting| {
+					false #(|| send::is_actor!(#types) && <#types as send::Covers<_MessageTy, _RootTy>>::covers(_visiting))*
+				})
+			}
+		}
+	}
+}
+
+/// Implement `ActorMeta`, with the names of the visited fields.
+fn actor_meta(name: &Ident, generics: &Generics, fields: &[String]) -> TokenStream {
+	let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+	let actor_name = name.to_string();
+	quote! {
+		impl #impl_generics send::ActorMeta for #name #ty_generics #where_clause {
+			const ACTOR_NAME: &'static str = #actor_name;
+			const FIELDS: &'static [&'static str] = &[#(#fields),*];
+		}
+	}
+}
+
+/// Warn about fields that contain the type itself, directly or through `Option`, `Box` and the sequence collections,
+/// like `Option<Box<Self>>` or `Vec<Self>`, since traversing a long chain of them can overflow the stack.
+///
+/// Fields marked with `#[actor(recursive)]` are expected to, so they aren't warned about, but it is an error if they
+/// don't.
+fn warn_recursive(name: &Ident, field: &Field) {
+	match (contains_self(name, &field.ty), recursive_span(&field.attrs)) {
+		(true, None) => Diagnostic::spanned(
+			field.ty.span().unwrap(),
+			Level::Warning,
+			"This field contains the `Actor` itself, so traversing a long chain of it can overflow the stack",
+		)
+		.help(
+			"Skip it with `#[actor(skip)]` if it doesn't need to receive messages, or mark it with \
+			 `#[actor(recursive)]`",
+		)
+		.emit(),
+		(false, Some(span)) => Diagnostic::spanned(
+			span.unwrap(),
+			Level::Error,
+			"This field doesn't contain the `Actor` itself, so it can't be `recursive`",
+		)
+		.emit(),
+		_ => {},
+	}
+}
+
+/// Check if `ty` is `Self` or `name`, or one of the wrappers that [`warn_recursive`] looks through around them.
+fn contains_self(name: &Ident, ty: &Type) -> bool {
+	let Type::Path(path) = ty else {
+		return false;
+	};
+	if path.qself.is_some() {
+		return false;
+	}
+	if path.path.segments.len() == 1 {
+		let ident = &path.path.segments[0].ident;
+		if ident == "Self" || ident == name {
+			return true;
+		}
+	}
+	path.path
+		.segments
+		.last()
+		.is_some_and(|segment| match &segment.arguments {
+			PathArguments::AngleBracketed(args)
+				if ["Option", "Box", "Vec", "VecDeque", "LinkedList"]
+					.iter()
+					.any(|wrapper| segment.ident == wrapper) =>
+			{
+				args.args.iter().any(|arg| match arg {
+					GenericArgument::Type(ty) => contains_self(name, ty),
+					_ => false,
+				})
+			},
+			_ => false,
+		})
+}
+
+/// Check if a field is excluded from traversal, either explicitly or because it is a marker.
+fn is_skipped(field: &Field) -> bool { has_skip(&field.attrs) || is_marker(&field.ty) }
+
+/// Check for zero-sized marker types that can never contain an `Actor`: `()`, `PhantomData` and `PhantomPinned`.
+///
+/// These are skipped so that they don't show up in the generated code at all, and so never constrain generics.
+fn is_marker(ty: &Type) -> bool {
+	match ty {
+		Type::Tuple(tuple) => tuple.elems.is_empty(),
+		Type::Path(path) => path
+			.path
+			.segments
+			.last()
+			.is_some_and(|segment| segment.ident == "PhantomData" || segment.ident == "PhantomPinned"),
+		Type::Group(group) => is_marker(&group.elem),
+		Type::Paren(paren) => is_marker(&paren.elem),
+		_ => false,
+	}
+}
+
+/// Check for `#[actor(skip)]`, which excludes a field from traversal.
+fn has_skip(attrs: &[Attribute]) -> bool {
+	let mut skip = false;
+	for attr in attrs.iter().filter(|attr| attr.path.is_ident("actor")) {
+		match attr.parse_meta() {
+			Ok(Meta::List(list)) => {
+				for nested in list.nested {
+					match nested {
+						NestedMeta::Meta(Meta::Path(path)) if path.is_ident("skip") => skip = true,
+						NestedMeta::Meta(Meta::Path(path)) if path.is_ident("recursive") => {},
+						nested => Diagnostic::spanned(
+							nested.span().unwrap(),
+							Level::Error,
+							"Unknown field option. Expected `skip` or `recursive`",
+						)
+						.emit(),
+					}
+				}
+			},
+			_ => Diagnostic::spanned(attr.span().unwrap(), Level::Error, "Expected `#[actor(...)]`").emit(),
+		}
+	}
+	skip
+}
+
+/// Find `#[actor(recursive)]`, which marks a field that contains the type itself, for [`warn_recursive`]. Other
+/// options are checked by [`has_skip`].
+fn recursive_span(attrs: &[Attribute]) -> Option<Span> {
+	attrs
+		.iter()
+		.filter(|attr| attr.path.is_ident("actor"))
+		.filter_map(|attr| match attr.parse_meta() {
+			Ok(Meta::List(list)) => Some(list.nested),
+			_ => None,
+		})
+		.flatten()
+		.find_map(|nested| match nested {
+			NestedMeta::Meta(Meta::Path(path)) if path.is_ident("recursive") => Some(path.span()),
+			_ => None,
+		})
+}
+
+/// The options of `#[actor(...)]` on the type itself.
+pub struct TypeOptions {
+	/// Whether to implement `ActorMeta`, unless `no_meta` is given.
+	pub meta: bool,
+	/// Where `resettable` was given, to implement `Receiver<Reset, _>`.
+	pub resettable: Option<Span>,
+	/// Whether to visit named fields in alphabetical order instead of declaration order, with
+	/// `order_by = "name"`.
+	pub order_by_name: bool,
+	/// The `bool` field that disables the `Actor` and its sub-`Actor`s when it is `false`, with
+	/// `enable_field = "..."`.
+	pub enable_field: Option<Ident>,
+	/// The `Topics` field that implements `Subscriber`, with `topics = "..."`.
+	pub topics_field: Option<Ident>,
+	/// Whether to implement `Covers`, with `sparse` or `handles(...)`.
+	pub sparse: bool,
+	/// The messages with `Receiver`s written by hand, with `handles(...)`.
+	pub handles: Vec<Path>,
+	/// The message types that implement `HandleOrder`, in order, with `handle_order(...)`.
+	pub handle_order: Vec<Path>,
+	/// Whether to visit the `Actor` before its fields instead of after them, with `top_down`.
+	pub top_down: bool,
+}
+
+/// Parse `#[actor(no_meta)]`, `#[actor(resettable)]`, `#[actor(order_by = "name")]`,
+/// `#[actor(enable_field = "...")]`, `#[actor(topics = "...")]`, `#[actor(sparse)]`, `#[actor(handles(...))]`,
+/// `#[actor(handle_order(...))]` and `#[actor(top_down)]` on the type.
+pub fn type_options(attrs: &[Attribute]) -> TypeOptions {
+	let mut options = TypeOptions {
+		meta: true,
+		resettable: None,
+		order_by_name: false,
+		enable_field: None,
+		topics_field: None,
+		sparse: false,
+		handles: Vec::new(),
+		handle_order: Vec::new(),
+		top_down: false,
+	};
+	for attr in attrs.iter().filter(|attr| attr.path.is_ident("actor")) {
+		match attr.parse_meta() {
+			Ok(Meta::List(list)) => {
+				for nested in list.nested {
+					match nested {
+						NestedMeta::Meta(Meta::Path(path)) if path.is_ident("no_meta") => options.meta = false,
+						NestedMeta::Meta(Meta::Path(path)) if path.is_ident("top_down") => options.top_down = true,
+						NestedMeta::Meta(Meta::Path(path)) if path.is_ident("resettable") => {
+							options.resettable = Some(path.span())
+						},
+						NestedMeta::Meta(Meta::NameValue(value)) if value.path.is_ident("order_by") => {
+							match &value.lit {
+								Lit::Str(order) if order.value() == "name" => options.order_by_name = true,
+								lit => {
+									Diagnostic::spanned(lit.span().unwrap(), Level::Error, "Expected `\"name\"`").emit()
+								},
+							}
+						},
+						NestedMeta::Meta(Meta::NameValue(value)) if value.path.is_ident("enable_field") => {
+							match &value.lit {
+								// Spanned on the attribute, so that a missing or non-`bool` field is reported there.
+								Lit::Str(field) if field.parse::<Ident>().is_ok() => {
+									options.enable_field = field.parse().ok()
+								},
+								lit => Diagnostic::spanned(lit.span().unwrap(), Level::Error, "Expected a field name")
+									.emit(),
+							}
+						},
+						NestedMeta::Meta(Meta::NameValue(value)) if value.path.is_ident("topics") => match &value.lit {
+							Lit::Str(field) if field.parse::<Ident>().is_ok() => {
+								options.topics_field = field.parse().ok()
+							},
+							lit => {
+								Diagnostic::spanned(lit.span().unwrap(), Level::Error, "Expected a field name").emit()
+							},
+						},
+						NestedMeta::Meta(Meta::Path(path)) if path.is_ident("sparse") => options.sparse = true,
+						NestedMeta::Meta(Meta::List(list)) if list.path.is_ident("handles") => {
+							options.sparse = true;
+							for nested in list.nested {
+								match nested {
+									NestedMeta::Meta(Meta::Path(path)) => options.handles.push(path),
+									nested => Diagnostic::spanned(
+										nested.span().unwrap(),
+										Level::Error,
+										"Expected a message type",
+									)
+									.emit(),
+								}
+							}
+						},
+						NestedMeta::Meta(Meta::List(list)) if list.path.is_ident("handle_order") => {
+							for nested in list.nested {
+								match nested {
+									NestedMeta::Meta(Meta::Path(path)) => options.handle_order.push(path),
+									nested => Diagnostic::spanned(
+										nested.span().unwrap(),
+										Level::Error,
+										"Expected a message type without generics",
+									)
+									.emit(),
+								}
+							}
+						},
+						nested => Diagnostic::spanned(
+							nested.span().unwrap(),
+							Level::Error,
+							"Unknown option. Expected `no_meta`, `resettable`, `order_by = \"name\"`, `enable_field = \
+							 \"...\"`, `topics = \"...\"`, `sparse`, `handles(...)`, `handle_order(...)` or `top_down`",
+						)
+						.emit(),
+					}
+				}
+			},
+			_ => Diagnostic::spanned(attr.span().unwrap(), Level::Error, "Expected `#[actor(...)]`").emit(),
+		}
+	}
+	options
+}
+
+/// Implement `Receiver<Reset, _>` by replacing the `Actor` with its `Default`, for `#[actor(resettable)]`.
+pub fn actor_reset(name: &Ident, generics: &Generics, span: Span) -> TokenStream {
+	let (_, ty_generics, where_clause) = generics.split_for_impl();
+	let mut with_root = generics.clone();
+	with_root.params.push(parse_quote!(_RootTy));
+	let (impl_generics, ..) = with_root.split_for_impl();
+	// Spanned so that a missing `Default` is reported on the attribute.
+	let default = quote_spanned! { span=> <Self as ::std::default::Default>::default() };
+	quote! {
+		impl #impl_generics send::Receiver<send::Reset, _RootTy> for #name #ty_generics #where_clause {
+			fn receive(&mut self, _: &mut send::Reset, _: send::Context<Self, _RootTy>) {
+				*self = #default;
+			}
+		}
+
+		impl #impl_generics send::Handles<send::Reset, _RootTy> for #name #ty_generics #where_clause {
+			fn handles() -> bool { true }
+		}
+	}
+}
+
+/// Implement `Handles` for the messages given with `#[actor(handles(...))]`, whose `Receiver`s are written by hand.
+pub fn actor_handles(name: &Ident, generics: &Generics, messages: &[Path]) -> TokenStream {
+	let (_, ty_generics, where_clause) = generics.split_for_impl();
+	let mut with_root = generics.clone();
+	with_root.params.push(parse_quote!(_RootTy));
+	let (impl_generics, ..) = with_root.split_for_impl();
+	quote! {
+		#(
+			impl #impl_generics send::Handles<#messages, _RootTy> for #name #ty_generics #where_clause {
+				fn handles() -> bool { true }
+			}
+		)*
 	}
 }
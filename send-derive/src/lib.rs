@@ -1,19 +1,50 @@
 #![feature(proc_macro_diagnostic)]
 
 mod actor;
+mod tagged;
 
 use proc_macro::{Diagnostic, Level};
 use proc_macro2::TokenStream;
+use quote::quote;
 use syn::{parse_macro_input, spanned::Spanned, Data, DeriveInput};
 
 /// Derive the `Actor` trait for the given struct.
 /// This automatically registers all the fields of the `struct` as `Actor`s.
-#[proc_macro_derive(Actor)]
+///
+/// Fields marked with `#[actor(skip)]` are not registered.
+/// Fields that contain the type itself, like `Vec<Self>` or `Option<Box<Self>>`, are warned about unless they are
+/// marked with `#[actor(recursive)]`.
+/// Registered fields can also be reached by their names, with `Actor::accept_path`.
+///
+/// `ActorMeta` is implemented too, unless the type is marked with `#[actor(no_meta)]`.
+/// Types marked with `#[actor(resettable)]` are replaced with their `Default` when they receive `Reset`.
+/// Named fields are visited in declaration order, or in alphabetical order with `#[actor(order_by = "name")]`.
+/// The type itself is visited after its fields, or before them with `#[actor(top_down)]` or when the `Framework` uses
+/// `set_top_down`.
+/// Structs marked with `#[actor(enable_field = "active")]` are skipped along with their sub-`Actor`s, and can't be
+/// reached by path, while their `bool` field `active` is `false`.
+/// Structs marked with `#[actor(topics = "topics")]` implement `Subscriber` with their `Topics` field `topics`, to
+/// receive the messages published to the topics it contains.
+/// Types marked with `#[actor(sparse)]` are skipped by `Framework::send`, along with their sub-`Actor`s, for messages
+/// that neither they nor their sub-`Actor`s handle. This only counts the `Receiver`s from `receive!`,
+/// `#[actor(resettable)]` and `#[counter(...)]`, so the `Receiver`s written by hand must be listed with
+/// `#[actor(handles(Message, ...))]`, which implies `sparse`.
+/// Struct fields marked with `#[counter(saturating)]` or `#[counter(wrapping)]` are updated with saturating or
+/// wrapping arithmetic by `send::Increment<T>` and `send::Decrement<T>`, where `T` is the type of the field, or by
+/// the tuple structs given with `increment = "Step"` and `decrement = "Unstep"`.
+/// Types marked with `#[actor(handle_order(Input, Physics))]` implement `HandleOrder`, so they receive `Input` before
+/// `Physics` when both are sent together with `Framework::send_tuple`.
+#[proc_macro_derive(Actor, attributes(actor, counter))]
 pub fn actor(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
 	let data = parse_macro_input!(input as DeriveInput);
-	match data.data {
-		Data::Struct(s) => actor::actor_struct(data.ident, s, data.generics),
-		Data::Enum(e) => actor::actor_enum(data.ident, e, data.generics),
+	let options = actor::type_options(&data.attrs);
+	let reset = options
+		.resettable
+		.map(|span| actor::actor_reset(&data.ident, &data.generics, span));
+	let handles = actor::actor_handles(&data.ident, &data.generics, &options.handles);
+	let actor = match data.data {
+		Data::Struct(s) => actor::actor_struct(data.ident, s, data.generics, &options),
+		Data::Enum(e) => actor::actor_enum(data.ident, e, data.generics, &options),
 		Data::Union(_) => {
 			Diagnostic::spanned(
 				data.span().unwrap(),
@@ -23,6 +54,15 @@ pub fn actor(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
 			.emit();
 			TokenStream::new()
 		},
-	}
-	.into()
+	};
+	quote! { #actor #reset #handles }.into()
+}
+
+/// Derive the `TraitTagged` trait for the given type, with the traits listed in `#[tagged(...)]`, like
+/// `#[tagged(Damageable, Flammable)]`. Every trait must be declared with `send::trait_tags!`, and implemented by the
+/// type.
+#[proc_macro_derive(TraitTagged, attributes(tagged))]
+pub fn trait_tagged(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+	let data = parse_macro_input!(input as DeriveInput);
+	tagged::trait_tagged(&data.ident, &data.generics, &data.attrs).into()
 }
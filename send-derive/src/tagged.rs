@@ -0,0 +1,36 @@
+use proc_macro::{Diagnostic, Level};
+use proc_macro2::{Ident, TokenStream};
+use quote::quote;
+use syn::{spanned::Spanned, Attribute, Generics, Meta, NestedMeta};
+
+/// Implement `TraitTagged` with the traits listed in `#[tagged(...)]`, checking that the type implements them.
+pub fn trait_tagged(name: &Ident, generics: &Generics, attrs: &[Attribute]) -> TokenStream {
+	let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+	let mut traits = Vec::new();
+	for attr in attrs.iter().filter(|attr| attr.path.is_ident("tagged")) {
+		match attr.parse_meta() {
+			Ok(Meta::List(list)) => {
+				for nested in list.nested {
+					match nested {
+						NestedMeta::Meta(Meta::Path(path)) => traits.push(path),
+						nested => Diagnostic::spanned(nested.span().unwrap(), Level::Error, "Expected a trait").emit(),
+					}
+				}
+			},
+			_ => Diagnostic::spanned(attr.span().unwrap(), Level::Error, "Expected `#[tagged(...)]`").emit(),
+		}
+	}
+	quote! {
+		impl #impl_generics send::TraitTagged for #name #ty_generics #where_clause {
+			#[inline]
+			fn trait_tags() -> u64 {
+				#({
+					// Fails to compile if the type doesn't implement the trait.
+					fn implements<T: ?Sized + #traits>() {}
+					implements::<Self>();
+				})*
+				0 #(| 1 << <dyn #traits as send::TraitTag>::BIT)*
+			}
+		}
+	}
+}
@@ -1,19 +1,101 @@
-use std::cell::RefCell;
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell, UnsafeCell};
 use std::collections::{BTreeMap, HashMap, LinkedList, VecDeque};
+use std::future::{ready, Future};
+use std::marker::PhantomData;
+use std::ops::ControlFlow;
+use std::pin::Pin;
+use std::rc::Weak;
+use std::sync::Arc;
+use std::task::Poll;
 
+#[cfg(feature = "indexmap")]
+use indexmap::{set::MutableValues, IndexMap, IndexSet};
 pub use send_derive::Actor;
 use uom::num::Num;
 use uom::si::{Dimension, Quantity, Units};
 use uom::Conversion;
 
+use crate::cow::{needs_copy, visit_copy};
 use crate::Context;
 
 /// A visitor for [`Actor`]s.
 pub trait ActorVisitor<T, R> {
 	/// Visit the [`Actor`], doing whatever on it.
+	///
+	/// Visitors that need the name of its type can get it with [`type_name`](std::any::type_name).
 	fn visit<A>(&mut self, actor: &mut A)
 	where
 		A: Actor + Receiver<T, R>;
+
+	/// Visit the [`Actor`], along with a name for it, like the name of its type. By default, this ignores the name
+	/// and calls [`visit`](Self::visit).
+	#[inline(always)]
+	fn visit_named<A>(&mut self, actor: &mut A, name: &'static str)
+	where
+		A: Actor + Receiver<T, R>,
+	{
+		let _ = name;
+		self.visit(actor)
+	}
+
+	/// Whether collections should remove the elements that [`is_removed`](Self::is_removed) returns `true` for,
+	/// after visiting them. Only the removals from [`Context::remove_self`] use this, and every collection that can
+	/// remove an element implements it.
+	#[doc(hidden)]
+	#[inline(always)]
+	fn removes(&self) -> bool { false }
+
+	/// Whether a collection should remove `actor`, if [`removes`](Self::removes) returns `true`.
+	#[doc(hidden)]
+	#[inline(always)]
+	fn is_removed<A>(&self, actor: &A) -> bool {
+		let _ = actor;
+		false
+	}
+
+	/// Edit a collection of [`Actor`]s once all its elements were visited, for
+	/// [`Framework::restructure`](crate::Framework::restructure). Every collection that can grow calls this, after
+	/// removing the elements that [`is_removed`](Self::is_removed) returns `true` for.
+	#[doc(hidden)]
+	#[inline(always)]
+	fn edit<C>(&mut self, collection: &mut C) { let _ = collection; }
+
+	/// Whether derived [`Actor`]s and tuples should skip their fields of type `A`, because nothing in them can handle
+	/// the message. Only [`Framework::send`](crate::Framework::send) uses this.
+	#[doc(hidden)]
+	#[inline(always)]
+	fn skips<A>(&self) -> bool { false }
+
+	/// Whether derived [`Actor`]s that don't use `#[actor(top_down)]` should be visited before their fields instead
+	/// of after them. Only the messages sent by a [`Framework`](crate::Framework) with
+	/// [`set_top_down`](crate::Framework::set_top_down) use this.
+	#[doc(hidden)]
+	#[inline(always)]
+	fn top_down(&self) -> bool { false }
+
+	/// Whether a derived [`Actor`] should be skipped along with its sub-[`Actor`]s, checked before visiting any of
+	/// them. Only [`Framework::send_pruned`](crate::Framework::send_pruned) uses this.
+	#[doc(hidden)]
+	#[inline(always)]
+	fn prunes<A: ?Sized>(&self, actor: &A) -> bool {
+		let _ = actor;
+		false
+	}
+
+	/// The number of messages delivered to [`Receiver`]s other than the default one during this traversal so far, or
+	/// `None` if the visitor can change [`Actor`]s in other ways, like
+	/// [`Framework::for_each_mut`](crate::Framework::for_each_mut).
+	#[doc(hidden)]
+	#[inline(always)]
+	fn handled(&self) -> Option<usize> { None }
+
+	/// Whether the visitor can change [`Actor`]s other than through their [`Receiver`]s, like
+	/// [`Framework::for_each_mut`](crate::Framework::for_each_mut), so that the copies of shared [`Actor`]s it visits
+	/// have to be kept.
+	#[doc(hidden)]
+	#[inline(always)]
+	fn mutates(&self) -> bool { true }
 }
 
 /// An [`Actor`] that can contain sub-[`Actor`]s.
@@ -28,9 +110,74 @@ pub trait ActorVisitor<T, R> {
 /// ```
 ///
 /// Use the derive macro instead of implementing this by hand.
+///
+/// The derive macro warns about fields that contain the type itself, like `Vec<Self>` or `Option<Box<Self>>`, since
+/// visiting a long chain of them can overflow the stack. Mark the ones that should still be visited with
+/// `#[actor(recursive)]`:
+/// ```
+/// # #![feature(min_specialization)]
+/// # #![feature(negative_impls)]
+/// # use send::Actor;
+/// #[derive(Actor)]
+/// struct Node {
+///     #[actor(recursive)]
+///     children: Vec<Node>,
+/// }
+///
+/// #[derive(Actor)]
+/// enum List {
+///     Cons(u32, #[actor(recursive)] Option<Box<Self>>),
+///     Nil,
+/// }
+/// ```
+/// Marking a field that doesn't contain the type itself fails to compile:
+/// ```compile_fail
+/// # #![feature(min_specialization)]
+/// # #![feature(negative_impls)]
+/// # use send::Actor;
+/// #[derive(Actor)]
+/// struct Leaf {
+///     // error: This field doesn't contain the `Actor` itself, so it can't be `recursive`
+///     #[actor(recursive)]
+///     values: Vec<u32>,
+/// }
+/// ```
+///
+/// # Safety
+/// [`accept`](Actor::accept) must only visit sub-[`Actor`]s that are disjoint from each other,
+/// and the [`NotActor`] invariant above must be upheld.
+/// [`accept_path`](Actor::accept_path) must visit at most one [`Actor`], which [`accept`](Actor::accept) would also
+/// visit.
+/// Visited [`Actor`]s must not have lifetimes shorter than `self`, since [`Actor`]s are identified by their type
+/// with its lifetimes erased.
 pub unsafe trait Actor {
 	/// Accept an [`ActorVisitor`].
 	fn accept<T, R>(&mut self, visitor: &mut impl ActorVisitor<T, R>);
+
+	/// Accept an [`ActorVisitor`] on only the [`Actor`] at `path`, where every segment is the name of a field, or an
+	/// index into a tuple or collection. An empty path is `self`.
+	/// Returns `false` without visiting anything if there is no [`Actor`] at `path`.
+	fn accept_path<T, R>(&mut self, path: &[&str], visitor: &mut impl ActorVisitor<T, R>) -> bool {
+		let _ = (path, visitor);
+		false
+	}
+}
+
+/// Split the first segment of a path into an index.
+#[inline(always)]
+fn index<'a>(path: &'a [&'a str]) -> Option<(usize, &'a [&'a str])> {
+	let (segment, rest) = path.split_first()?;
+	Some((segment.parse().ok()?, rest))
+}
+
+/// Static information about an [`Actor`], implemented by `#[derive(Actor)]` unless the type is marked with
+/// `#[actor(no_meta)]`.
+pub trait ActorMeta {
+	/// The name of the type, without its module or generic parameters.
+	const ACTOR_NAME: &'static str;
+	/// The names of the visited fields of a struct with named fields, in the order they are visited. Empty for other
+	/// types.
+	const FIELDS: &'static [&'static str];
 }
 
 /// A trait that allows an [`Actor`] to receive a message sent from another [`Actor`].
@@ -39,34 +186,252 @@ pub trait Receiver<T, R>: Sized {
 	fn receive(&mut self, message: &mut T, context: Context<Self, R>);
 }
 
+/// A trait that allows an [`Actor`] to receive a message asynchronously,
+/// with [`Framework::send_async`](crate::Framework::send_async).
+///
+/// Use [`receive!`](crate::receive) with `async (...)` instead of implementing this by hand.
+pub trait AsyncReceiver<T, R>: Sized {
+	/// Receives the message.
+	fn receive<'a>(
+		&'a mut self, message: &'a mut T, context: Context<Self, R>,
+	) -> Pin<Box<dyn Future<Output = ()> + 'a>>
+	where
+		R: 'a;
+}
+
+/// A trait that allows an [`Actor`] to read a view of fields, with
+/// [`Framework::view_broadcast`](crate::Framework::view_broadcast).
+///
+/// The view is only borrowed, and handlers only get `&self` without a [`Context`], so they can't mutate anything or
+/// send messages during the broadcast, except through interior mutability.
+pub trait ReceiverRef<T>: Sized {
+	/// Reads the view.
+	fn receive_ref(&self, view: &T);
+}
+
+/// A message that [`Actor`]s can respond to, with a [`Responder`].
+pub trait Query {
+	/// The type of the responses.
+	type Response;
+}
+
+/// A trait that allows an [`Actor`] to respond to a [`Query`], like
+/// [`Framework::send_fold`](crate::Framework::send_fold).
+///
+/// Use [`receive!`](crate::receive) with `respond (...)` instead of implementing this by hand,
+/// unless the [`Actor`] only responds some of the time.
+pub trait Responder<T: Query, R>: Sized {
+	/// Responds to the query, or returns `None` to not respond.
+	fn respond(&mut self, query: &mut T, context: Context<Self, R>) -> Option<T::Response>;
+}
+
+/// A trait that allows an [`Actor`] to receive a message by value, returning the [`Actor`] to replace it with, with
+/// [`Framework::transform_to`](crate::Framework::transform_to).
+///
+/// This is useful for state machines where every transition produces a new state.
+pub trait Transform<T, R>: Sized {
+	/// Receives the message, and returns the new [`Actor`].
+	///
+	/// `self` isn't in the tree while this runs, so any `&mut self` can be used as `from` with the `context`.
+	fn transform(self, message: &mut T, context: Context<Self, R>) -> Self;
+}
+
+/// The priority of an [`Actor`] when receiving a message with
+/// [`Framework::send_prioritized`](crate::Framework::send_prioritized).
+///
+/// [`Actor`]s have a priority of `0` by default. Override it by specializing:
+/// ```
+/// # #![feature(min_specialization)]
+/// # use send::Priority;
+///
+/// # struct Input;
+/// # struct Tick;
+///
+/// impl Priority<Tick> for Input {
+///     fn priority() -> i32 { 10 }
+/// }
+/// ```
+pub trait Priority<T> {
+	/// Get the priority. Higher priorities receive messages first.
+	fn priority() -> i32;
+}
+
+/// The position of a message type among the ones an [`Actor`] handles, for the order in which it receives the
+/// messages of a [`MessageTuple`](crate::MessageTuple) from [`Framework::send_tuple`](crate::Framework::send_tuple).
+///
+/// Messages without a position are received after the ones with a position, in tuple order, so [`Actor`]s without
+/// any receive them in tuple order. Derive it by listing message types with `#[actor(handle_order(...))]`:
+/// ```
+/// # #![feature(min_specialization)]
+/// # #![feature(negative_impls)]
+/// # use send::Actor;
+/// # struct Input;
+/// # struct Physics;
+/// #[derive(Actor)]
+/// #[actor(handle_order(Input, Physics))]
+/// struct Player;
+/// ```
+pub trait HandleOrder<T> {
+	/// Get the position, where lower positions receive their message first.
+	fn position() -> Option<usize>;
+}
+
 pub auto trait NotActor {}
 
-impl<D: Dimension + ?Sized, U: Units<V> + ?Sized, V: Num + Conversion<V> + ?Sized> NotActor for Quantity<D, U, V> {}
+impl<D: Dimension + ?Sized, U: Units<V> + ?Sized, V: Num + Conversion<V>> NotActor for Quantity<D, U, V> {}
+
+/// Check at compile time whether a type is an [`Actor`], that is, whether it un-implements [`NotActor`] like every
+/// derived [`Actor`] does. Evaluates to a `const` `bool`.
+///
+/// The type must be concrete, so this is meant for static assertions that the fields selected for
+/// [`send_with`](crate::Framework::send_with) aren't [`Actor`]s, next to the selector:
+/// ```
+/// # #![feature(min_specialization)]
+/// # #![feature(negative_impls)]
+/// # use send::{is_actor, Actor};
+/// #[derive(Actor)]
+/// struct Player {
+///     health: u32,
+///     weapon: Weapon,
+/// }
+///
+/// #[derive(Actor)]
+/// struct Weapon;
+///
+/// const _: () = assert!(!is_actor!(u32));
+/// const _: () = assert!(is_actor!(Weapon));
+/// const _: () = assert!(is_actor!(Vec<Weapon>));
+/// ```
+/// Selecting an [`Actor`] by mistake then fails to compile:
+/// ```compile_fail
+/// # #![feature(min_specialization)]
+/// # #![feature(negative_impls)]
+/// # use send::{is_actor, Actor};
+/// # #[derive(Actor)]
+/// # struct Weapon;
+/// // error: evaluation panicked: the selected field is an actor
+/// const _: () = assert!(!is_actor!(Weapon), "the selected field is an actor");
+/// ```
+#[macro_export]
+macro_rules! is_actor {
+	($ty:ty) => {{
+		#[allow(unused_imports)]
+		use $crate::ActorProbeFallback as _;
+		$crate::ActorProbe::<$ty>::IS_ACTOR
+	}};
+}
+
+/// The type behind [`is_actor!`]: its inherent `IS_ACTOR` is `false` for [`NotActor`] types, and takes precedence
+/// over the `true` of [`ActorProbeFallback`].
+#[doc(hidden)]
+pub struct ActorProbe<T: ?Sized>(PhantomData<T>);
+
+#[doc(hidden)]
+pub trait ActorProbeFallback {
+	const IS_ACTOR: bool = true;
+}
+
+impl<T: ?Sized> ActorProbeFallback for ActorProbe<T> {}
+
+impl<T: ?Sized + NotActor> ActorProbe<T> {
+	pub const IS_ACTOR: bool = false;
+}
 
 // A dummy implementation for all types.
 // Specialization will be used to override this behavior while deriving.
 unsafe impl<T> Actor for T {
 	#[inline(always)]
 	default fn accept<V, R>(&mut self, _: &mut impl ActorVisitor<V, R>) {}
+
+	#[inline(always)]
+	default fn accept_path<V, R>(&mut self, _: &[&str], _: &mut impl ActorVisitor<V, R>) -> bool { false }
 }
 
 // A dummy implementation for all types.
 // Specialization will be used to override this behavior for custom types.
 impl<M, R, T> Receiver<M, R> for T {
 	#[inline(always)]
-	default fn receive(&mut self, _: &mut M, _: Context<Self, R>) {}
+	default fn receive(&mut self, _: &mut M, context: Context<Self, R>) { context.ignore() }
+}
+
+/// Deliver a message to `actor`, returning whether it handled it, that is, whether it has a [`Receiver`] for it other
+/// than the default one.
+#[inline(always)]
+pub(crate) fn receive_handled<A, M, R>(actor: &mut A, message: &mut M, context: Context<A, R>) -> bool
+where
+	A: Receiver<M, R>,
+{
+	let ignored = Cell::new(false);
+	actor.receive(message, context.reporting(&ignored));
+	!ignored.get()
+}
+
+// A dummy implementation for all types.
+// Specialization will be used to override this behavior for custom types.
+impl<M: Query, R, T> Responder<M, R> for T {
+	#[inline(always)]
+	default fn respond(&mut self, _: &mut M, _: Context<Self, R>) -> Option<M::Response> { None }
+}
+
+// A dummy implementation for all types.
+// Specialization will be used to override this behavior for custom types.
+impl<M, R, T> AsyncReceiver<M, R> for T {
+	#[inline(always)]
+	default fn receive<'a>(
+		&'a mut self, _: &'a mut M, context: Context<Self, R>,
+	) -> Pin<Box<dyn Future<Output = ()> + 'a>>
+	where
+		R: 'a,
+	{
+		context.ignore();
+		// `Ready<()>` is zero-sized, so this doesn't allocate.
+		Box::pin(ready(()))
+	}
+}
+
+// A dummy implementation for all types.
+// Specialization will be used to override this behavior for custom types.
+impl<M, T> ReceiverRef<M> for T {
+	#[inline(always)]
+	default fn receive_ref(&self, _: &M) { VIEW_IGNORED.set(true); }
+}
+
+thread_local! {
+	/// Set by the default [`ReceiverRef`], so that [`Framework::view_broadcast`](crate::Framework::view_broadcast)
+	/// doesn't count the [`Actor`]s that ignore the view. There is no [`Context`] to report it through instead.
+	pub(crate) static VIEW_IGNORED: Cell<bool> = const { Cell::new(false) };
+}
+
+// A dummy implementation for all types.
+// Specialization will be used to override this behavior for custom types.
+impl<T, A> Priority<T> for A {
+	#[inline(always)]
+	default fn priority() -> i32 { 0 }
+}
+
+// A dummy implementation for all types.
+// Specialization will be used to override this behavior for custom types.
+impl<T, A> HandleOrder<T> for A {
+	#[inline(always)]
+	default fn position() -> Option<usize> { None }
 }
 
 // Implementations for standard library types.
 
+// Shared references can't be mutated, so there is nothing to visit.
 unsafe impl<T> Actor for &T {
 	#[inline(always)]
-	default fn accept<V, R>(&mut self, visitor: &mut impl ActorVisitor<V, R>) { self.accept(visitor) }
+	default fn accept<V, R>(&mut self, _: &mut impl ActorVisitor<V, R>) {}
 }
 
 unsafe impl<T> Actor for &mut T {
 	#[inline(always)]
-	default fn accept<V, R>(&mut self, visitor: &mut impl ActorVisitor<V, R>) { self.accept(visitor) }
+	default fn accept<V, R>(&mut self, visitor: &mut impl ActorVisitor<V, R>) { (**self).accept(visitor) }
+
+	#[inline(always)]
+	default fn accept_path<V, R>(&mut self, path: &[&str], visitor: &mut impl ActorVisitor<V, R>) -> bool {
+		(**self).accept_path(path, visitor)
+	}
 }
 
 unsafe impl<T> Actor for Option<T> {
@@ -76,6 +441,11 @@ unsafe impl<T> Actor for Option<T> {
 			v.accept(visitor)
 		}
 	}
+
+	#[inline(always)]
+	fn accept_path<V, R>(&mut self, path: &[&str], visitor: &mut impl ActorVisitor<V, R>) -> bool {
+		self.as_mut().is_some_and(|v| v.accept_path(path, visitor))
+	}
 }
 
 unsafe impl<T, E> Actor for Result<T, E> {
@@ -86,11 +456,69 @@ unsafe impl<T, E> Actor for Result<T, E> {
 			Err(v) => v.accept(visitor),
 		}
 	}
+
+	#[inline(always)]
+	fn accept_path<V, R>(&mut self, path: &[&str], visitor: &mut impl ActorVisitor<V, R>) -> bool {
+		match self.as_mut() {
+			Ok(v) => v.accept_path(path, visitor),
+			Err(v) => v.accept_path(path, visitor),
+		}
+	}
+}
+
+unsafe impl<B, C> Actor for ControlFlow<B, C> {
+	#[inline(always)]
+	fn accept<V, R>(&mut self, visitor: &mut impl ActorVisitor<V, R>) {
+		match self {
+			ControlFlow::Continue(v) => v.accept(visitor),
+			ControlFlow::Break(v) => v.accept(visitor),
+		}
+	}
+
+	#[inline(always)]
+	fn accept_path<V, R>(&mut self, path: &[&str], visitor: &mut impl ActorVisitor<V, R>) -> bool {
+		match self {
+			ControlFlow::Continue(v) => v.accept_path(path, visitor),
+			ControlFlow::Break(v) => v.accept_path(path, visitor),
+		}
+	}
+}
+
+unsafe impl<T> Actor for Poll<T> {
+	#[inline(always)]
+	fn accept<V, R>(&mut self, visitor: &mut impl ActorVisitor<V, R>) {
+		if let Poll::Ready(v) = self {
+			v.accept(visitor)
+		}
+	}
+
+	#[inline(always)]
+	fn accept_path<V, R>(&mut self, path: &[&str], visitor: &mut impl ActorVisitor<V, R>) -> bool {
+		match self {
+			Poll::Ready(v) => v.accept_path(path, visitor),
+			Poll::Pending => false,
+		}
+	}
 }
 
 unsafe impl<T> Actor for Box<T> {
 	#[inline(always)]
 	fn accept<V, R>(&mut self, visitor: &mut impl ActorVisitor<V, R>) { self.as_mut().accept(visitor); }
+
+	#[inline(always)]
+	fn accept_path<V, R>(&mut self, path: &[&str], visitor: &mut impl ActorVisitor<V, R>) -> bool {
+		self.as_mut().accept_path(path, visitor)
+	}
+}
+
+unsafe impl<T> Actor for Box<[T]> {
+	#[inline(always)]
+	fn accept<V, R>(&mut self, visitor: &mut impl ActorVisitor<V, R>) { self.as_mut().accept(visitor); }
+
+	#[inline(always)]
+	fn accept_path<V, R>(&mut self, path: &[&str], visitor: &mut impl ActorVisitor<V, R>) -> bool {
+		self.as_mut().accept_path(path, visitor)
+	}
 }
 
 unsafe impl<T> Actor for [T] {
@@ -100,6 +528,11 @@ unsafe impl<T> Actor for [T] {
 			v.accept(visitor);
 		}
 	}
+
+	#[inline(always)]
+	fn accept_path<V, R>(&mut self, path: &[&str], visitor: &mut impl ActorVisitor<V, R>) -> bool {
+		index(path).is_some_and(|(index, rest)| self.get_mut(index).is_some_and(|v| v.accept_path(rest, visitor)))
+	}
 }
 
 unsafe impl<T, const N: usize> Actor for [T; N] {
@@ -109,136 +542,752 @@ unsafe impl<T, const N: usize> Actor for [T; N] {
 			v.accept(visitor);
 		}
 	}
+
+	#[inline(always)]
+	fn accept_path<V, R>(&mut self, path: &[&str], visitor: &mut impl ActorVisitor<V, R>) -> bool {
+		index(path).is_some_and(|(index, rest)| self.get_mut(index).is_some_and(|v| v.accept_path(rest, visitor)))
+	}
 }
 
 unsafe impl<T> Actor for Vec<T> {
 	#[inline(always)]
 	fn accept<V, R>(&mut self, visitor: &mut impl ActorVisitor<V, R>) {
-		for v in self {
+		for v in self.iter_mut() {
 			v.accept(visitor);
 		}
+		if visitor.removes() {
+			self.retain(|v| !visitor.is_removed(v));
+		}
+		visitor.edit(self);
+	}
+
+	#[inline(always)]
+	fn accept_path<V, R>(&mut self, path: &[&str], visitor: &mut impl ActorVisitor<V, R>) -> bool {
+		index(path).is_some_and(|(index, rest)| self.get_mut(index).is_some_and(|v| v.accept_path(rest, visitor)))
 	}
 }
 
 unsafe impl<T> Actor for VecDeque<T> {
 	#[inline(always)]
 	fn accept<V, R>(&mut self, visitor: &mut impl ActorVisitor<V, R>) {
-		for v in self {
+		for v in self.iter_mut() {
 			v.accept(visitor);
 		}
+		if visitor.removes() {
+			self.retain(|v| !visitor.is_removed(v));
+		}
+		visitor.edit(self);
+	}
+
+	#[inline(always)]
+	fn accept_path<V, R>(&mut self, path: &[&str], visitor: &mut impl ActorVisitor<V, R>) -> bool {
+		index(path).is_some_and(|(index, rest)| self.get_mut(index).is_some_and(|v| v.accept_path(rest, visitor)))
 	}
 }
 
 unsafe impl<T> Actor for LinkedList<T> {
 	#[inline(always)]
 	fn accept<V, R>(&mut self, visitor: &mut impl ActorVisitor<V, R>) {
-		for v in self {
+		for v in self.iter_mut() {
 			v.accept(visitor);
 		}
+		if visitor.removes() {
+			// The elements are found by address, so they are checked before any of them is moved.
+			let removed: Vec<_> = self.iter().map(|v| visitor.is_removed(v)).collect();
+			if removed.contains(&true) {
+				*self = std::mem::take(self)
+					.into_iter()
+					.zip(removed)
+					.filter_map(|(v, removed)| (!removed).then_some(v))
+					.collect();
+			}
+		}
+		visitor.edit(self);
+	}
+
+	#[inline(always)]
+	fn accept_path<V, R>(&mut self, path: &[&str], visitor: &mut impl ActorVisitor<V, R>) -> bool {
+		index(path)
+			.is_some_and(|(index, rest)| self.iter_mut().nth(index).is_some_and(|v| v.accept_path(rest, visitor)))
 	}
 }
 
 unsafe impl<K, V> Actor for HashMap<K, V> {
 	#[inline(always)]
 	fn accept<T, R>(&mut self, visitor: &mut impl ActorVisitor<T, R>) {
-		for v in self {
+		for v in self.iter_mut() {
 			v.1.accept(visitor);
 		}
+		if visitor.removes() {
+			self.retain(|_, v| !visitor.is_removed(v));
+		}
+		visitor.edit(self);
 	}
 }
 
+/// Its values can't be removed with [`Context::remove_self`], since removing them needs an [`Ord`] bound on the
+/// keys.
 unsafe impl<K, V> Actor for BTreeMap<K, V> {
 	#[inline(always)]
 	fn accept<T, R>(&mut self, visitor: &mut impl ActorVisitor<T, R>) {
-		for v in self {
+		for v in self.iter_mut() {
 			v.1.accept(visitor);
 		}
+		visitor.edit(self);
+	}
+}
+
+/// Visits the values in insertion order.
+#[cfg(feature = "indexmap")]
+unsafe impl<K, V, S> Actor for IndexMap<K, V, S> {
+	#[inline(always)]
+	fn accept<T, R>(&mut self, visitor: &mut impl ActorVisitor<T, R>) {
+		for v in self.values_mut() {
+			v.accept(visitor);
+		}
+		if visitor.removes() {
+			self.retain(|_, v| !visitor.is_removed(v));
+		}
+		visitor.edit(self);
+	}
+
+	#[inline(always)]
+	fn accept_path<T, R>(&mut self, path: &[&str], visitor: &mut impl ActorVisitor<T, R>) -> bool {
+		index(path).is_some_and(|(index, rest)| {
+			self.get_index_mut(index)
+				.is_some_and(|(_, v)| v.accept_path(rest, visitor))
+		})
+	}
+}
+
+/// Visits the values in insertion order.
+///
+/// Values are mutated in place, so handlers must not change anything that affects their [`Hash`](std::hash::Hash)
+/// or [`Eq`] implementations, or the set will behave incorrectly.
+#[cfg(feature = "indexmap")]
+unsafe impl<T> Actor for IndexSet<T> {
+	#[inline(always)]
+	fn accept<V, R>(&mut self, visitor: &mut impl ActorVisitor<V, R>) {
+		for index in 0..self.len() {
+			self.get_index_mut2(index).unwrap().accept(visitor);
+		}
+		if visitor.removes() {
+			self.retain(|v| !visitor.is_removed(v));
+		}
+		visitor.edit(self);
+	}
+
+	#[inline(always)]
+	fn accept_path<V, R>(&mut self, path: &[&str], visitor: &mut impl ActorVisitor<V, R>) -> bool {
+		index(path)
+			.is_some_and(|(index, rest)| self.get_index_mut2(index).is_some_and(|v| v.accept_path(rest, visitor)))
 	}
 }
 
 unsafe impl<T> Actor for RefCell<T> {
 	#[inline(always)]
 	fn accept<V, R>(&mut self, visitor: &mut impl ActorVisitor<V, R>) { self.get_mut().accept(visitor); }
+
+	#[inline(always)]
+	fn accept_path<V, R>(&mut self, path: &[&str], visitor: &mut impl ActorVisitor<V, R>) -> bool {
+		self.get_mut().accept_path(path, visitor)
+	}
+}
+
+/// Forwards to the inner [`Actor`] through [`UnsafeCell::get_mut`], since traversal already has `&mut self`.
+///
+/// # Safety
+/// Nothing else may access the inner value through a pointer from [`UnsafeCell::get`] while the tree is being
+/// traversed, including from inside a receiver, or the inner [`Actor`] will be aliased.
+unsafe impl<T> Actor for UnsafeCell<T> {
+	#[inline(always)]
+	fn accept<V, R>(&mut self, visitor: &mut impl ActorVisitor<V, R>) { self.get_mut().accept(visitor); }
+
+	#[inline(always)]
+	fn accept_path<V, R>(&mut self, path: &[&str], visitor: &mut impl ActorVisitor<V, R>) -> bool {
+		self.get_mut().accept_path(path, visitor)
+	}
+}
+
+/// Visits the [`Actor`] only if this is the only [`Arc`] to it, since a shared one can't be mutated. It can't be
+/// copied with [`Arc::make_mut`] either, since every type is an [`Actor`], and implementations can't be specialized on
+/// [`Clone`].
+///
+/// Use [`CowArc`](crate::CowArc) to share [`Actor`]s that are copied when they need to receive messages instead, like
+/// with [`CowArc::from_arc`](crate::CowArc::from_arc).
+unsafe impl<T> Actor for Arc<T> {
+	#[inline(always)]
+	fn accept<V, R>(&mut self, visitor: &mut impl ActorVisitor<V, R>) {
+		if let Some(actor) = Arc::get_mut(self) {
+			actor.accept(visitor);
+		}
+	}
+
+	#[inline(always)]
+	fn accept_path<V, R>(&mut self, path: &[&str], visitor: &mut impl ActorVisitor<V, R>) -> bool {
+		Arc::get_mut(self).is_some_and(|actor| actor.accept_path(path, visitor))
+	}
+}
+
+/// Visits the elements only if this is the only [`Arc`] to them, like [`Arc<T>`].
+unsafe impl<T> Actor for Arc<[T]> {
+	#[inline(always)]
+	fn accept<V, R>(&mut self, visitor: &mut impl ActorVisitor<V, R>) {
+		if let Some(actors) = Arc::get_mut(self) {
+			actors.accept(visitor);
+		}
+	}
+
+	#[inline(always)]
+	fn accept_path<V, R>(&mut self, path: &[&str], visitor: &mut impl ActorVisitor<V, R>) -> bool {
+		Arc::get_mut(self).is_some_and(|actors| actors.accept_path(path, visitor))
+	}
+}
+
+/// Visits the owned [`Actor`]. A borrowed one is replaced with a copy before it is visited, and borrowed again if
+/// nothing in the copy handles the message, so it stays borrowed through messages that nothing in it handles. Like
+/// with [`CowArc`](crate::CowArc), the copy is only made if something in it can handle the message, and always kept
+/// after visitors that mutate [`Actor`]s without sending them messages.
+unsafe impl<B: ToOwned + ?Sized> Actor for Cow<'_, B> {
+	#[inline(always)]
+	fn accept<V, R>(&mut self, visitor: &mut impl ActorVisitor<V, R>) {
+		let borrowed = match self {
+			Cow::Owned(actor) => return actor.accept(visitor),
+			Cow::Borrowed(actor) => *actor,
+		};
+		if visitor.prunes(borrowed) || !needs_copy::<B::Owned, V, R>(visitor) {
+			return;
+		}
+		// The copy is visited in place, since handlers can keep its address, like to mark it dirty.
+		let ((), keep) = visit_copy(visitor, |visitor| self.to_mut().accept(visitor));
+		if !keep {
+			*self = Cow::Borrowed(borrowed);
+		}
+	}
+
+	#[inline(always)]
+	fn accept_path<V, R>(&mut self, path: &[&str], visitor: &mut impl ActorVisitor<V, R>) -> bool {
+		let borrowed = match self {
+			Cow::Owned(actor) => return actor.accept_path(path, visitor),
+			Cow::Borrowed(actor) => *actor,
+		};
+		if !needs_copy::<B::Owned, V, R>(visitor) {
+			return false;
+		}
+		let (found, keep) = visit_copy(visitor, |visitor| self.to_mut().accept_path(path, visitor));
+		if !keep {
+			*self = Cow::Borrowed(borrowed);
+		}
+		found
+	}
+}
+
+/// Visits the [`Actor`] if it is still alive, and skips it otherwise.
+///
+/// If the same [`Actor`] is also reachable through a strong path in the tree, it will receive every message twice,
+/// unless the `dedup` feature is enabled. Otherwise, only use [`Weak`] for back-edges that aren't otherwise traversed.
+/// Reaching an [`Actor`] through a [`Weak`] while it is already receiving a message panics, like
+/// [`RefCell::borrow_mut`] does.
+unsafe impl<T> Actor for Weak<RefCell<T>> {
+	#[inline(always)]
+	fn accept<V, R>(&mut self, visitor: &mut impl ActorVisitor<V, R>) {
+		if let Some(strong) = self.upgrade() {
+			(*strong.borrow_mut()).accept(visitor);
+		}
+	}
+
+	#[inline(always)]
+	fn accept_path<V, R>(&mut self, path: &[&str], visitor: &mut impl ActorVisitor<V, R>) -> bool {
+		self.upgrade()
+			.is_some_and(|strong| (*strong.borrow_mut()).accept_path(path, visitor))
+	}
 }
 
 unsafe impl<A> Actor for (A,) {
 	#[inline(always)]
-	fn accept<T, R>(&mut self, visitor: &mut impl ActorVisitor<T, R>) { self.0.accept(visitor); }
+	fn accept<T, R>(&mut self, visitor: &mut impl ActorVisitor<T, R>) {
+		if !visitor.skips::<A>() {
+			self.0.accept(visitor);
+		}
+	}
+
+	#[inline(always)]
+	fn accept_path<T, R>(&mut self, path: &[&str], visitor: &mut impl ActorVisitor<T, R>) -> bool {
+		match index(path) {
+			Some((0, rest)) => self.0.accept_path(rest, visitor),
+			_ => false,
+		}
+	}
 }
 
 unsafe impl<A, B> Actor for (A, B) {
 	#[inline(always)]
 	fn accept<T, R>(&mut self, visitor: &mut impl ActorVisitor<T, R>) {
-		self.0.accept(visitor);
-		self.1.accept(visitor);
+		if !visitor.skips::<A>() {
+			self.0.accept(visitor);
+		}
+		if !visitor.skips::<B>() {
+			self.1.accept(visitor);
+		}
+	}
+
+	#[inline(always)]
+	fn accept_path<T, R>(&mut self, path: &[&str], visitor: &mut impl ActorVisitor<T, R>) -> bool {
+		match index(path) {
+			Some((0, rest)) => self.0.accept_path(rest, visitor),
+			Some((1, rest)) => self.1.accept_path(rest, visitor),
+			_ => false,
+		}
 	}
 }
 
 unsafe impl<A, B, C> Actor for (A, B, C) {
 	#[inline(always)]
 	fn accept<T, R>(&mut self, visitor: &mut impl ActorVisitor<T, R>) {
-		self.0.accept(visitor);
-		self.1.accept(visitor);
-		self.2.accept(visitor);
+		if !visitor.skips::<A>() {
+			self.0.accept(visitor);
+		}
+		if !visitor.skips::<B>() {
+			self.1.accept(visitor);
+		}
+		if !visitor.skips::<C>() {
+			self.2.accept(visitor);
+		}
+	}
+
+	#[inline(always)]
+	fn accept_path<T, R>(&mut self, path: &[&str], visitor: &mut impl ActorVisitor<T, R>) -> bool {
+		match index(path) {
+			Some((0, rest)) => self.0.accept_path(rest, visitor),
+			Some((1, rest)) => self.1.accept_path(rest, visitor),
+			Some((2, rest)) => self.2.accept_path(rest, visitor),
+			_ => false,
+		}
 	}
 }
 
 unsafe impl<A, B, C, D> Actor for (A, B, C, D) {
 	#[inline(always)]
 	fn accept<T, R>(&mut self, visitor: &mut impl ActorVisitor<T, R>) {
-		self.0.accept(visitor);
-		self.1.accept(visitor);
-		self.2.accept(visitor);
-		self.3.accept(visitor);
+		if !visitor.skips::<A>() {
+			self.0.accept(visitor);
+		}
+		if !visitor.skips::<B>() {
+			self.1.accept(visitor);
+		}
+		if !visitor.skips::<C>() {
+			self.2.accept(visitor);
+		}
+		if !visitor.skips::<D>() {
+			self.3.accept(visitor);
+		}
+	}
+
+	#[inline(always)]
+	fn accept_path<T, R>(&mut self, path: &[&str], visitor: &mut impl ActorVisitor<T, R>) -> bool {
+		match index(path) {
+			Some((0, rest)) => self.0.accept_path(rest, visitor),
+			Some((1, rest)) => self.1.accept_path(rest, visitor),
+			Some((2, rest)) => self.2.accept_path(rest, visitor),
+			Some((3, rest)) => self.3.accept_path(rest, visitor),
+			_ => false,
+		}
 	}
 }
 
 unsafe impl<A, B, C, D, E> Actor for (A, B, C, D, E) {
 	#[inline(always)]
 	fn accept<T, R>(&mut self, visitor: &mut impl ActorVisitor<T, R>) {
-		self.0.accept(visitor);
-		self.1.accept(visitor);
-		self.2.accept(visitor);
-		self.3.accept(visitor);
-		self.4.accept(visitor);
+		if !visitor.skips::<A>() {
+			self.0.accept(visitor);
+		}
+		if !visitor.skips::<B>() {
+			self.1.accept(visitor);
+		}
+		if !visitor.skips::<C>() {
+			self.2.accept(visitor);
+		}
+		if !visitor.skips::<D>() {
+			self.3.accept(visitor);
+		}
+		if !visitor.skips::<E>() {
+			self.4.accept(visitor);
+		}
+	}
+
+	#[inline(always)]
+	fn accept_path<T, R>(&mut self, path: &[&str], visitor: &mut impl ActorVisitor<T, R>) -> bool {
+		match index(path) {
+			Some((0, rest)) => self.0.accept_path(rest, visitor),
+			Some((1, rest)) => self.1.accept_path(rest, visitor),
+			Some((2, rest)) => self.2.accept_path(rest, visitor),
+			Some((3, rest)) => self.3.accept_path(rest, visitor),
+			Some((4, rest)) => self.4.accept_path(rest, visitor),
+			_ => false,
+		}
 	}
 }
 
 unsafe impl<A, B, C, D, E, F> Actor for (A, B, C, D, E, F) {
 	#[inline(always)]
 	fn accept<T, R>(&mut self, visitor: &mut impl ActorVisitor<T, R>) {
-		self.0.accept(visitor);
-		self.1.accept(visitor);
-		self.2.accept(visitor);
-		self.3.accept(visitor);
-		self.4.accept(visitor);
-		self.5.accept(visitor);
+		if !visitor.skips::<A>() {
+			self.0.accept(visitor);
+		}
+		if !visitor.skips::<B>() {
+			self.1.accept(visitor);
+		}
+		if !visitor.skips::<C>() {
+			self.2.accept(visitor);
+		}
+		if !visitor.skips::<D>() {
+			self.3.accept(visitor);
+		}
+		if !visitor.skips::<E>() {
+			self.4.accept(visitor);
+		}
+		if !visitor.skips::<F>() {
+			self.5.accept(visitor);
+		}
+	}
+
+	#[inline(always)]
+	fn accept_path<T, R>(&mut self, path: &[&str], visitor: &mut impl ActorVisitor<T, R>) -> bool {
+		match index(path) {
+			Some((0, rest)) => self.0.accept_path(rest, visitor),
+			Some((1, rest)) => self.1.accept_path(rest, visitor),
+			Some((2, rest)) => self.2.accept_path(rest, visitor),
+			Some((3, rest)) => self.3.accept_path(rest, visitor),
+			Some((4, rest)) => self.4.accept_path(rest, visitor),
+			Some((5, rest)) => self.5.accept_path(rest, visitor),
+			_ => false,
+		}
 	}
 }
 
 unsafe impl<A, B, C, D, E, F, G> Actor for (A, B, C, D, E, F, G) {
 	#[inline(always)]
 	fn accept<T, R>(&mut self, visitor: &mut impl ActorVisitor<T, R>) {
-		self.0.accept(visitor);
-		self.1.accept(visitor);
-		self.2.accept(visitor);
-		self.3.accept(visitor);
-		self.4.accept(visitor);
-		self.5.accept(visitor);
-		self.6.accept(visitor);
+		if !visitor.skips::<A>() {
+			self.0.accept(visitor);
+		}
+		if !visitor.skips::<B>() {
+			self.1.accept(visitor);
+		}
+		if !visitor.skips::<C>() {
+			self.2.accept(visitor);
+		}
+		if !visitor.skips::<D>() {
+			self.3.accept(visitor);
+		}
+		if !visitor.skips::<E>() {
+			self.4.accept(visitor);
+		}
+		if !visitor.skips::<F>() {
+			self.5.accept(visitor);
+		}
+		if !visitor.skips::<G>() {
+			self.6.accept(visitor);
+		}
+	}
+
+	#[inline(always)]
+	fn accept_path<T, R>(&mut self, path: &[&str], visitor: &mut impl ActorVisitor<T, R>) -> bool {
+		match index(path) {
+			Some((0, rest)) => self.0.accept_path(rest, visitor),
+			Some((1, rest)) => self.1.accept_path(rest, visitor),
+			Some((2, rest)) => self.2.accept_path(rest, visitor),
+			Some((3, rest)) => self.3.accept_path(rest, visitor),
+			Some((4, rest)) => self.4.accept_path(rest, visitor),
+			Some((5, rest)) => self.5.accept_path(rest, visitor),
+			Some((6, rest)) => self.6.accept_path(rest, visitor),
+			_ => false,
+		}
 	}
 }
 
 unsafe impl<A, B, C, D, E, F, G, H> Actor for (A, B, C, D, E, F, G, H) {
 	#[inline(always)]
 	fn accept<T, R>(&mut self, visitor: &mut impl ActorVisitor<T, R>) {
-		self.0.accept(visitor);
-		self.1.accept(visitor);
-		self.2.accept(visitor);
-		self.3.accept(visitor);
-		self.4.accept(visitor);
-		self.5.accept(visitor);
-		self.6.accept(visitor);
-		self.7.accept(visitor);
+		if !visitor.skips::<A>() {
+			self.0.accept(visitor);
+		}
+		if !visitor.skips::<B>() {
+			self.1.accept(visitor);
+		}
+		if !visitor.skips::<C>() {
+			self.2.accept(visitor);
+		}
+		if !visitor.skips::<D>() {
+			self.3.accept(visitor);
+		}
+		if !visitor.skips::<E>() {
+			self.4.accept(visitor);
+		}
+		if !visitor.skips::<F>() {
+			self.5.accept(visitor);
+		}
+		if !visitor.skips::<G>() {
+			self.6.accept(visitor);
+		}
+		if !visitor.skips::<H>() {
+			self.7.accept(visitor);
+		}
 	}
+
+	#[inline(always)]
+	fn accept_path<T, R>(&mut self, path: &[&str], visitor: &mut impl ActorVisitor<T, R>) -> bool {
+		match index(path) {
+			Some((0, rest)) => self.0.accept_path(rest, visitor),
+			Some((1, rest)) => self.1.accept_path(rest, visitor),
+			Some((2, rest)) => self.2.accept_path(rest, visitor),
+			Some((3, rest)) => self.3.accept_path(rest, visitor),
+			Some((4, rest)) => self.4.accept_path(rest, visitor),
+			Some((5, rest)) => self.5.accept_path(rest, visitor),
+			Some((6, rest)) => self.6.accept_path(rest, visitor),
+			Some((7, rest)) => self.7.accept_path(rest, visitor),
+			_ => false,
+		}
+	}
+}
+
+/// A macro for implementing [`Actor`] on a newtype around a type from another crate, which can't be derived.
+///
+/// The orphan rules don't allow implementing [`Actor`] or un-implementing [`NotActor`] for foreign types, so the
+/// foreign type must be wrapped in a local type. The closure-like part lists the sub-[`Actor`]s to visit, as
+/// expressions that borrow them from the wrapper. Each of them must be disjoint from the others.
+///
+/// The wrapper itself is also visited, so it can [`receive!`](crate::receive) messages too. It is visited after the
+/// sub-[`Actor`]s, or before them when the [`Framework`](crate::Framework) uses
+/// [`set_top_down`](crate::Framework::set_top_down).
+/// ```
+/// # #![feature(min_specialization)]
+/// # #![feature(negative_impls)]
+/// # use send::{impl_actor, Actor};
+/// # mod foreign {
+/// #     pub struct Graph<N> { pub nodes: Vec<N>, pub root: N }
+/// # }
+/// #[derive(Actor)]
+/// struct Node;
+///
+/// struct MyGraph(foreign::Graph<Node>);
+///
+/// impl_actor!(MyGraph => |graph| [&mut graph.0.nodes, &mut graph.0.root]);
+/// ```
+/// Generics use the same `%(...)` syntax as [`receive!`](crate::receive):
+/// ```
+/// # #![feature(min_specialization)]
+/// # #![feature(negative_impls)]
+/// # use send::impl_actor;
+/// struct Wrapper<T>(Vec<T>);
+///
+/// impl_actor!(%(T) Wrapper<T> => |wrapper| [&mut wrapper.0]);
+/// ```
+#[macro_export]
+macro_rules! impl_actor {
+	($(%( $($generics:tt)* ))? $on:ty => |$this:ident| [$($field:expr),* $(,)?]) => {
+		unsafe impl$(<$($generics)*>)? $crate::Actor for $on {
+			#[inline]
+			fn accept<_MessageTy, _RootTy>(&mut self, visitor: &mut impl $crate::ActorVisitor<_MessageTy, _RootTy>) {
+				if visitor.prunes(self) {
+					return;
+				}
+				if visitor.top_down() {
+					visitor.visit(self);
+					$({
+						let $this = &mut *self;
+						$crate::Actor::accept($field, visitor);
+					})*
+				} else {
+					$({
+						let $this = &mut *self;
+						$crate::Actor::accept($field, visitor);
+					})*
+					visitor.visit(self);
+				}
+			}
+
+			// The sub-actors don't have names, so only the wrapper itself can be reached by a path.
+			#[inline]
+			fn accept_path<_MessageTy, _RootTy>(
+				&mut self, path: &[&str], visitor: &mut impl $crate::ActorVisitor<_MessageTy, _RootTy>,
+			) -> bool {
+				if path.is_empty() {
+					visitor.visit(self);
+				}
+				path.is_empty()
+			}
+		}
+
+		impl$(<$($generics)*>)? !$crate::NotActor for $on {}
+	};
+}
+
+/// A macro for implementing [`Actor`] on a smart-pointer-like wrapper by forwarding to the [`Actor`] it derefs to,
+/// through [`DerefMut`](std::ops::DerefMut), like [`Box`] does.
+///
+/// A blanket implementation for every [`DerefMut`](std::ops::DerefMut) type isn't possible, since the blanket
+/// default implementation of [`Actor`] can only be specialized by concrete types, not by trait bounds. This
+/// replaces the default implementation that visits nothing for the wrapper, like any other [`Actor`] implementation.
+///
+/// The wrapper itself isn't visited, and paths are resolved by the inner [`Actor`], so the wrapper is transparent.
+/// ```
+/// # #![feature(min_specialization)]
+/// # #![feature(negative_impls)]
+/// # use std::ops::{Deref, DerefMut};
+/// # use send::{impl_actor_via_deref, Actor};
+/// #[derive(Actor)]
+/// struct Node;
+///
+/// struct Tracked<T> {
+///     inner: T,
+///     generation: u64,
+/// }
+///
+/// impl<T> Deref for Tracked<T> {
+///     type Target = T;
+///
+///     fn deref(&self) -> &T { &self.inner }
+/// }
+///
+/// impl<T> DerefMut for Tracked<T> {
+///     fn deref_mut(&mut self) -> &mut T { &mut self.inner }
+/// }
+///
+/// impl_actor_via_deref!(%(T) Tracked<T>);
+/// ```
+#[macro_export]
+macro_rules! impl_actor_via_deref {
+	($(%( $($generics:tt)* ))? $on:ty) => {
+		unsafe impl$(<$($generics)*>)? $crate::Actor for $on {
+			#[inline]
+			fn accept<_MessageTy, _RootTy>(&mut self, visitor: &mut impl $crate::ActorVisitor<_MessageTy, _RootTy>) {
+				$crate::Actor::accept(::std::ops::DerefMut::deref_mut(self), visitor);
+			}
+
+			#[inline]
+			fn accept_path<_MessageTy, _RootTy>(
+				&mut self, path: &[&str], visitor: &mut impl $crate::ActorVisitor<_MessageTy, _RootTy>,
+			) -> bool {
+				$crate::Actor::accept_path(::std::ops::DerefMut::deref_mut(self), path, visitor)
+			}
+		}
+
+		impl$(<$($generics)*>)? !$crate::NotActor for $on {}
+	};
+}
+
+/// A macro for implementing [`Actor`] on many types without sub-[`Actor`]s at once, like small value types that
+/// only [`receive!`](crate::receive) messages, or newtypes around types from other crates.
+///
+/// Each type is visited itself, and can only be reached by the empty path, like a derived [`Actor`] without fields.
+/// Generics use the same `%(...)` syntax as [`receive!`](crate::receive):
+/// ```
+/// # #![feature(min_specialization)]
+/// # #![feature(negative_impls)]
+/// # use send::leaf_actors;
+/// struct Health(u32);
+/// struct Name(String);
+/// struct Tag<T>(T);
+///
+/// leaf_actors!(Health, Name, %(T) Tag<T>);
+/// ```
+#[macro_export]
+macro_rules! leaf_actors {
+	($($(%( $($generics:tt)* ))? $on:ty),* $(,)?) => {
+		$(
+			unsafe impl$(<$($generics)*>)? $crate::Actor for $on {
+				#[inline]
+				fn accept<_MessageTy, _RootTy>(&mut self, visitor: &mut impl $crate::ActorVisitor<_MessageTy, _RootTy>) {
+					if !visitor.prunes(self) {
+						visitor.visit(self);
+					}
+				}
+
+				#[inline]
+				fn accept_path<_MessageTy, _RootTy>(
+					&mut self, path: &[&str], visitor: &mut impl $crate::ActorVisitor<_MessageTy, _RootTy>,
+				) -> bool {
+					if path.is_empty() {
+						visitor.visit(self);
+					}
+					path.is_empty()
+				}
+			}
+
+			impl$(<$($generics)*>)? !$crate::NotActor for $on {}
+		)*
+	};
+}
+
+/// A macro for implementing [`Actor`] on many container types at once, by listing the fields that hold their
+/// sub-[`Actor`]s, like `#[derive(Actor)]` with the other fields skipped.
+///
+/// Fields are named, or indices for tuple structs. They are visited in the listed order, and then the container
+/// itself, or the other way around when the [`Framework`](crate::Framework) uses
+/// [`set_top_down`](crate::Framework::set_top_down). Paths use the field names, like with the derive.
+/// Generics use the same `%(...)` syntax as [`receive!`](crate::receive):
+/// ```
+/// # #![feature(min_specialization)]
+/// # #![feature(negative_impls)]
+/// # use send::{container_actors, Actor};
+/// #[derive(Actor)]
+/// struct Wheel;
+///
+/// struct Axle(Wheel, Wheel);
+/// struct Car {
+///     front: Axle,
+///     back: Axle,
+///     model: &'static str,
+/// }
+/// struct Garage<T> {
+///     cars: Vec<T>,
+/// }
+///
+/// container_actors! {
+///     Axle => [0, 1],
+///     Car => [front, back],
+///     %(T) Garage<T> => [cars],
+/// }
+/// ```
+#[macro_export]
+macro_rules! container_actors {
+	($($(%( $($generics:tt)* ))? $on:ty => [$($field:tt),* $(,)?]),* $(,)?) => {
+		$(
+			unsafe impl$(<$($generics)*>)? $crate::Actor for $on {
+				#[inline]
+				fn accept<_MessageTy, _RootTy>(&mut self, visitor: &mut impl $crate::ActorVisitor<_MessageTy, _RootTy>) {
+					if visitor.prunes(self) {
+						return;
+					}
+					if visitor.top_down() {
+						visitor.visit(self);
+						$($crate::Actor::accept(&mut self.$field, visitor);)*
+					} else {
+						$($crate::Actor::accept(&mut self.$field, visitor);)*
+						visitor.visit(self);
+					}
+				}
+
+				#[inline]
+				#[allow(unused_variables)]
+				fn accept_path<_MessageTy, _RootTy>(
+					&mut self, path: &[&str], visitor: &mut impl $crate::ActorVisitor<_MessageTy, _RootTy>,
+				) -> bool {
+					match path.split_first() {
+						None => {
+							visitor.visit(self);
+							true
+						},
+						Some((segment, rest)) => match *segment {
+							$(stringify!($field) => $crate::Actor::accept_path(&mut self.$field, rest, visitor),)*
+							_ => false,
+						},
+					}
+				}
+			}
+
+			impl$(<$($generics)*>)? !$crate::NotActor for $on {}
+		)*
+	};
 }
@@ -0,0 +1,77 @@
+//! Buffering events from another system, like an ECS, with [`EventAdapter`].
+
+use crate::{Actor, Framework};
+
+/// A buffer of messages from a system outside of the tree, like the events of an ECS, that are sent to the
+/// [`Framework`] together once per tick with [`send_batch`](Framework::send_batch).
+///
+/// ```
+/// # #![feature(min_specialization)]
+/// # #![feature(negative_impls)]
+/// # use send::prelude::*;
+/// # use send::EventAdapter;
+/// struct Collision {
+///     damage: u32,
+/// }
+///
+/// #[derive(Actor)]
+/// struct Player {
+///     health: u32,
+/// }
+///
+/// receive! {
+///     Collision => Player = (&mut self, collision, _) {
+///         self.health = self.health.saturating_sub(collision.damage);
+///     }
+/// }
+///
+/// let mut framework = Framework::new(Player { health: 100 });
+/// let mut collisions = EventAdapter::new();
+///
+/// // In the systems of the other scheduler, whenever an event happens.
+/// collisions.push(Collision { damage: 10 });
+/// collisions.push(Collision { damage: 5 });
+///
+/// // Once per tick, like at the end of the schedule.
+/// collisions.flush(&mut framework);
+/// assert_eq!(framework.get().health, 85);
+/// assert!(collisions.is_empty());
+/// ```
+pub struct EventAdapter<M> {
+	events: Vec<M>,
+}
+
+impl<M> EventAdapter<M> {
+	/// Create an empty [`EventAdapter`].
+	pub fn new() -> Self { Self { events: Vec::new() } }
+
+	/// Buffer a message until the next [`flush`](Self::flush).
+	pub fn push(&mut self, event: M) { self.events.push(event); }
+
+	/// The number of buffered messages.
+	pub fn len(&self) -> usize { self.events.len() }
+
+	/// Check if no messages are buffered.
+	pub fn is_empty(&self) -> bool { self.events.is_empty() }
+
+	/// Send every buffered message to the [`Framework`] with [`send_batch`](Framework::send_batch), in the order they
+	/// were pushed, and clear the buffer.
+	pub fn flush<R, X>(&mut self, framework: &mut Framework<R, X>)
+	where
+		R: Actor + 'static,
+		X: 'static,
+	{
+		if !self.events.is_empty() {
+			framework.send_batch(&mut self.events);
+			self.events.clear();
+		}
+	}
+}
+
+impl<M> Default for EventAdapter<M> {
+	fn default() -> Self { Self::new() }
+}
+
+impl<M> Extend<M> for EventAdapter<M> {
+	fn extend<I: IntoIterator<Item = M>>(&mut self, events: I) { self.events.extend(events); }
+}
@@ -0,0 +1,151 @@
+use crate::{Actor, ActorVisitor};
+
+/// A handle to a value in an [`Arena`].
+///
+/// Handles are never reused: once a value is removed, its handle stays invalid even if the slot is filled again.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Index {
+	slot: usize,
+	generation: u32,
+}
+
+/// A generational arena of [`Actor`]s.
+///
+/// Only occupied slots are visited, and removed slots are reused by later insertions.
+/// Use [`Context::spawn`](crate::Context::spawn) and [`Context::despawn`](crate::Context::despawn) to run the
+/// [`Mount`](crate::Mount) and [`Unmount`](crate::Unmount) lifecycle messages.
+pub struct Arena<T> {
+	slots: Vec<Slot<T>>,
+	free: Vec<usize>,
+	len: usize,
+}
+
+struct Slot<T> {
+	generation: u32,
+	value: Option<T>,
+}
+
+impl<T> Arena<T> {
+	/// Create an empty [`Arena`].
+	pub fn new() -> Self {
+		Self {
+			slots: Vec::new(),
+			free: Vec::new(),
+			len: 0,
+		}
+	}
+
+	/// Insert a value, returning its [`Index`].
+	pub fn insert(&mut self, value: T) -> Index {
+		self.len += 1;
+		match self.free.pop() {
+			Some(slot) => {
+				let entry = &mut self.slots[slot];
+				entry.value = Some(value);
+				Index {
+					slot,
+					generation: entry.generation,
+				}
+			},
+			None => {
+				self.slots.push(Slot {
+					generation: 0,
+					value: Some(value),
+				});
+				Index {
+					slot: self.slots.len() - 1,
+					generation: 0,
+				}
+			},
+		}
+	}
+
+	/// Remove a value, returning it if the [`Index`] was still valid.
+	pub fn remove(&mut self, index: Index) -> Option<T> {
+		let entry = self.slots.get_mut(index.slot)?;
+		if entry.generation != index.generation {
+			return None;
+		}
+
+		let value = entry.value.take()?;
+		entry.generation = entry.generation.wrapping_add(1);
+		self.free.push(index.slot);
+		self.len -= 1;
+		Some(value)
+	}
+
+	/// Get a reference to a value.
+	pub fn get(&self, index: Index) -> Option<&T> {
+		self.slots
+			.get(index.slot)
+			.filter(|entry| entry.generation == index.generation)
+			.and_then(|entry| entry.value.as_ref())
+	}
+
+	/// Get a mutable reference to a value.
+	pub fn get_mut(&mut self, index: Index) -> Option<&mut T> {
+		self.slots
+			.get_mut(index.slot)
+			.filter(|entry| entry.generation == index.generation)
+			.and_then(|entry| entry.value.as_mut())
+	}
+
+	/// Check if the [`Index`] refers to a value in the [`Arena`].
+	pub fn contains(&self, index: Index) -> bool { self.get(index).is_some() }
+
+	/// The number of values in the [`Arena`].
+	pub fn len(&self) -> usize { self.len }
+
+	/// Check if the [`Arena`] contains no values.
+	pub fn is_empty(&self) -> bool { self.len == 0 }
+
+	/// Iterate over all values and their [`Index`]es.
+	pub fn iter(&self) -> impl Iterator<Item = (Index, &T)> {
+		self.slots.iter().enumerate().filter_map(|(slot, entry)| {
+			entry.value.as_ref().map(|value| {
+				(
+					Index {
+						slot,
+						generation: entry.generation,
+					},
+					value,
+				)
+			})
+		})
+	}
+
+	/// Iterate mutably over all values and their [`Index`]es.
+	pub fn iter_mut(&mut self) -> impl Iterator<Item = (Index, &mut T)> {
+		self.slots.iter_mut().enumerate().filter_map(|(slot, entry)| {
+			let generation = entry.generation;
+			entry.value.as_mut().map(|value| (Index { slot, generation }, value))
+		})
+	}
+}
+
+impl<T> Default for Arena<T> {
+	fn default() -> Self { Self::new() }
+}
+
+unsafe impl<T> Actor for Arena<T> {
+	#[inline(always)]
+	fn accept<V, R>(&mut self, visitor: &mut impl ActorVisitor<V, R>) {
+		for entry in self.slots.iter_mut() {
+			if let Some(value) = entry.value.as_mut() {
+				value.accept(visitor);
+			}
+		}
+		if visitor.removes() {
+			for (slot, entry) in self.slots.iter_mut().enumerate() {
+				if entry.value.as_ref().is_some_and(|value| visitor.is_removed(value)) {
+					// Like `remove`, so that the `Index` of the removed value stays invalid.
+					entry.value = None;
+					entry.generation = entry.generation.wrapping_add(1);
+					self.free.push(slot);
+					self.len -= 1;
+				}
+			}
+		}
+		visitor.edit(self);
+	}
+}
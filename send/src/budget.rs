@@ -0,0 +1,15 @@
+/// Where a dispatch from [`Framework::send_budgeted`](crate::Framework::send_budgeted) stopped.
+///
+/// Pass it to [`Framework::send_resume`](crate::Framework::send_resume) to continue.
+/// The position is the number of [`Actor`](crate::Actor)s visited, so if [`Actor`](crate::Actor)s are added or
+/// removed before resuming, some [`Actor`](crate::Actor)s might be skipped or receive the message twice.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[must_use]
+pub struct DispatchResume {
+	pub(crate) next: Option<usize>,
+}
+
+impl DispatchResume {
+	/// Check if every [`Actor`](crate::Actor) has received the message.
+	pub fn is_done(&self) -> bool { self.next.is_none() }
+}
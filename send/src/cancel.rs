@@ -0,0 +1,23 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// A token for cancelling a dispatch from outside of it, with
+/// [`Framework::send_cancelable`](crate::Framework::send_cancelable).
+///
+/// Clones share the same state, so one can be given to another thread or to an [`Actor`](crate::Actor), and
+/// cancelling any of them cancels all of them.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken {
+	cancelled: Arc<AtomicBool>,
+}
+
+impl CancelToken {
+	/// Create a token that is not cancelled.
+	pub fn new() -> Self { Self::default() }
+
+	/// Cancel every dispatch using this token.
+	pub fn cancel(&self) { self.cancelled.store(true, Ordering::Relaxed) }
+
+	/// Check if the token was cancelled.
+	pub fn is_cancelled(&self) -> bool { self.cancelled.load(Ordering::Relaxed) }
+}
@@ -0,0 +1,73 @@
+//! Forwarding messages to other threads, with [`ChannelActor`].
+
+use std::sync::mpsc::Sender;
+
+use crate::types::type_id;
+use crate::{Actor, ActorVisitor, Context, NotActor, Receiver};
+
+/// An [`Actor`] that forwards every message of type `M` it receives to a [`Sender`], like to hand work off to a
+/// worker thread. Messages are cloned, so the handlers after it still get the original.
+///
+/// It has no sub-[`Actor`]s. Once the receiving end of the channel is dropped, messages are dropped instead, and
+/// [`is_connected`](Self::is_connected) returns `false`.
+///
+/// Messages are matched by type with their lifetimes erased, like with [`Framework::on`](crate::Framework::on), which
+/// is why [`new`](Self::new) is `unsafe`.
+pub struct ChannelActor<M> {
+	sender: Sender<M>,
+	clone: fn(&M) -> M,
+	connected: bool,
+}
+
+impl<M> ChannelActor<M> {
+	/// Create a [`ChannelActor`] that forwards messages to `sender`.
+	///
+	/// # Safety
+	/// Messages are matched by type with their lifetimes erased, so every message whose type only differs from `M`
+	/// in its lifetimes is forwarded as an `M`, like `Msg<'a>` to a `Sender<Msg<'static>>`. `M` must not have
+	/// lifetime parameters, or every message of its type that is sent must actually be an `M`.
+	pub unsafe fn new(sender: Sender<M>) -> Self
+	where
+		M: Clone + Send + 'static,
+	{
+		Self {
+			sender,
+			clone: M::clone,
+			connected: true,
+		}
+	}
+
+	/// Check if the receiving end of the channel was still there the last time a message was forwarded.
+	pub fn is_connected(&self) -> bool { self.connected }
+}
+
+unsafe impl<M> Actor for ChannelActor<M> {
+	#[inline(always)]
+	fn accept<V, R>(&mut self, visitor: &mut impl ActorVisitor<V, R>) { visitor.visit(self); }
+
+	#[inline(always)]
+	fn accept_path<V, R>(&mut self, path: &[&str], visitor: &mut impl ActorVisitor<V, R>) -> bool {
+		if path.is_empty() {
+			visitor.visit(self);
+		}
+		path.is_empty()
+	}
+}
+
+impl<M> !NotActor for ChannelActor<M> {}
+
+// `M` can't be repeated in the trait and in the type with specialization, so the message type is compared at runtime.
+impl<N, R, M> Receiver<N, R> for ChannelActor<M> {
+	#[inline(always)]
+	default fn receive(&mut self, message: &mut N, context: Context<Self, R>) {
+		if type_id::<N>() != type_id::<M>() {
+			return context.ignore();
+		}
+		if !self.connected {
+			return;
+		}
+		// SAFETY: `N` and `M` are the same type, up to lifetimes, which the caller of `new` guarantees to match.
+		let message = unsafe { &*(message as *const N as *const M) };
+		self.connected = self.sender.send((self.clone)(message)).is_ok();
+	}
+}
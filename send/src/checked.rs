@@ -0,0 +1,73 @@
+//! Borrow tracking for the `checked` feature.
+//!
+//! Every [`Context`](crate::Context) is tied to a root and to the [`Actor`](crate::Actor) that received it.
+//! With the `checked` feature, the [`Actor`](crate::Actor)s that are currently receiving are tracked, and using a
+//! [`Context`](crate::Context) with a `from` that isn't one of them panics. Passing anything other than the
+//! receiving [`Actor`](crate::Actor) as `from` would leave `self` usable while the tree is being mutated.
+//!
+//! Without the feature, all of this compiles to nothing.
+
+use std::cell::RefCell;
+
+thread_local! {
+	static RECEIVING: RefCell<Vec<(*const (), *const ())>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Marks an [`Actor`](crate::Actor) as receiving a message until dropped.
+pub(crate) struct Receiving {
+	entry: (*const (), *const ()),
+}
+
+impl Receiving {
+	#[inline(always)]
+	pub fn enter<A, R>(actor: *mut A, root: *mut R) -> Self {
+		let entry = (actor as *const (), root as *const ());
+		if cfg!(feature = "checked") {
+			RECEIVING.with(|receiving| receiving.borrow_mut().push(entry));
+		}
+		Self { entry }
+	}
+
+	/// Mark an [`Actor`](crate::Actor) that was moved out of the tree as receiving, so any `from` is allowed until
+	/// something else starts receiving.
+	#[inline(always)]
+	pub fn enter_owned<R>(root: *mut R) -> Self { Self::enter(std::ptr::null_mut::<()>(), root) }
+}
+
+impl Drop for Receiving {
+	#[inline(always)]
+	fn drop(&mut self) {
+		if cfg!(feature = "checked") {
+			RECEIVING.with(|receiving| {
+				let mut receiving = receiving.borrow_mut();
+				if let Some(index) = receiving.iter().rposition(|entry| *entry == self.entry) {
+					receiving.remove(index);
+				}
+			});
+		}
+	}
+}
+
+/// Check that `from` is receiving a message from a dispatch on `root`.
+#[inline(always)]
+#[track_caller]
+pub(crate) fn check_from<S, R>(from: *const S, root: *mut R) {
+	if cfg!(feature = "checked") {
+		let entry = (from as *const (), root as *const ());
+		let (receiving, dispatching) = RECEIVING.with(|receiving| {
+			let receiving = receiving.borrow();
+			let owned = receiving.last() == Some(&(std::ptr::null(), entry.1));
+			(
+				owned || receiving.contains(&entry),
+				receiving.iter().any(|(_, other)| *other == entry.1),
+			)
+		});
+
+		if !dispatching {
+			panic!("`Context` used outside of a dispatch on its `Framework`");
+		}
+		if !receiving {
+			panic!("`from` must be the `Actor` that received the `Context`");
+		}
+	}
+}
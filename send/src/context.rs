@@ -1,20 +1,80 @@
+use std::cell::Cell;
 use std::marker::PhantomData;
 
-use crate::{Actor, ActorVisitor, MessageVisitor, NotActor, Receiver};
+#[cfg(feature = "rand")]
+use rand_chacha::rand_core::RngCore;
+
+use crate::checked::check_from;
+use crate::dispatch::{Dispatch, Reentry};
+#[cfg(feature = "rand")]
+use crate::rng::{self, OrdinalVisitor};
+use crate::siblings::{self, SiblingVisitor};
+use crate::topics::TopicVisitor;
+use crate::types::type_id;
+use crate::{
+	Actor,
+	ActorVisitor,
+	Arena,
+	ExceptVisitor,
+	FoldVisitor,
+	Gate,
+	Index,
+	MessageVisitor,
+	Meta,
+	Mount,
+	NotActor,
+	Query,
+	Receiver,
+	Slots,
+	TopicId,
+	TypeVisitor,
+	Unmount,
+};
 
 /// A context that give you access to the [`Framework`](super::Framework) from inside an [`Actor`].
 pub struct Context<S, R> {
 	root: *mut R,
+	dispatch: *const Dispatch<R>,
+	/// Set by the default [`Receiver`], so that the one delivering the message can tell it apart from every other one,
+	/// including the ones written by hand.
+	ignored: *const Cell<bool>,
 	phantom: PhantomData<*const S>,
 }
 
 impl<S, R> Context<S, R> {
-	pub fn new(root: *mut R) -> Self {
+	pub fn new(root: *mut R) -> Self { Self::with_dispatch(root, std::ptr::null()) }
+
+	pub(crate) fn with_dispatch(root: *mut R, dispatch: *const Dispatch<R>) -> Self {
 		Self {
 			root,
+			dispatch,
+			ignored: std::ptr::null(),
+			phantom: PhantomData,
+		}
+	}
+
+	/// Reuse the context for an [`Actor`] at the same address, like the inner one of a [`Wrap`](crate::Wrap).
+	pub(crate) fn cast<T>(self) -> Context<T, R> {
+		Context {
+			root: self.root,
+			dispatch: self.dispatch,
+			ignored: self.ignored,
 			phantom: PhantomData,
 		}
 	}
+
+	/// Report to `ignored` whether the [`Receiver`] given this context is the default one.
+	#[inline(always)]
+	pub(crate) fn reporting(self, ignored: &Cell<bool>) -> Self { Self { ignored, ..self } }
+
+	/// Mark the message as not handled, like the default [`Receiver`] does.
+	#[inline(always)]
+	pub(crate) fn ignore(&self) {
+		// SAFETY: The flag outlives the delivery that this context was made for.
+		if let Some(ignored) = unsafe { self.ignored.as_ref() } {
+			ignored.set(true);
+		}
+	}
 }
 
 impl<S, R> Context<S, R>
@@ -22,19 +82,124 @@ where
 	S: 'static,
 	R: Actor,
 {
-	/// Broadcast a message to all the [`Actor`]s in the [`Framework`](super::Framework).
+	/// Check that `from` received this context, and count the message it sends until the returned guard is dropped.
+	/// See [`Framework::set_reentrancy_limit`](super::Framework::set_reentrancy_limit).
 	#[inline(always)]
-	pub fn broadcast<T>(&self, _from: &mut S, message: &mut T) {
-		let mut visitor = MessageVisitor {
-			message,
-			root: self.root,
-		};
+	fn enter(&self, from: *const S) -> Option<Reentry<'_>> {
+		check_from(from, self.root);
+		// SAFETY: The dispatch outlives every `Context` created during it.
+		unsafe { self.dispatch.as_ref() }.map(Dispatch::enter)
+	}
+
+	/// Check if `from`, the [`Actor`] that received this [`Context`], is the root of the
+	/// [`Framework`](super::Framework), like for a handler shared by several [`Actor`] types that behaves differently
+	/// at the top of the tree.
+	///
+	/// This compares the address of `from` with the address of the root, and their types too, since a sub-[`Actor`]
+	/// can share the address of its parent, like the first field of a struct.
+	pub fn is_root(&self, from: &S) -> bool {
+		from as *const S as *const () == self.root as *const () && type_id::<S>() == type_id::<R>()
+	}
+
+	/// Broadcast a message to all the [`Actor`]s in the [`Framework`](super::Framework), including `from`.
+	///
+	/// Prefer [`broadcast_others`](Self::broadcast_others) to tell everyone else, since `from` handles its own
+	/// message otherwise.
+	#[inline(always)]
+	pub fn broadcast<T>(&self, from: &mut S, message: &mut T) {
+		let _reentry = self.enter(from);
 		// SAFETY:
 		// This is safe because `from` was the only `Actor` that had a mutable reference taken to it.
 		// Since we now have a mutable reference to `from`, we can mutate the `Framework`.
-		unsafe {
-			(*self.root).accept(&mut visitor);
-		}
+		unsafe { self.broadcast_from_root(message) }
+	}
+
+	/// Broadcast a message to every [`Actor`] in the [`Framework`](super::Framework) except `from` itself.
+	/// Other [`Actor`]s of the same type still receive it.
+	#[inline(always)]
+	pub fn broadcast_others<T>(&self, from: &mut S, message: &mut T) {
+		let _reentry = self.enter(from);
+		let mut visitor = MessageVisitor::new(message, self.root, self.dispatch);
+		let mut visitor = ExceptVisitor::new(&mut visitor, from as *const S);
+		// SAFETY: Like in `broadcast`.
+		unsafe { (*self.root).accept(&mut visitor) }
+	}
+
+	/// Publish a message to the [`Actor`]s in the [`Framework`](super::Framework) that are subscribed to `topic`,
+	/// including `from`, like [`Framework::publish`](super::Framework::publish).
+	pub fn publish<T>(&self, from: &mut S, topic: TopicId, message: &mut T) {
+		let _reentry = self.enter(from);
+		let mut visitor = TopicVisitor {
+			inner: MessageVisitor::new(message, self.root, self.dispatch),
+			topic,
+		};
+		// SAFETY: Like in `broadcast`.
+		unsafe { (*self.root).accept(&mut visitor) }
+	}
+
+	/// Broadcast a [`Query`] to all the [`Actor`]s in the [`Framework`](super::Framework), and fold their responses
+	/// into a single value, like [`Framework::send_fold`](super::Framework::send_fold).
+	///
+	/// Responses are folded in the same order that [`broadcast`](Self::broadcast) delivers messages, so `f` doesn't
+	/// need to be commutative. The sending [`Actor`] can respond too, and [`Actor`]s that don't respond are skipped.
+	pub fn broadcast_fold<T, Acc>(
+		&self, from: &mut S, message: &mut T, init: Acc, f: impl FnMut(Acc, T::Response) -> Acc,
+	) -> Acc
+	where
+		T: Query,
+	{
+		let _reentry = self.enter(from);
+		let mut visitor = FoldVisitor::new(message, self.root, self.dispatch, init, f);
+		// SAFETY: Like in `broadcast`.
+		unsafe { (*self.root).accept(&mut visitor) };
+		visitor.finish()
+	}
+
+	/// Broadcast a message without reborrowing the sending [`Actor`].
+	///
+	/// # Safety
+	/// The caller must have given up every borrow of the sending [`Actor`], except for [`NotActor`] fields.
+	#[inline(always)]
+	unsafe fn broadcast_from_root<T>(&self, message: &mut T) {
+		let mut visitor = MessageVisitor::new(message, self.root, self.dispatch);
+		(*self.root).accept(&mut visitor);
+	}
+
+	/// Send a message to only the first [`Actor`] of type `A` in the [`Framework`](super::Framework), in traversal
+	/// order, wherever it is. Returns `false` if there is none.
+	///
+	/// If there might be several of them, prefer [`send_to_all_type`](Self::send_to_all_type), since which one is
+	/// first depends on the layout of the tree. The sending [`Actor`] can receive the message too.
+	pub fn send_to_first_type<T, A: 'static>(&self, from: &mut S, message: &mut T) -> bool {
+		let _reentry = self.enter(from);
+		// SAFETY: Like in `broadcast`.
+		unsafe { self.send_to_type::<T, A>(message, true) == 1 }
+	}
+
+	/// Send a message to every [`Actor`] of type `A` in the [`Framework`](super::Framework), wherever they are.
+	/// Returns the number of [`Actor`]s that received it.
+	///
+	/// The sending [`Actor`] can receive the message too.
+	pub fn send_to_all_type<T, A: 'static>(&self, from: &mut S, message: &mut T) -> usize {
+		let _reentry = self.enter(from);
+		// SAFETY: Like in `broadcast`.
+		unsafe { self.send_to_type::<T, A>(message, false) }
+	}
+
+	/// Send a message to the [`Actor`]s of type `A`, walking the whole tree.
+	///
+	/// # Safety
+	/// Like [`broadcast_from_root`](Self::broadcast_from_root).
+	unsafe fn send_to_type<T, A: 'static>(&self, message: &mut T, first: bool) -> usize {
+		let mut visitor = TypeVisitor::<T, R, A> {
+			inner: MessageVisitor::new(message, self.root, self.dispatch),
+			skip: 0,
+			first,
+			delivered: 0,
+			phantom: PhantomData,
+		};
+		(*self.root).accept(&mut visitor);
+		visitor.delivered
 	}
 
 	/// Send a message to only a specific [`Actor`].
@@ -46,13 +211,68 @@ where
 		A: Actor + Receiver<T, R>,
 		F: FnOnce(&mut S) -> &mut A,
 	{
-		let mut visitor = MessageVisitor {
-			message,
-			root: self.root,
-		};
+		let _reentry = self.enter(from);
+		let mut visitor = MessageVisitor::new(message, self.root, self.dispatch);
 		visitor.visit(getter(from))
 	}
 
+	/// Send a message to only a specific [`Actor`] outside of the one that is currently receiving, like a sibling,
+	/// without walking the tree.
+	///
+	/// The target must not overlap `from`, so it can't be `from`, one of its sub-[`Actor`]s or one of its ancestors.
+	/// This is checked in debug builds, except for zero-sized [`Actor`]s. Use [`send_to_each!`](crate::send_to_each)
+	/// to send to several of them.
+	///
+	/// `getter`: A function that takes in the root and outputs the [`Actor`] to send the message to.
+	pub fn send_sibling<T, F, A>(&self, from: &mut S, message: &mut T, getter: F)
+	where
+		A: Actor + Receiver<T, R>,
+		F: FnOnce(&mut R) -> &mut A,
+	{
+		let _reentry = self.enter(from);
+		// SAFETY: `from` is not accessed while the target is borrowed, and the target doesn't overlap it, which is
+		// checked below in debug builds.
+		let target = getter(unsafe { &mut *self.root });
+		debug_assert!(
+			!overlaps(from, target),
+			"the target of `send_sibling` overlaps the sending `Actor`"
+		);
+		let mut visitor = MessageVisitor::new(message, self.root, self.dispatch);
+		visitor.visit(target)
+	}
+
+	/// Send a message to the [`Actor`] that is currently receiving.
+	///
+	/// Unlike calling a method directly, this goes through [`Receiver`] like any other message.
+	#[inline(always)]
+	pub fn send_to_self<T>(&self, from: &mut S, message: &mut T) {
+		let _reentry = self.enter(from);
+		let mut visitor = MessageVisitor::new(message, self.root, self.dispatch);
+		visitor.visit(from)
+	}
+
+	/// Send a message to the siblings of the [`Actor`] that is currently receiving,
+	/// but not to their sub-[`Actor`]s.
+	///
+	/// The siblings of an [`Actor`] are the other [`Actor`]s whose closest [`Actor`] ancestor is the same.
+	/// Fields of a struct or enum are siblings of each other. Collections like [`Vec`] and [`Option`] are not
+	/// [`Actor`]s themselves, so the elements of a [`Vec`] field are siblings of each other and of the other fields.
+	/// If there is no ancestor, the siblings are the other roots of a multi-root
+	/// [`Framework`](super::Framework).
+	///
+	/// Siblings receive the message in traversal order. They are found before any of them receive it, so they
+	/// shouldn't add or remove sub-[`Actor`]s of the siblings after them.
+	/// Finding the siblings walks the whole tree, and every sub-tree in it once more, so this is much slower than
+	/// [`send`](Self::send).
+	pub fn send_siblings<T>(&self, from: &mut S, message: &mut T) {
+		let _reentry = self.enter(from);
+		// SAFETY: `from` is not accessed while the tree is walked, like in `broadcast`.
+		let found = unsafe { siblings::find::<S, T, R>(self.root, from) };
+		let mut visitor = SiblingVisitor::new(MessageVisitor::new(message, self.root, self.dispatch), found);
+		// SAFETY: Above.
+		unsafe { (*self.root).accept(&mut visitor) }
+	}
+
 	/// Send a message to a specific [`Actor`] and its sub-[`Actor`]s.
 	///
 	/// `getter`: A function that takes in `Self` and outputs the [`Actor`] to send the event to.
@@ -62,17 +282,43 @@ where
 		A: Actor + Receiver<T, R>,
 		F: FnOnce(&mut S) -> &mut A,
 	{
-		let mut visitor = MessageVisitor {
-			message,
-			root: self.root,
-		};
+		let _reentry = self.enter(from);
+		let mut visitor = MessageVisitor::new(message, self.root, self.dispatch);
 
 		getter(from).accept(&mut visitor);
 	}
 
+	/// Send a [`Query`] to a specific [`Actor`] and its sub-[`Actor`]s, and collect their responses.
+	///
+	/// The responses are in the same order that [`send_sub`](Self::send_sub) delivers messages, so sub-[`Actor`]s
+	/// respond before their parents, and the selected [`Actor`] responds last.
+	/// [`Actor`]s that don't respond are skipped.
+	///
+	/// `getter`: A function that takes in `Self` and outputs the [`Actor`] to send the query to.
+	pub fn send_sub_collect<T, F, A>(&self, from: &mut S, message: &mut T, getter: F) -> Vec<T::Response>
+	where
+		T: Query,
+		A: Actor,
+		F: FnOnce(&mut S) -> &mut A,
+	{
+		let _reentry = self.enter(from);
+		let collect = |mut responses: Vec<_>, response| {
+			responses.push(response);
+			responses
+		};
+		let mut visitor = FoldVisitor::new(message, self.root, self.dispatch, Vec::new(), collect);
+		getter(from).accept(&mut visitor);
+		visitor.finish()
+	}
+
 	/// Send a message that contains references to fields or sub-fields.
 	/// This sends the message to every [`Actor`] in the [`Framework`](super::Framework).
 	///
+	/// Unlike [`broadcast`](Self::broadcast), the sending [`Actor`] doesn't receive the message, since the selected
+	/// fields are still borrowed from it, and neither do the [`Actor`]s that contain it, like its parent. Other
+	/// [`Actor`]s of the same type do, like with [`broadcast_others`](Self::broadcast_others), and so do the
+	/// sub-[`Actor`]s of the sender, so only select fields that aren't in them.
+	///
 	/// `selector`: A function that selects the fields to contain in the message.  
 	/// `creator`: A function that generates the message to send.
 	pub fn broadcast_with<'a, Sel, F, C, M>(&self, from: &'a mut S, selector: Sel, creator: C)
@@ -81,8 +327,16 @@ where
 		F: 'a + NotActor,
 		C: FnOnce(F) -> M,
 	{
-		let fields = selector(unsafe { &mut *(from as *mut S) });
-		self.broadcast(from, &mut creator(fields));
+		let _reentry = self.enter(from);
+		let except = from as *const S;
+		// The selector consumes `from`, so the only borrows of it left are the selected fields, which are `NotActor`.
+		let fields = selector(from);
+		let mut message = creator(fields);
+		let mut visitor = MessageVisitor::new(&mut message, self.root, self.dispatch);
+		let mut visitor = ExceptVisitor::enclosing(&mut visitor, except);
+		// SAFETY: Above, and `from` is skipped along with the `Actor`s that contain it, so none of their `Receiver`s
+		// get `self` while the fields are borrowed.
+		unsafe { (*self.root).accept(&mut visitor) }
 	}
 
 	/// Send a message that contains references to fields or sub-fields.
@@ -98,8 +352,14 @@ where
 		C: FnOnce(F) -> M,
 		G: FnOnce(&mut S) -> &mut A,
 	{
-		let fields = selector(unsafe { &mut *(from as *mut S) });
-		self.send(from, &mut creator(fields), getter);
+		let _reentry = self.enter(from);
+		let from = from as *mut S;
+		// SAFETY: The selected fields are `NotActor`, so they are disjoint from the target `Actor`.
+		// `from` itself is not reborrowed while the message is alive, except to find the target.
+		let fields = selector(unsafe { &mut *from });
+		let message = &mut creator(fields);
+		let mut visitor = MessageVisitor::new(message, self.root, self.dispatch);
+		visitor.visit(getter(unsafe { &mut *from }));
 	}
 
 	/// Send a message that contains references to fields or sub-fields.
@@ -115,7 +375,328 @@ where
 		C: FnOnce(F) -> M,
 		G: FnOnce(&mut S) -> &mut A,
 	{
-		let fields = selector(unsafe { &mut *(from as *mut S) });
-		self.send_sub(from, &mut creator(fields), getter);
+		let _reentry = self.enter(from);
+		let from = from as *mut S;
+		// SAFETY: Above.
+		let fields = selector(unsafe { &mut *from });
+		let message = &mut creator(fields);
+		let mut visitor = MessageVisitor::new(message, self.root, self.dispatch);
+		getter(unsafe { &mut *from }).accept(&mut visitor);
+	}
+
+	/// Queue a message to be broadcast once the current dispatch from the [`Framework`](super::Framework) is done.
+	///
+	/// All queued messages of the same type are delivered in a single traversal of the tree,
+	/// instead of walking the tree once per message like [`broadcast`](Self::broadcast) does.
+	/// Every [`Actor`] receives them in the order they were queued.
+	///
+	/// Panics if this [`Context`] was not created by a [`Framework`](super::Framework).
+	pub fn queue_broadcast<T: 'static>(&self, message: T) {
+		// SAFETY: The dispatch outlives every `Context` created during it.
+		let dispatch = unsafe { self.dispatch.as_ref() };
+		dispatch
+			.expect("`queue_broadcast` requires a `Context` created by a `Framework`")
+			.queue(message);
+	}
+
+	/// Queue a message for a specific [`Actor`], to be delivered once the current dispatch from the
+	/// [`Framework`](super::Framework) is done, like to break a feedback loop between two [`Actor`]s without
+	/// re-entering the target now.
+	///
+	/// `getter` takes in the root and outputs the [`Actor`] to send the message to. It is only called when the
+	/// message is delivered, so it finds the [`Actor`] in the tree as it is then.
+	/// Queued messages are delivered in the order they were queued, except that every broadcast from
+	/// [`queue_broadcast`](Self::queue_broadcast) is delivered along with the first one of the same type. Messages
+	/// queued while delivering them are delivered afterwards.
+	///
+	/// Panics if this [`Context`] was not created by a [`Framework`](super::Framework).
+	pub fn defer_to<T, F, A>(&self, getter: F, message: T)
+	where
+		T: 'static,
+		F: FnOnce(&mut R) -> &mut A + 'static,
+		A: Actor + Receiver<T, R>,
+		R: 'static,
+	{
+		// SAFETY: The dispatch outlives every `Context` created during it.
+		let dispatch = unsafe { self.dispatch.as_ref() };
+		dispatch
+			.expect("`defer_to` requires a `Context` created by a `Framework`")
+			.queue_to(getter, message);
+	}
+
+	/// Remove `from` from the collection that contains it, like an entity that died, once the current dispatch from
+	/// the [`Framework`](super::Framework) is done, after the broadcasts queued with
+	/// [`queue_broadcast`](Self::queue_broadcast). It keeps receiving messages until then.
+	///
+	/// `from` must be an element of a collection, like a [`Vec`], a [`HashMap`](std::collections::HashMap), an
+	/// [`Arena`](crate::Arena) or [`Slots`](crate::Slots), and not wrapped in anything else. It is dropped without
+	/// receiving [`Unmount`]. It is found again by its address, so the collection must not be reallocated before the
+	/// dispatch is done, like by pushing to it.
+	///
+	/// Panics if this [`Context`] was not created by a [`Framework`](super::Framework). The dispatch panics when it is
+	/// done if `from` isn't found in a collection that can remove it, like an array or a
+	/// [`BTreeMap`](std::collections::BTreeMap).
+	pub fn remove_self(&self, from: &mut S) {
+		check_from(from, self.root);
+		// SAFETY: The dispatch outlives every `Context` created during it.
+		let dispatch = unsafe { self.dispatch.as_ref() };
+		dispatch
+			.expect("`remove_self` requires a `Context` created by a `Framework`")
+			.remove(from as *const S);
+	}
+
+	/// Get the resources of the [`Framework`](super::Framework), from
+	/// [`Framework::with_resources`](super::Framework::with_resources).
+	///
+	/// The resources are not part of the tree, so they can be borrowed alongside `self`. This borrows the
+	/// [`Context`], so no messages can be sent while the resources are borrowed.
+	///
+	/// Panics if `X` is not the type of the resources, or if this [`Context`] was not created by a
+	/// [`Framework`](super::Framework).
+	pub fn resources<X: 'static>(&mut self) -> &mut X {
+		// SAFETY: The dispatch outlives every `Context` created during it.
+		let dispatch = unsafe { self.dispatch.as_ref() };
+		let resources = dispatch
+			.expect("`resources` requires a `Context` created by a `Framework`")
+			.resources();
+		// SAFETY: The resources are only reachable through a `Context`, which stays borrowed while they are, so no
+		// other handler can run until the borrow ends. The `Framework` itself is borrowed for the whole dispatch.
+		unsafe { &mut *resources }
+			.downcast_mut()
+			.expect("`X` is not the type of the resources")
+	}
+
+	/// Get the scratch buffer lent to this dispatch by
+	/// [`Framework::send_with_scratch`](super::Framework::send_with_scratch).
+	///
+	/// Every handler gets the same buffer, so it should be cleared before being reused. Like the resources, it
+	/// borrows the [`Context`], and it must not be kept past the handler.
+	///
+	/// Panics if `B` is not the type of the scratch buffer, or if there is none.
+	pub fn scratch<B: 'static>(&mut self) -> &mut B {
+		// SAFETY: The dispatch outlives every `Context` created during it.
+		let dispatch = unsafe { self.dispatch.as_ref() };
+		let scratch = dispatch
+			.map(|dispatch| dispatch.scratch())
+			.filter(|scratch| !scratch.is_null())
+			.expect("`scratch` requires a `Context` from `Framework::send_with_scratch`");
+		// SAFETY: Like in `resources`, and the buffer is borrowed by `send_with_scratch` for the whole dispatch.
+		unsafe { &mut *scratch }
+			.downcast_mut()
+			.expect("`B` is not the type of the scratch buffer")
+	}
+
+	/// Get the metadata of the message that started this dispatch, if it was sent with
+	/// [`Framework::send_meta`](super::Framework::send_meta).
+	pub fn meta(&self) -> Option<Meta> {
+		// SAFETY: The dispatch outlives every `Context` created during it.
+		unsafe { self.dispatch.as_ref() }.and_then(Dispatch::meta)
+	}
+
+	/// Ask [`Framework::send`](super::Framework::send) to send the message that started this dispatch again, once
+	/// every [`Actor`] has received it, like to lay out again after a handler invalidated the layout.
+	///
+	/// The message is sent again as it was left by the handlers, until a pass doesn't request it anymore, up to the
+	/// limit set with [`Framework::set_repeat_limit`](super::Framework::set_repeat_limit). Only
+	/// [`send`](super::Framework::send) and the methods built on it, like
+	/// [`send_meta`](super::Framework::send_meta), repeat messages: other dispatches ignore this.
+	pub fn request_repeat(&self) {
+		// SAFETY: The dispatch outlives every `Context` created during it.
+		if let Some(dispatch) = unsafe { self.dispatch.as_ref() } {
+			dispatch.request_repeat();
+		}
+	}
+
+	/// Mark `from` as changed by the message, for [`Framework::send_dirty`](super::Framework::send_dirty), like to
+	/// only redraw the parts of a user interface that changed. Other dispatches ignore this.
+	pub fn mark_dirty(&self, from: &mut S) {
+		check_from(from, self.root);
+		// SAFETY: The dispatch outlives every `Context` created during it.
+		if let Some(dispatch) = unsafe { self.dispatch.as_ref() } {
+			dispatch.mark_dirty(from as *const S);
+		}
+	}
+
+	/// Get the logical time of the [`Framework`](super::Framework), from [`Framework::tick`](super::Framework::tick).
+	///
+	/// It is read when the dispatch starts, so every handler in it sees the same time, including the ones for
+	/// broadcasts. Returns `0` if this [`Context`] was not created by a [`Framework`](super::Framework).
+	pub fn now(&self) -> u64 {
+		// SAFETY: The dispatch outlives every `Context` created during it.
+		unsafe { self.dispatch.as_ref() }.map_or(0, Dispatch::time)
+	}
+
+	/// Get a random number generator for `from` that is deterministic across runs, like for simulations that need
+	/// to be replayed.
+	///
+	/// It is derived from the seed set with [`Framework::set_seed`](super::Framework::set_seed), the type of `from`
+	/// and its position among the [`Actor`]s of that type, and the number of dispatches that the
+	/// [`Framework`](super::Framework) started before this one, like with [`send`](super::Framework::send). So
+	/// sending the same messages to the same tree gives every [`Actor`] the same numbers, but a new sequence in every
+	/// dispatch.
+	///
+	/// The generator doesn't advance between calls in the same dispatch, so keep it for the whole handler instead of
+	/// calling this again. This walks the tree to find the position of `from`.
+	#[cfg(feature = "rand")]
+	pub fn rng(&self, from: &mut S) -> impl RngCore {
+		check_from(from, self.root);
+		let mut visitor = OrdinalVisitor::new(from as *const S);
+		// SAFETY: Like in `broadcast_others`, `from` is not accessed while the tree is visited.
+		unsafe { (*self.root).accept::<(), R>(&mut visitor) };
+		// SAFETY: The dispatch outlives every `Context` created during it.
+		let (seed, number) = unsafe { self.dispatch.as_ref() }.map_or((0, 0), Dispatch::seed);
+		rng::generator(
+			seed,
+			number,
+			std::any::type_name::<S>(),
+			visitor.ordinal().unwrap_or_default(),
+		)
+	}
+
+	/// Insert an [`Actor`] into an [`Arena`], and send [`Mount`] to it and its sub-[`Actor`]s.
+	///
+	/// `getter`: A function that takes in `Self` and outputs the [`Arena`] to insert into.
+	pub fn spawn<T, F>(&self, from: &mut S, getter: F, actor: T) -> Index
+	where
+		F: FnOnce(&mut S) -> &mut Arena<T>,
+	{
+		let _reentry = self.enter(from);
+		let arena = getter(from);
+		let index = arena.insert(actor);
+		let mut message = Mount;
+		let mut visitor = MessageVisitor::new(&mut message, self.root, self.dispatch);
+		arena.get_mut(index).unwrap().accept(&mut visitor);
+		index
+	}
+
+	/// Send [`Unmount`] to an [`Actor`] in an [`Arena`] and its sub-[`Actor`]s, and then remove it.
+	/// Returns the removed [`Actor`], or `None` if `index` was invalid.
+	///
+	/// `getter`: A function that takes in `Self` and outputs the [`Arena`] to remove from.
+	pub fn despawn<T, F>(&self, from: &mut S, getter: F, index: Index) -> Option<T>
+	where
+		F: FnOnce(&mut S) -> &mut Arena<T>,
+	{
+		let _reentry = self.enter(from);
+		let arena = getter(from);
+		let mut message = Unmount;
+		let mut visitor = MessageVisitor::new(&mut message, self.root, self.dispatch);
+		arena.get_mut(index)?.accept(&mut visitor);
+		arena.remove(index)
+	}
+
+	/// Insert an [`Actor`] into the first free slot of a [`Slots`], and send [`Mount`] to it and its sub-[`Actor`]s.
+	/// Returns the actor back without sending anything if every slot is occupied.
+	///
+	/// `getter`: A function that takes in `Self` and outputs the [`Slots`] to insert into.
+	pub fn spawn_slot<T, F, const N: usize>(&self, from: &mut S, getter: F, actor: T) -> Result<usize, T>
+	where
+		F: FnOnce(&mut S) -> &mut Slots<T, N>,
+	{
+		let _reentry = self.enter(from);
+		let slots = getter(from);
+		let index = slots.insert(actor)?;
+		let mut message = Mount;
+		let mut visitor = MessageVisitor::new(&mut message, self.root, self.dispatch);
+		slots.get_mut(index).unwrap().accept(&mut visitor);
+		Ok(index)
+	}
+
+	/// Send [`Unmount`] to an [`Actor`] in a [`Slots`] and its sub-[`Actor`]s, and then remove it.
+	/// Returns the removed [`Actor`], or `None` if the slot was empty.
+	///
+	/// `getter`: A function that takes in `Self` and outputs the [`Slots`] to remove from.
+	pub fn despawn_slot<T, F, const N: usize>(&self, from: &mut S, getter: F, index: usize) -> Option<T>
+	where
+		F: FnOnce(&mut S) -> &mut Slots<T, N>,
+	{
+		let _reentry = self.enter(from);
+		let slots = getter(from);
+		let mut message = Unmount;
+		let mut visitor = MessageVisitor::new(&mut message, self.root, self.dispatch);
+		slots.get_mut(index)?.accept(&mut visitor);
+		slots.remove(index)
+	}
+
+	/// Open a [`Gate`], and send [`Mount`] to its [`Actor`] and its sub-[`Actor`]s.
+	/// Returns `false` without sending anything if it was already open.
+	///
+	/// `getter`: A function that takes in `Self` and outputs the [`Gate`] to open.
+	pub fn open_gate<T, F>(&self, from: &mut S, getter: F) -> bool
+	where
+		F: FnOnce(&mut S) -> &mut Gate<T>,
+	{
+		let _reentry = self.enter(from);
+		let gate = getter(from);
+		if gate.is_open() {
+			return false;
+		}
+		gate.set_open(true);
+		let mut message = Mount;
+		let mut visitor = MessageVisitor::new(&mut message, self.root, self.dispatch);
+		gate.accept(&mut visitor);
+		true
 	}
+
+	/// Send [`Unmount`] to the [`Actor`] in a [`Gate`] and its sub-[`Actor`]s, and then close it.
+	/// Returns `false` without sending anything if it was already closed.
+	///
+	/// `getter`: A function that takes in `Self` and outputs the [`Gate`] to close.
+	pub fn close_gate<T, F>(&self, from: &mut S, getter: F) -> bool
+	where
+		F: FnOnce(&mut S) -> &mut Gate<T>,
+	{
+		let _reentry = self.enter(from);
+		let gate = getter(from);
+		if !gate.is_open() {
+			return false;
+		}
+		let mut message = Unmount;
+		let mut visitor = MessageVisitor::new(&mut message, self.root, self.dispatch);
+		gate.accept(&mut visitor);
+		gate.set_open(false);
+		true
+	}
+}
+
+/// Check if the memory of `a` and `b` overlaps.
+fn overlaps<A, B>(a: &A, b: &B) -> bool {
+	let a = a as *const A as usize;
+	let b = b as *const B as usize;
+	a < b + std::mem::size_of::<B>() && b < a + std::mem::size_of::<A>()
+}
+
+/// Send the same message to several specific [`Actor`]s outside of the sender, one after the other, like a
+/// coordinator notifying some of its peers.
+///
+/// This calls [`Context::send_sibling`] with every getter, in order, so every target must be disjoint from the
+/// sender, but the targets can have different types. Every target has handled the message before the next getter
+/// runs, so their borrows never overlap.
+/// ```
+/// # #![feature(min_specialization)]
+/// # #![feature(negative_impls)]
+/// # use send::prelude::*;
+/// # use send::send_to_each;
+/// # #[derive(Actor)]
+/// # struct Coordinator { coordinated: bool }
+/// # #[derive(Actor)]
+/// # struct Peer;
+/// # struct Start;
+/// # struct Notify;
+/// type Root = (Coordinator, Peer, Peer, Peer);
+///
+/// impl Receiver<Start, Root> for Coordinator {
+///     fn receive(&mut self, _: &mut Start, context: Context<Self, Root>) {
+///         self.coordinated = true;
+///         // Only the first and last peers are notified, in that order.
+///         send_to_each!(context, self, &mut Notify, |root| &mut root.1, |root| &mut root.3);
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! send_to_each {
+	($context:expr, $from:expr, $message:expr, $($getter:expr),+ $(,)?) => {{
+		let (context, from, message) = (&$context, $from, $message);
+		$(context.send_sibling(from, message, $getter);)+
+	}};
 }
@@ -0,0 +1,12 @@
+/// Adds its amount to every field marked with `#[counter(...)]` of the same type, when deriving
+/// [`Actor`](crate::Actor), like `Increment(1u32)` for `u32` counters.
+///
+/// `#[counter(saturating)]` stops at the maximum value, and `#[counter(wrapping)]` wraps around to the minimum.
+pub struct Increment<T>(pub T);
+
+/// Subtracts its amount from every field marked with `#[counter(...)]` of the same type, when deriving
+/// [`Actor`](crate::Actor), like `Decrement(1u32)` for `u32` counters.
+///
+/// `#[counter(saturating)]` stops at the minimum value, like `0` for unsigned counters, and `#[counter(wrapping)]`
+/// wraps around to the maximum.
+pub struct Decrement<T>(pub T);
@@ -0,0 +1,151 @@
+//! Which [`Actor`](crate::Actor) types can contain a [`Receiver`](crate::Receiver) for a message, so that
+//! [`Framework::send`](crate::Framework::send) can skip the sub-trees that can't handle it at all.
+//!
+//! This only depends on the types in the tree, not on what they contain, so it is computed once per type and message
+//! type. It is derived from the registrations of [`receive!`](crate::receive) and the derive macro, and only for the
+//! types marked with `#[actor(sparse)]`, which promise that they have no other [`Receiver`](crate::Receiver)s. Every
+//! other type is assumed to handle everything.
+
+use std::any::TypeId;
+use std::cell::{RefCell, UnsafeCell};
+use std::collections::{BTreeMap, HashMap, LinkedList, VecDeque};
+use std::ops::ControlFlow;
+use std::rc::Weak;
+use std::sync::Arc;
+use std::task::Poll;
+
+#[cfg(feature = "indexmap")]
+use indexmap::{IndexMap, IndexSet};
+
+use crate::types::type_id;
+use crate::CowArc;
+
+/// Whether an [`Actor`](crate::Actor) has a [`Receiver`](crate::Receiver) for `M` that isn't the default one.
+/// Implemented by [`receive!`](crate::receive), and by the derive macro for `#[actor(resettable)]`, `#[counter(...)]`
+/// and `#[actor(handles(...))]`.
+#[doc(hidden)]
+pub trait Handles<M, R> {
+	fn handles() -> bool;
+}
+
+impl<M, R, T> Handles<M, R> for T {
+	#[inline(always)]
+	default fn handles() -> bool { false }
+}
+
+/// Whether an [`Actor`](crate::Actor) or one of its sub-[`Actor`](crate::Actor)s of any type can have a
+/// [`Receiver`](crate::Receiver) for `M` that isn't the default one. Derived for `#[actor(sparse)]`.
+#[doc(hidden)]
+pub trait Covers<M, R> {
+	/// `visiting` holds the derived types whose fields are being checked, to stop at recursive types.
+	fn covers(visiting: &mut Vec<TypeId>) -> bool;
+}
+
+// A conservative implementation for all types, which might have sub-`Actor`s that handle anything.
+impl<M, R, T> Covers<M, R> for T {
+	#[inline(always)]
+	default fn covers(_: &mut Vec<TypeId>) -> bool { true }
+}
+
+/// Check if a derived [`Actor`](crate::Actor) handles `M` itself or has `fields` that do, stopping if `T` is already
+/// being checked further up, since its own fields are already checked there.
+#[doc(hidden)]
+pub fn covers_fields<T, M, R>(visiting: &mut Vec<TypeId>, fields: impl FnOnce(&mut Vec<TypeId>) -> bool) -> bool {
+	if <T as Handles<M, R>>::handles() {
+		return true;
+	}
+	let id = type_id::<T>();
+	if visiting.contains(&id) {
+		return false;
+	}
+	visiting.push(id);
+	let covers = fields(visiting);
+	visiting.pop();
+	covers
+}
+
+// Types that can't have sub-`Actor`s, and are never visited themselves.
+macro_rules! leaf {
+	($($ty:ty),* $(,)?) => {
+		$(
+			impl<M, R> Covers<M, R> for $ty {
+				#[inline(always)]
+				fn covers(_: &mut Vec<TypeId>) -> bool { false }
+			}
+		)*
+	};
+}
+
+leaf!(bool, char, u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64, String);
+
+// Shared references are never visited.
+impl<M, R, T> Covers<M, R> for &T {
+	#[inline(always)]
+	fn covers(_: &mut Vec<TypeId>) -> bool { false }
+}
+
+// Containers that only visit their elements, not themselves.
+macro_rules! forward {
+	($(%($($generics:tt)*) $ty:ty => $($inner:ty),+;)*) => {
+		$(
+			impl<M, R, $($generics)*> Covers<M, R> for $ty {
+				#[inline(always)]
+				fn covers(visiting: &mut Vec<TypeId>) -> bool {
+					$(<$inner as Covers<M, R>>::covers(visiting))||+
+				}
+			}
+		)*
+	};
+}
+
+forward! {
+	%(T) &mut T => T;
+	%(T) Option<T> => T;
+	%(T, E) Result<T, E> => T, E;
+	%(B, C) ControlFlow<B, C> => B, C;
+	%(T) Poll<T> => T;
+	%(T) Box<T> => T;
+	%(T) Box<[T]> => T;
+	%(T, const N: usize) [T; N] => T;
+	%(T) Vec<T> => T;
+	%(T) VecDeque<T> => T;
+	%(T) LinkedList<T> => T;
+	%(K, V) HashMap<K, V> => V;
+	%(K, V) BTreeMap<K, V> => V;
+	%(T) RefCell<T> => T;
+	%(T) UnsafeCell<T> => T;
+	%(T) Arc<T> => T;
+	%(T) CowArc<T> => T;
+	%(T) Arc<[T]> => T;
+	%(T) Weak<RefCell<T>> => T;
+	%(A) (A,) => A;
+	%(A, B) (A, B) => A, B;
+	%(A, B, C) (A, B, C) => A, B, C;
+	%(A, B, C, D) (A, B, C, D) => A, B, C, D;
+	%(A, B, C, D, E) (A, B, C, D, E) => A, B, C, D, E;
+	%(A, B, C, D, E, F) (A, B, C, D, E, F) => A, B, C, D, E, F;
+	%(A, B, C, D, E, F, G) (A, B, C, D, E, F, G) => A, B, C, D, E, F, G;
+	%(A, B, C, D, E, F, G, H) (A, B, C, D, E, F, G, H) => A, B, C, D, E, F, G, H;
+}
+
+#[cfg(feature = "indexmap")]
+forward! {
+	%(K, V, S) IndexMap<K, V, S> => V;
+	%(T) IndexSet<T> => T;
+}
+
+/// The [`Covers`] of every type checked so far, for one message type.
+#[derive(Default)]
+pub(crate) struct Coverage(RefCell<HashMap<TypeId, bool>>);
+
+impl Coverage {
+	/// Check if an [`Actor`](crate::Actor) of type `A` can contain a [`Receiver`](crate::Receiver) for `M`.
+	#[inline(always)]
+	pub fn covers<A, M, R>(&self) -> bool {
+		*self
+			.0
+			.borrow_mut()
+			.entry(type_id::<A>())
+			.or_insert_with(|| <A as Covers<M, R>>::covers(&mut Vec::new()))
+	}
+}
@@ -0,0 +1,121 @@
+use std::ops::Deref;
+use std::sync::Arc;
+
+use crate::{Actor, ActorVisitor, Covers, HandledVisitor};
+
+/// A shared [`Actor`] that is cloned when it needs to receive a message, like a large configuration that many
+/// [`Actor`]s share but rarely change.
+///
+/// Cloning a [`CowArc`] only clones the [`Arc`]. When a message reaches a shared [`CowArc`], it is replaced with a
+/// copy that receives the message, and shared again if nothing in the copy handles it. So mutating messages detach it
+/// from the other [`CowArc`]s, which will not see the changes, while other messages leave it shared. If the
+/// [`Actor`] is derived with `#[actor(sparse)]`, the messages that nothing in it handles don't even copy it, see
+/// [`Covers`].
+///
+/// Visitors that mutate [`Actor`]s without sending them messages, like
+/// [`Framework::for_each_mut`](crate::Framework::for_each_mut), always detach a shared one, and keep the copy.
+pub struct CowArc<T> {
+	arc: Arc<T>,
+	clone: fn(&T) -> T,
+}
+
+impl<T: Clone> CowArc<T> {
+	/// Create a [`CowArc`] around an [`Actor`].
+	pub fn new(actor: T) -> Self { Self::from_arc(Arc::new(actor)) }
+
+	/// Create a [`CowArc`] from an [`Arc`], keeping it shared with the other [`Arc`]s to the [`Actor`].
+	pub fn from_arc(arc: Arc<T>) -> Self { Self { arc, clone: T::clone } }
+}
+
+impl<T> CowArc<T> {
+	/// Get the [`Arc`], like to check if it is still shared.
+	pub fn as_arc(&self) -> &Arc<T> { &self.arc }
+
+	/// Get a mutable reference to the [`Actor`], cloning it if it is shared.
+	pub fn make_mut(&mut self) -> &mut T {
+		if Arc::get_mut(&mut self.arc).is_none() {
+			self.arc = Arc::new((self.clone)(&self.arc));
+		}
+		Arc::get_mut(&mut self.arc).unwrap()
+	}
+
+	/// Get the [`Arc`] back.
+	pub fn into_arc(self) -> Arc<T> { self.arc }
+}
+
+impl<T> Clone for CowArc<T> {
+	fn clone(&self) -> Self {
+		Self {
+			arc: self.arc.clone(),
+			clone: self.clone,
+		}
+	}
+}
+
+impl<T> Deref for CowArc<T> {
+	type Target = T;
+
+	fn deref(&self) -> &T { &self.arc }
+}
+
+impl<T> CowArc<T> {
+	/// Replace a shared [`Arc`] with one to a copy of the [`Actor`], and return the shared one.
+	fn detach(&mut self) -> Arc<T> {
+		let copy = Arc::new((self.clone)(&self.arc));
+		std::mem::replace(&mut self.arc, copy)
+	}
+}
+
+unsafe impl<T> Actor for CowArc<T> {
+	#[inline(always)]
+	fn accept<V, R>(&mut self, visitor: &mut impl ActorVisitor<V, R>) {
+		if let Some(actor) = Arc::get_mut(&mut self.arc) {
+			actor.accept(visitor);
+			return;
+		}
+		if visitor.prunes(&*self.arc) || !needs_copy::<T, V, R>(visitor) {
+			return;
+		}
+		// The copy is visited in place, since handlers can keep its address, like to mark it dirty.
+		let shared = self.detach();
+		let ((), keep) = visit_copy(visitor, |visitor| self.make_mut().accept(visitor));
+		if !keep {
+			self.arc = shared;
+		}
+	}
+
+	#[inline(always)]
+	fn accept_path<V, R>(&mut self, path: &[&str], visitor: &mut impl ActorVisitor<V, R>) -> bool {
+		if let Some(actor) = Arc::get_mut(&mut self.arc) {
+			return actor.accept_path(path, visitor);
+		}
+		if !needs_copy::<T, V, R>(visitor) {
+			return false;
+		}
+		let shared = self.detach();
+		let (found, keep) = visit_copy(visitor, |visitor| self.make_mut().accept_path(path, visitor));
+		if !keep {
+			self.arc = shared;
+		}
+		found
+	}
+}
+
+/// Whether a shared [`Actor`] of type `T` has to be copied to be visited, unless nothing in it can handle the message
+/// that `visitor` delivers, according to [`Covers`].
+#[inline(always)]
+pub(crate) fn needs_copy<T, M, R>(visitor: &impl ActorVisitor<M, R>) -> bool {
+	visitor.handled().is_none() || !visitor.skips::<T>() && <T as Covers<M, R>>::covers(&mut Vec::new())
+}
+
+/// Visit the copy of a shared [`Actor`] with `visit`, and check if the copy has to be kept, because something in it
+/// handled the message, or because the visitor can change it in other ways.
+#[inline(always)]
+pub(crate) fn visit_copy<M, R, V, O>(visitor: &mut V, visit: impl FnOnce(&mut HandledVisitor<'_, V>) -> O) -> (O, bool)
+where
+	V: ActorVisitor<M, R>,
+{
+	let mut visitor = HandledVisitor::new(visitor);
+	let output = visit(&mut visitor);
+	(output, visitor.handled || visitor.inner.mutates())
+}
@@ -0,0 +1,39 @@
+//! Deduplication of [`Actor`](crate::Actor)s that are reachable through several paths, for the `dedup` feature.
+//!
+//! Without the feature, all of this compiles to nothing.
+
+#[cfg(feature = "dedup")]
+use std::{any::TypeId, collections::HashSet};
+
+#[cfg(feature = "dedup")]
+use crate::types::type_id;
+
+/// The [`Actor`](crate::Actor)s visited by a traversal.
+pub(crate) struct Visited {
+	// The type is needed too, since a struct and its first field have the same address.
+	#[cfg(feature = "dedup")]
+	visited: HashSet<(*const (), TypeId)>,
+}
+
+impl Visited {
+	pub fn new() -> Self {
+		Self {
+			#[cfg(feature = "dedup")]
+			visited: HashSet::new(),
+		}
+	}
+
+	/// Mark `actor` as visited, returning `false` if it already was.
+	///
+	/// Zero-sized [`Actor`](crate::Actor)s can share their address with other instances, so they are never
+	/// deduplicated.
+	#[inline(always)]
+	pub fn insert<A>(&mut self, actor: *const A) -> bool {
+		#[cfg(feature = "dedup")]
+		if std::mem::size_of::<A>() != 0 {
+			return self.visited.insert((actor as *const (), type_id::<A>()));
+		}
+		let _ = actor;
+		true
+	}
+}
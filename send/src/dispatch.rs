@@ -0,0 +1,395 @@
+use std::any::{Any, TypeId};
+use std::cell::{Cell, RefCell};
+
+use crate::dedup::Visited;
+use crate::trace::{Delivery, Trace};
+use crate::types::type_id;
+use crate::{Actor, ActorVisitor, MessageVisitor, Meta, Receiver};
+
+/// State shared by every [`Context`](crate::Context) in a single dispatch from the [`Framework`](crate::Framework).
+pub(crate) struct Dispatch<R> {
+	queue: RefCell<Vec<Queued<R>>>,
+	resources: *mut dyn Any,
+	/// The scratch buffer from [`Framework::send_with_scratch`](crate::Framework::send_with_scratch), or null.
+	scratch: *mut dyn Any,
+	/// The trace from [`Framework::enable_trace`](crate::Framework::enable_trace), or null if it is disabled.
+	trace: *mut Trace,
+	/// The metadata from [`Framework::send_meta`](crate::Framework::send_meta), if the dispatch was started by it.
+	meta: Option<Meta>,
+	/// How many messages sent through a [`Context`](crate::Context) are being delivered, nested in each other.
+	depth: Cell<usize>,
+	/// The most messages that can be nested, from
+	/// [`Framework::set_reentrancy_limit`](crate::Framework::set_reentrancy_limit).
+	limit: usize,
+	/// Whether a handler called [`Context::request_repeat`](crate::Context::request_repeat).
+	repeat: Cell<bool>,
+	/// The [`Actor`]s marked with [`Context::remove_self`](crate::Context::remove_self), by address and type.
+	removed: RefCell<Vec<(*const (), TypeId)>>,
+	/// The logical time when the dispatch started.
+	time: u64,
+	/// Whether derived [`Actor`]s receive messages before their fields.
+	top_down: bool,
+	/// The [`Actor`]s marked with [`Context::mark_dirty`](crate::Context::mark_dirty), by address and type.
+	dirty: RefCell<Vec<(*const (), TypeId)>>,
+	/// The seed from [`Framework::set_seed`](crate::Framework::set_seed).
+	#[cfg(feature = "rand")]
+	seed: u64,
+	/// How many dispatches the [`Framework`](crate::Framework) started before this one.
+	#[cfg(feature = "rand")]
+	number: u64,
+}
+
+/// The settings of a [`Framework`](crate::Framework) that every dispatch from it uses.
+pub(crate) struct Settings {
+	/// The trace from [`Framework::enable_trace`](crate::Framework::enable_trace), if it is enabled.
+	pub trace: Option<Trace>,
+	/// The limit from [`Framework::set_reentrancy_limit`](crate::Framework::set_reentrancy_limit).
+	pub reentrancy_limit: usize,
+	/// The limit from [`Framework::set_repeat_limit`](crate::Framework::set_repeat_limit).
+	pub repeat_limit: usize,
+	/// Whether a handler in the latest dispatch called [`Context::request_repeat`](crate::Context::request_repeat).
+	pub repeat: bool,
+	/// The logical time from [`Framework::tick`](crate::Framework::tick), which [`Context::now`](crate::Context::now)
+	/// reads.
+	pub time: u64,
+	/// Whether derived [`Actor`]s receive messages before their fields, from
+	/// [`Framework::set_top_down`](crate::Framework::set_top_down).
+	pub top_down: bool,
+	/// The seed from [`Framework::set_seed`](crate::Framework::set_seed).
+	#[cfg(feature = "rand")]
+	pub seed: u64,
+	/// How many dispatches were started, which [`Context::rng`](crate::Context::rng) is derived from.
+	#[cfg(feature = "rand")]
+	pub dispatches: u64,
+}
+
+impl Default for Settings {
+	fn default() -> Self {
+		Self {
+			trace: None,
+			reentrancy_limit: 128,
+			repeat_limit: 64,
+			repeat: false,
+			time: 0,
+			top_down: false,
+			#[cfg(feature = "rand")]
+			seed: 0,
+			#[cfg(feature = "rand")]
+			dispatches: 0,
+		}
+	}
+}
+
+impl Settings {
+	/// Copy the settings, without the trace.
+	pub fn untraced(&self) -> Self {
+		Self {
+			trace: None,
+			reentrancy_limit: self.reentrancy_limit,
+			repeat_limit: self.repeat_limit,
+			repeat: false,
+			time: self.time,
+			top_down: self.top_down,
+			#[cfg(feature = "rand")]
+			seed: self.seed,
+			#[cfg(feature = "rand")]
+			dispatches: self.dispatches,
+		}
+	}
+}
+
+/// A message sent through a [`Context`](crate::Context) that is being delivered, for [`Dispatch::enter`].
+pub(crate) struct Reentry<'a>(&'a Cell<usize>);
+
+impl Drop for Reentry<'_> {
+	fn drop(&mut self) { self.0.set(self.0.get() - 1); }
+}
+
+/// Messages queued to be delivered once the dispatch is done.
+enum Queued<R> {
+	/// Broadcasts of a single message type, queued with
+	/// [`Context::queue_broadcast`](crate::Context::queue_broadcast).
+	Broadcasts {
+		type_id: TypeId,
+		messages: Box<dyn Any>,
+		deliver: fn(*mut R, &mut dyn Any, &Dispatch<R>),
+	},
+	/// A message for a single [`Actor`], queued with [`Context::defer_to`](crate::Context::defer_to), which finds the
+	/// [`Actor`] in the root and delivers the message to it.
+	Targeted(DeliverFn<R>),
+}
+
+type DeliverFn<R> = Box<dyn FnOnce(*mut R, &Dispatch<R>)>;
+
+impl<R> Dispatch<R>
+where
+	R: Actor,
+{
+	pub fn new(resources: *mut dyn Any, scratch: *mut dyn Any, settings: &mut Settings, meta: Option<Meta>) -> Self {
+		#[cfg(feature = "rand")]
+		let number = {
+			settings.dispatches += 1;
+			settings.dispatches - 1
+		};
+		Self {
+			queue: RefCell::new(Vec::new()),
+			resources,
+			scratch,
+			trace: settings
+				.trace
+				.as_mut()
+				.map_or(std::ptr::null_mut(), |trace| trace as *mut _),
+			meta,
+			depth: Cell::new(0),
+			limit: settings.reentrancy_limit,
+			repeat: Cell::new(false),
+			removed: RefCell::new(Vec::new()),
+			time: settings.time,
+			top_down: settings.top_down,
+			dirty: RefCell::new(Vec::new()),
+			#[cfg(feature = "rand")]
+			seed: settings.seed,
+			#[cfg(feature = "rand")]
+			number,
+		}
+	}
+
+	/// The resources of the [`Framework`](crate::Framework).
+	pub fn resources(&self) -> *mut dyn Any { self.resources }
+
+	/// The scratch buffer of this dispatch, or null if there is none.
+	pub fn scratch(&self) -> *mut dyn Any { self.scratch }
+
+	/// The metadata of the message that started this dispatch, if it was sent with metadata.
+	pub fn meta(&self) -> Option<Meta> { self.meta }
+
+	/// The logical time of the [`Framework`](crate::Framework) when this dispatch started.
+	pub fn time(&self) -> u64 { self.time }
+
+	/// Whether derived [`Actor`]s receive messages before their fields during this dispatch.
+	pub fn top_down(&self) -> bool { self.top_down }
+
+	/// Ask for the message that started this dispatch to be sent again once it finishes.
+	pub fn request_repeat(&self) { self.repeat.set(true); }
+
+	/// Whether [`request_repeat`](Self::request_repeat) was called.
+	pub fn repeat(&self) -> bool { self.repeat.get() }
+
+	/// The seed of the [`Framework`](crate::Framework), and how many dispatches it started before this one.
+	#[cfg(feature = "rand")]
+	pub fn seed(&self) -> (u64, u64) { (self.seed, self.number) }
+
+	/// Count a message sent through a [`Context`](crate::Context) until the returned guard is dropped, panicking if
+	/// too many are nested, which usually means that handlers keep sending messages to each other.
+	#[inline(always)]
+	pub fn enter(&self) -> Reentry<'_> {
+		let depth = self.depth.get() + 1;
+		if depth > self.limit {
+			panic!(
+				"broadcast re-entrancy limit exceeded ({}); possible message loop",
+				self.limit
+			);
+		}
+		self.depth.set(depth);
+		Reentry(&self.depth)
+	}
+
+	/// Record that `A` received `M`, if tracing is enabled.
+	#[inline(always)]
+	pub fn record<A, M>(&self) {
+		if !self.trace.is_null() {
+			// SAFETY: The trace outlives the dispatch, and is only accessed here while it runs.
+			unsafe {
+				(*self.trace).push(Delivery {
+					actor: std::any::type_name::<A>(),
+					message: std::any::type_name::<M>(),
+				})
+			}
+		}
+	}
+
+	/// Mark an [`Actor`] to be removed from its collection by [`finish`](Self::finish).
+	pub fn remove<A>(&self, actor: *const A) {
+		let actor = (actor as *const (), type_id::<A>());
+		let mut removed = self.removed.borrow_mut();
+		if !removed.contains(&actor) {
+			removed.push(actor);
+		}
+	}
+
+	/// Mark an [`Actor`] as changed, for [`take_dirty`](Self::take_dirty).
+	pub fn mark_dirty<A>(&self, actor: *const A) {
+		let actor = (actor as *const (), type_id::<A>());
+		let mut dirty = self.dirty.borrow_mut();
+		if !dirty.contains(&actor) {
+			dirty.push(actor);
+		}
+	}
+
+	/// Take the [`Actor`]s marked with [`mark_dirty`](Self::mark_dirty), by address and type.
+	pub fn take_dirty(&self) -> Vec<(*const (), TypeId)> { std::mem::take(&mut *self.dirty.borrow_mut()) }
+
+	/// Queue a broadcast, to be delivered by [`drain`](Self::drain).
+	pub fn queue<T: 'static>(&self, message: T) {
+		let mut queue = self.queue.borrow_mut();
+		let queued = queue.iter_mut().find_map(|queued| match queued {
+			Queued::Broadcasts { type_id, messages, .. } if *type_id == TypeId::of::<T>() => Some(messages),
+			_ => None,
+		});
+		match queued {
+			Some(messages) => messages.downcast_mut::<Vec<T>>().unwrap().push(message),
+			None => queue.push(Queued::Broadcasts {
+				type_id: TypeId::of::<T>(),
+				messages: Box::new(vec![message]),
+				deliver: deliver::<T, R>,
+			}),
+		}
+	}
+
+	/// Queue a message for the [`Actor`] that `getter` finds in the root, to be delivered by
+	/// [`drain`](Self::drain).
+	pub fn queue_to<T, F, A>(&self, getter: F, message: T)
+	where
+		T: 'static,
+		F: FnOnce(&mut R) -> &mut A + 'static,
+		A: Actor + Receiver<T, R>,
+		R: 'static,
+	{
+		let mut message = message;
+		let deliver = move |root: *mut R, dispatch: &Dispatch<R>| {
+			// SAFETY: Draining only happens once the dispatch is done, so nothing else is borrowing the tree.
+			let target = getter(unsafe { &mut *root });
+			MessageVisitor::new(&mut message, root, dispatch).visit(target);
+		};
+		self.queue.borrow_mut().push(Queued::Targeted(Box::new(deliver)));
+	}
+
+	/// End the dispatch: deliver the queued broadcasts, and then remove the marked [`Actor`]s from their collections.
+	pub fn finish(&self, root: *mut R) {
+		self.drain(root);
+		let removed = std::mem::take(&mut *self.removed.borrow_mut());
+		if !removed.is_empty() {
+			let mut visitor = RemovalVisitor {
+				removed,
+				found: Cell::new(0),
+			};
+			// SAFETY: The dispatch is done, so nothing else is borrowing the tree.
+			unsafe { (*root).accept::<(), R>(&mut visitor) }
+			let missing = visitor.removed.len() - visitor.found.get();
+			assert!(
+				missing == 0,
+				"{missing} `Actor`s called `remove_self`, but they aren't elements of a collection that can remove \
+				 them",
+			);
+		}
+	}
+
+	/// Deliver queued messages until there are none left.
+	///
+	/// Every broadcast message type is delivered in a single traversal, in the order the type was first queued.
+	/// Targeted messages are delivered in the order they were queued, relative to the first broadcast of each type.
+	/// Messages queued while draining are delivered afterwards.
+	fn drain(&self, root: *mut R) {
+		loop {
+			let queue = std::mem::take(&mut *self.queue.borrow_mut());
+			if queue.is_empty() {
+				break;
+			}
+
+			for queued in queue {
+				match queued {
+					Queued::Broadcasts {
+						mut messages, deliver, ..
+					} => deliver(root, &mut *messages, self),
+					Queued::Targeted(deliver) => deliver(root, self),
+				}
+			}
+		}
+	}
+}
+
+fn deliver<T: 'static, R: Actor>(root: *mut R, messages: &mut dyn Any, dispatch: &Dispatch<R>) {
+	let mut visitor = BatchVisitor::new(messages.downcast_mut::<Vec<T>>().unwrap(), root, dispatch);
+	// SAFETY: Draining only happens once the dispatch is done, so nothing else is borrowing the tree.
+	unsafe { (*root).accept(&mut visitor) }
+}
+
+/// Delivers several messages of the same type to every [`Actor`] in a single traversal.
+pub(crate) struct BatchVisitor<'a, M, R> {
+	messages: &'a mut [M],
+	root: *mut R,
+	dispatch: *const Dispatch<R>,
+	visited: Visited,
+	handled: usize,
+}
+
+impl<'a, M, R> BatchVisitor<'a, M, R> {
+	pub fn new(messages: &'a mut [M], root: *mut R, dispatch: *const Dispatch<R>) -> Self {
+		Self {
+			messages,
+			root,
+			dispatch,
+			visited: Visited::new(),
+			handled: 0,
+		}
+	}
+}
+
+impl<M, R> ActorVisitor<M, R> for BatchVisitor<'_, M, R> {
+	#[inline(always)]
+	fn visit<A>(&mut self, actor: &mut A)
+	where
+		A: Actor + Receiver<M, R>,
+	{
+		if !self.visited.insert(actor) {
+			return;
+		}
+		for message in self.messages.iter_mut() {
+			let mut visitor = MessageVisitor::new(message, self.root, self.dispatch);
+			visitor.visit(actor);
+			self.handled += visitor.handled;
+		}
+	}
+
+	#[inline(always)]
+	fn top_down(&self) -> bool {
+		// SAFETY: The dispatch, if there is one, outlives the visitor.
+		unsafe { self.dispatch.as_ref() }.is_some_and(Dispatch::top_down)
+	}
+
+	#[inline(always)]
+	fn handled(&self) -> Option<usize> { Some(self.handled) }
+
+	#[inline(always)]
+	fn mutates(&self) -> bool { false }
+}
+
+/// Removes the marked [`Actor`]s from the collections that contain them, without visiting anything.
+struct RemovalVisitor {
+	removed: Vec<(*const (), TypeId)>,
+	/// The number of marked [`Actor`]s that were found in a collection.
+	found: Cell<usize>,
+}
+
+impl<M, R> ActorVisitor<M, R> for RemovalVisitor {
+	#[inline(always)]
+	fn visit<A>(&mut self, _: &mut A)
+	where
+		A: Actor + Receiver<M, R>,
+	{
+	}
+
+	#[inline(always)]
+	fn removes(&self) -> bool { true }
+
+	#[inline(always)]
+	fn is_removed<A>(&self, actor: &A) -> bool {
+		let removed = self.removed.contains(&(actor as *const A as *const (), type_id::<A>()));
+		self.found.set(self.found.get() + removed as usize);
+		removed
+	}
+
+	// Nothing is changed but the collections, so shared `Actor`s aren't detached.
+	#[inline(always)]
+	fn mutates(&self) -> bool { false }
+}
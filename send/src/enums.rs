@@ -0,0 +1,201 @@
+//! Sending the variants of an enum as separate messages, for [`dispatch_enum!`](crate::dispatch_enum) and
+//! [`receive_enum!`](crate::receive_enum).
+
+use crate::{Actor, Framework};
+
+/// An enum whose variants are sent as separate messages with [`Framework::send_enum`], like events from an external
+/// source. Use [`dispatch_enum!`](crate::dispatch_enum) instead of implementing this by hand.
+pub trait MessageEnum {
+	/// Send the message in the active variant, returning the number of [`Actor`]s that handled it.
+	fn send_to<R, X>(self, framework: &mut Framework<R, X>) -> usize
+	where
+		R: Actor + 'static,
+		X: 'static;
+}
+
+/// A macro for defining an enum whose variants are sent as separate messages with [`Framework::send_enum`], so that
+/// [`Actor`]s can handle each variant with its own [`Receiver`](crate::Receiver) instead of matching on the enum.
+///
+/// Every variant gets a struct of the same name with the same fields, which are public like the enum. Named fields
+/// become a struct with named fields, unnamed fields a tuple struct, and no fields a unit struct. The variants then
+/// contain their structs, which can be converted into the enum with [`From`]. Attributes on the enum are kept on it,
+/// and attributes on a variant are moved to its struct.
+///
+/// ## Examples:
+/// ```
+/// # #![feature(min_specialization)]
+/// # #![feature(negative_impls)]
+/// # use send::prelude::*;
+/// use send::dispatch_enum;
+///
+/// dispatch_enum! {
+///     pub enum Event {
+///         Resize { width: u32, height: u32 },
+///         Key(char),
+///         Quit,
+///     }
+/// }
+///
+/// #[derive(Actor)]
+/// struct Window {
+///     width: u32,
+/// }
+///
+/// receive! {
+///     Resize => Window = (&mut self, resize, _) {
+///         self.width = resize.width;
+///     }
+/// }
+///
+/// let mut framework = Framework::new(Window { width: 0 });
+/// framework.send_enum(Event::Resize(Resize { width: 640, height: 480 }));
+/// framework.send_enum(Event::from(Key('q')));
+/// assert_eq!(framework.get().width, 640);
+/// ```
+#[macro_export]
+macro_rules! dispatch_enum {
+	($(#[$meta:meta])* $vis:vis enum $name:ident { $($variants:tt)* }) => {
+		$crate::dispatch_enum! { @variants $vis $name [$(#[$meta])*] [] $($variants)* }
+	};
+
+	(@variants $vis:vis $name:ident $attrs:tt [$($done:ident)*]
+		$(#[$meta:meta])* $variant:ident { $($field:ident : $ty:ty),* $(,)? } $(, $($rest:tt)*)?
+	) => {
+		$(#[$meta])*
+		$vis struct $variant {
+			$($vis $field: $ty,)*
+		}
+
+		$crate::dispatch_enum! { @variants $vis $name $attrs [$($done)* $variant] $($($rest)*)? }
+	};
+
+	(@variants $vis:vis $name:ident $attrs:tt [$($done:ident)*]
+		$(#[$meta:meta])* $variant:ident ($($ty:ty),* $(,)?) $(, $($rest:tt)*)?
+	) => {
+		$(#[$meta])*
+		$vis struct $variant($($vis $ty,)*);
+
+		$crate::dispatch_enum! { @variants $vis $name $attrs [$($done)* $variant] $($($rest)*)? }
+	};
+
+	(@variants $vis:vis $name:ident $attrs:tt [$($done:ident)*]
+		$(#[$meta:meta])* $variant:ident $(, $($rest:tt)*)?
+	) => {
+		$(#[$meta])*
+		$vis struct $variant;
+
+		$crate::dispatch_enum! { @variants $vis $name $attrs [$($done)* $variant] $($($rest)*)? }
+	};
+
+	(@variants $vis:vis $name:ident [$($attrs:tt)*] [$($done:ident)*]) => {
+		$($attrs)*
+		$vis enum $name {
+			$($done($done),)*
+		}
+
+		$(
+			impl ::std::convert::From<$done> for $name {
+				fn from(message: $done) -> Self { Self::$done(message) }
+			}
+		)*
+
+		impl $crate::MessageEnum for $name {
+			fn send_to<_RootTy, _ResourcesTy>(self, framework: &mut $crate::Framework<_RootTy, _ResourcesTy>) -> usize
+			where
+				_RootTy: $crate::Actor + 'static,
+				_ResourcesTy: 'static,
+			{
+				match self {
+					$(Self::$done(mut message) => framework.send(&mut message),)*
+				}
+			}
+		}
+	};
+}
+
+/// A macro for defining the receivers of an [`Actor`] like with [`receive!`](crate::receive), that also defines an
+/// enum of the messages they receive, like to list the commands that the [`Actor`] accepts or to deserialize one of
+/// them.
+///
+/// The enum comes first, and the handlers follow with the same syntax as in [`receive!`](crate::receive), except
+/// that the message types must be plain names, since they are also the names of the variants. Every variant contains
+/// its message, which can be converted into the enum with [`From`], and [`Framework::send_enum`] sends the active one.
+/// `NAMES` lists the names of the variants in order, and `name` returns the name of the active one.
+///
+/// Every message type can only be listed once, so use one [`receive_enum!`](crate::receive_enum) for every
+/// [`Actor`].
+/// ```
+/// # #![feature(min_specialization)]
+/// # #![feature(negative_impls)]
+/// # use send::prelude::*;
+/// use send::receive_enum;
+///
+/// #[derive(Actor)]
+/// struct Counter {
+///     count: i32,
+/// }
+///
+/// struct Increment;
+/// struct Decrement;
+///
+/// receive_enum! {
+///     pub enum CounterMessage;
+///
+///     Increment => Counter = (&mut self, _, _) { self.count += 1 }
+///     Decrement => Counter = (&mut self, _, _) { self.count -= 1 }
+/// }
+///
+/// assert_eq!(CounterMessage::NAMES, ["Increment", "Decrement"]);
+///
+/// let mut framework = Framework::new(Counter { count: 0 });
+/// framework.send_enum(CounterMessage::from(Increment));
+/// framework.send_enum(CounterMessage::Increment(Increment));
+/// assert_eq!(framework.get().count, 2);
+/// ```
+#[macro_export]
+macro_rules! receive_enum {
+	(
+		$(#[$meta:meta])* $vis:vis enum $name:ident;
+
+		$($message:ident => $on:ty = $($kind:ident)? ($($args:tt)*) $code:block)*
+	) => {
+		$crate::receive! {
+			$($message => $on = $($kind)? ($($args)*) $code)*
+		}
+
+		$(#[$meta])*
+		$vis enum $name {
+			$($message($message),)*
+		}
+
+		impl $name {
+			/// The names of the messages, in the order of the variants.
+			pub const NAMES: &'static [&'static str] = &[$(stringify!($message)),*];
+
+			/// The name of the active message.
+			pub fn name(&self) -> &'static str {
+				match self {
+					$(Self::$message(_) => stringify!($message),)*
+				}
+			}
+		}
+
+		$(
+			impl ::std::convert::From<$message> for $name {
+				fn from(message: $message) -> Self { Self::$message(message) }
+			}
+		)*
+
+		impl $crate::MessageEnum for $name {
+			fn send_to<_RootTy, _ResourcesTy>(self, framework: &mut $crate::Framework<_RootTy, _ResourcesTy>) -> usize
+			where
+				_RootTy: $crate::Actor + 'static,
+				_ResourcesTy: 'static,
+			{
+				match self {
+					$(Self::$message(mut message) => framework.send(&mut message),)*
+				}
+			}
+		}
+	};
+}
@@ -0,0 +1,189 @@
+use std::any::TypeId;
+
+pub use send_derive::TraitTagged;
+
+/// A list of the [`Actor`](crate::Actor) types that implement a trait, for sending to all of them through the trait
+/// object `T` with [`Framework::send_trait`](crate::Framework::send_trait).
+///
+/// Rust can't find the implementors of a trait by itself, so they must be registered with
+/// [`register_trait_actors!`](crate::register_trait_actors).
+pub struct TraitFilter<T: ?Sized> {
+	cast: fn(TypeId, *mut ()) -> Option<*mut T>,
+}
+
+impl<T: ?Sized> TraitFilter<T> {
+	/// Create a [`TraitFilter`] from a function that casts a pointer to an [`Actor`](crate::Actor) with the given
+	/// [`TypeId`] to `T`, or returns `None` if it isn't one of the registered types.
+	///
+	/// Prefer [`register_trait_actors!`](crate::register_trait_actors), which writes it for you.
+	///
+	/// # Safety
+	/// `cast` must only return pointers to the same value, cast from the type with the [`TypeId`].
+	pub const unsafe fn new(cast: fn(TypeId, *mut ()) -> Option<*mut T>) -> Self { Self { cast } }
+
+	/// Cast a pointer to an [`Actor`](crate::Actor) of the type with the [`TypeId`] to `T`, if it is registered.
+	#[inline(always)]
+	pub(crate) fn cast(&self, type_id: TypeId, actor: *mut ()) -> Option<*mut T> { (self.cast)(type_id, actor) }
+}
+
+/// A macro for registering the [`Actor`](crate::Actor) types that implement a trait, to create a [`TraitFilter`].
+///
+/// ## Examples:
+/// ```
+/// # #![feature(min_specialization)]
+/// # #![feature(negative_impls)]
+/// use send::prelude::*;
+/// use send::{register_trait_actors, TraitFilter};
+///
+/// trait Damageable {
+///     fn damage(&mut self, amount: u32);
+/// }
+///
+/// #[derive(Actor)]
+/// struct Player {
+///     health: u32,
+/// }
+///
+/// #[derive(Actor)]
+/// struct Crate {
+///     broken: bool,
+/// }
+///
+/// impl Damageable for Player {
+///     fn damage(&mut self, amount: u32) { self.health -= amount; }
+/// }
+///
+/// impl Damageable for Crate {
+///     fn damage(&mut self, _: u32) { self.broken = true; }
+/// }
+///
+/// static DAMAGEABLE: TraitFilter<dyn Damageable> = register_trait_actors!(dyn Damageable: Player, Crate);
+///
+/// struct Damage(u32);
+///
+/// let mut framework = Framework::new((Player { health: 10 }, Crate { broken: false }));
+/// let hit = framework.send_trait(&mut Damage(3), &DAMAGEABLE, |actor, damage| actor.damage(damage.0));
+/// assert_eq!(hit, 2);
+/// assert_eq!(framework.get().0.health, 7);
+/// ```
+#[macro_export]
+macro_rules! register_trait_actors {
+	(dyn $trait:path: $($actor:ty),* $(,)?) => {
+		// SAFETY: The pointer is only cast from the type that it points to.
+		unsafe {
+			$crate::TraitFilter::<dyn $trait>::new(|type_id, actor| {
+				$(
+					if type_id == ::std::any::TypeId::of::<$actor>() {
+						return ::std::option::Option::Some(actor as *mut $actor as *mut dyn $trait);
+					}
+				)*
+				::std::option::Option::None
+			})
+		}
+	};
+}
+
+/// A trait that [`Actor`](crate::Actor)s can be tagged with, to send messages only to the ones that implement it with
+/// [`Framework::send_to_impls`](crate::Framework::send_to_impls). Implemented for the trait object with
+/// [`trait_tags!`](crate::trait_tags).
+pub trait TraitTag {
+	/// The bit of the trait in the tags of every [`TraitTagged`] type, from `0` to `63`.
+	const BIT: u32;
+}
+
+/// The [`TraitTag`]s of an [`Actor`](crate::Actor) type, as a bitset of their [`BIT`](TraitTag::BIT)s.
+///
+/// Derive it with `#[derive(TraitTagged)]` and list the traits with `#[tagged(Trait, OtherTrait)]`, see
+/// [`trait_tags!`](crate::trait_tags). Every other type has no tags.
+///
+/// The derive checks that the type implements each of them:
+/// ```compile_fail
+/// # #![feature(min_specialization)]
+/// # #![feature(negative_impls)]
+/// # use send::{trait_tags, Actor, TraitTagged};
+/// trait Flammable {}
+///
+/// trait_tags!(dyn Flammable);
+///
+/// // error: the trait bound `Rock: Flammable` is not satisfied
+/// #[derive(Actor, TraitTagged)]
+/// #[tagged(Flammable)]
+/// struct Rock;
+/// ```
+pub trait TraitTagged {
+	/// Get the bitset of the tags, which is constant for each type.
+	fn trait_tags() -> u64;
+}
+
+impl<T> TraitTagged for T {
+	#[inline(always)]
+	default fn trait_tags() -> u64 { 0 }
+}
+
+/// A macro for declaring the traits that [`Actor`](crate::Actor)s can be tagged with by deriving [`TraitTagged`],
+/// by implementing [`TraitTag`] for their trait objects.
+///
+/// The tags get consecutive bits in the order they are listed, so declare all of them in a single invocation, which
+/// guarantees that every one of them has its own bit. Declaring the same trait twice doesn't compile, and neither
+/// does declaring more than 64 of them.
+///
+/// ## Examples:
+/// ```
+/// # #![feature(min_specialization)]
+/// # #![feature(negative_impls)]
+/// use send::prelude::*;
+/// use send::{trait_tags, TraitTagged};
+///
+/// trait Damageable {}
+/// trait Flammable {}
+///
+/// trait_tags!(dyn Damageable, dyn Flammable);
+///
+/// #[derive(Actor, TraitTagged)]
+/// #[tagged(Damageable, Flammable)]
+/// struct Barrel {
+///     damage: u32,
+/// }
+///
+/// impl Damageable for Barrel {}
+/// impl Flammable for Barrel {}
+///
+/// #[derive(Actor)]
+/// struct Wall {
+///     damage: u32,
+/// }
+///
+/// struct Damage(u32);
+///
+/// receive! {
+///     Damage => Barrel = (&mut self, damage, _) {
+///         self.damage += damage.0;
+///     }
+///
+///     Damage => Wall = (&mut self, damage, _) {
+///         self.damage += damage.0;
+///     }
+/// }
+///
+/// let mut framework = Framework::new((Barrel { damage: 0 }, Wall { damage: 0 }));
+/// assert_eq!(framework.send_to_impls::<dyn Damageable, _>(&mut Damage(3)), 1);
+/// assert_eq!((framework.get().0.damage, framework.get().1.damage), (3, 0));
+/// ```
+#[macro_export]
+macro_rules! trait_tags {
+	($(dyn $trait:path),* $(,)?) => {
+		$crate::trait_tags!(@bit 0u32; $(dyn $trait,)*);
+	};
+
+	(@bit $bit:expr; dyn $trait:path, $($rest:tt)*) => {
+		impl $crate::TraitTag for dyn $trait {
+			const BIT: u32 = $bit;
+		}
+
+		const _: () = ::std::assert!($bit < 64, "at most 64 traits can be tagged");
+
+		$crate::trait_tags!(@bit $bit + 1; $($rest)*);
+	};
+
+	(@bit $bit:expr;) => {};
+}
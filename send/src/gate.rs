@@ -0,0 +1,45 @@
+use crate::{Actor, ActorVisitor};
+
+/// An [`Actor`] that can be opened and closed at runtime.
+///
+/// A closed [`Gate`] doesn't visit its [`Actor`], so neither it nor its sub-[`Actor`]s receive any messages.
+/// Use [`Context::open_gate`](crate::Context::open_gate) and [`Context::close_gate`](crate::Context::close_gate) to
+/// run the [`Mount`](crate::Mount) and [`Unmount`](crate::Unmount) lifecycle messages when toggling.
+pub struct Gate<T> {
+	open: bool,
+	inner: T,
+}
+
+impl<T> Gate<T> {
+	/// Create a [`Gate`] around an [`Actor`].
+	pub fn new(inner: T, open: bool) -> Self { Self { open, inner } }
+
+	/// Check if the [`Gate`] is open.
+	pub fn is_open(&self) -> bool { self.open }
+
+	/// Open or close the [`Gate`], without sending any lifecycle messages.
+	pub fn set_open(&mut self, open: bool) { self.open = open; }
+
+	/// Get a reference to the [`Actor`], even if the [`Gate`] is closed.
+	pub fn get(&self) -> &T { &self.inner }
+
+	/// Get a mutable reference to the [`Actor`], even if the [`Gate`] is closed.
+	pub fn get_mut(&mut self) -> &mut T { &mut self.inner }
+
+	/// Get the [`Actor`] back.
+	pub fn into_inner(self) -> T { self.inner }
+}
+
+unsafe impl<T> Actor for Gate<T> {
+	#[inline(always)]
+	fn accept<V, R>(&mut self, visitor: &mut impl ActorVisitor<V, R>) {
+		if self.open {
+			self.inner.accept(visitor);
+		}
+	}
+
+	#[inline(always)]
+	fn accept_path<V, R>(&mut self, path: &[&str], visitor: &mut impl ActorVisitor<V, R>) -> bool {
+		self.open && self.inner.accept_path(path, visitor)
+	}
+}
@@ -0,0 +1,70 @@
+//! Handlers that are registered at runtime, with [`Framework::on`](crate::Framework::on).
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+
+use crate::types::type_id;
+
+/// A handler for messages of type `M` that is registered at runtime with [`Framework::on`](crate::Framework::on),
+/// like by a plugin or a script, instead of with a [`Receiver`](crate::Receiver).
+pub type DynHandler<M> = Box<dyn FnMut(&mut M)>;
+
+/// The [`DynHandler`]s of a [`Framework`](crate::Framework), by the type of their messages.
+#[derive(Default)]
+pub(crate) struct DynHandlers {
+	handlers: HashMap<TypeId, Vec<Box<dyn Any>>>,
+}
+
+impl DynHandlers {
+	pub(crate) fn insert<M: 'static>(&mut self, handler: DynHandler<M>) {
+		self.handlers.entry(type_id::<M>()).or_default().push(Box::new(handler));
+	}
+
+	/// Call every handler for `M` in the order they were registered, and return how many there were.
+	pub(crate) fn call<M>(&mut self, message: &mut M) -> usize {
+		let Some(handlers) = self.handlers.get_mut(&type_id::<M>()) else {
+			return 0;
+		};
+		for handler in handlers.iter_mut() {
+			// SAFETY: Handlers are stored by the `TypeId` of their message, so this is a `DynHandler<M>`, up to
+			// lifetimes.
+			let handler = unsafe { &mut *(&mut **handler as *mut dyn Any as *mut DynHandler<M>) };
+			handler(message);
+		}
+		handlers.len()
+	}
+}
+
+/// An interceptor for messages of type `M` that is registered with
+/// [`Framework::intercept`](crate::Framework::intercept), which can rewrite a message before any
+/// [`Actor`](crate::Actor) receives it, or return `false` to cancel sending it.
+pub type Interceptor<M> = Box<dyn FnMut(&mut M) -> bool>;
+
+/// The [`Interceptor`]s of a [`Framework`](crate::Framework), by the type of their messages.
+#[derive(Default)]
+pub(crate) struct Interceptors {
+	interceptors: HashMap<TypeId, Vec<Box<dyn Any>>>,
+}
+
+impl Interceptors {
+	pub(crate) fn insert<M: 'static>(&mut self, interceptor: Interceptor<M>) {
+		self.interceptors
+			.entry(type_id::<M>())
+			.or_default()
+			.push(Box::new(interceptor));
+	}
+
+	/// Call the interceptors for `M` in the order they were registered, until one cancels the message.
+	/// Returns `false` if it was canceled.
+	pub(crate) fn run<M>(&mut self, message: &mut M) -> bool {
+		let Some(interceptors) = self.interceptors.get_mut(&type_id::<M>()) else {
+			return true;
+		};
+		interceptors.iter_mut().all(|interceptor| {
+			// SAFETY: Interceptors are stored by the `TypeId` of their message, so this is an `Interceptor<M>`, up to
+			// lifetimes.
+			let interceptor = unsafe { &mut *(&mut **interceptor as *mut dyn Any as *mut Interceptor<M>) };
+			interceptor(message)
+		})
+	}
+}
@@ -1,18 +1,117 @@
 #![feature(auto_traits)]
 #![feature(min_specialization)]
+#![feature(negative_impls)]
+
+use std::any::{Any, TypeId};
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::time::{Duration, Instant};
 
 mod actor;
+mod adapter;
+mod arena;
+mod budget;
+mod cancel;
+mod channel;
+mod checked;
 mod context;
+mod counter;
+mod coverage;
+mod cow;
+mod dedup;
+mod dispatch;
+mod enums;
+mod filter;
+mod gate;
+mod handlers;
+mod lifecycle;
+mod meta;
+#[cfg(feature = "rayon")]
+mod par;
+pub mod prelude;
+mod registry;
+mod rev;
+#[cfg(feature = "rand")]
+mod rng;
+mod roots;
+mod siblings;
+mod slots;
+#[cfg(feature = "serde")]
+mod snapshot;
+mod state;
+mod structure;
+#[cfg(feature = "testing")]
+pub mod testing;
+mod topics;
+mod trace;
+mod tuple;
+mod types;
+mod wrap;
 
 pub use actor::*;
+pub use adapter::*;
+pub use arena::*;
+pub use budget::*;
+pub use cancel::*;
+pub use channel::*;
 pub use context::*;
+pub use counter::*;
+pub use coverage::*;
+pub use cow::*;
+pub use enums::*;
+pub use filter::*;
+pub use gate::*;
+pub use handlers::*;
+pub use lifecycle::*;
+pub use meta::*;
+#[cfg(feature = "rayon")]
+pub use par::*;
+/// The `rand_core` crate, for the traits of the generators from [`Context::rng`].
+#[cfg(feature = "rand")]
+pub use rand_chacha::rand_core;
+pub use registry::*;
+pub use rev::*;
+pub use roots::*;
+pub use slots::*;
+pub use state::*;
+pub use structure::*;
+pub use topics::*;
+pub use trace::*;
+pub use tuple::*;
+pub use wrap::*;
+
+use crate::actor::receive_handled;
+use crate::coverage::Coverage;
+use crate::dedup::Visited;
+use crate::dispatch::{BatchVisitor, Dispatch, Settings};
+use crate::handlers::{DynHandlers, Interceptors};
+use crate::trace::Trace;
+use crate::types::type_id;
 
 /// The root of everything.
 ///
 /// It handles a root [`Actor`] and all its sub-[`Actor`]s,
 /// and facilitates message-passing between them, as well as external events.
-pub struct Framework<R> {
+///
+/// It can also own resources of type `X`, which aren't part of the tree but can be accessed by every handler with
+/// [`Context::resources`].
+pub struct Framework<R, X = ()> {
 	root: R,
+	resources: X,
+	routes: HashMap<TypeId, HashSet<TypeId>>,
+	/// Which types can contain a [`Receiver`] for each message type sent so far, see [`Covers`].
+	coverage: HashMap<TypeId, Coverage>,
+	/// The message types that were already warned about, if [`warn_on_unhandled`](Self::warn_on_unhandled) is on.
+	unhandled: Option<HashSet<TypeId>>,
+	handlers: DynHandlers,
+	interceptors: Interceptors,
+	settings: Settings,
+	/// The sequence number of the next message sent with [`send_meta`](Self::send_meta).
+	sequence: u64,
+	registry: MessageRegistry<R, X>,
 }
 
 impl<R> Framework<R>
@@ -20,15 +119,599 @@ where
 	R: Actor + 'static,
 {
 	/// Create a [`Framework`] handling a root [`Actor`].
-	pub fn new(root: R) -> Self { Self { root } }
+	pub fn new(root: R) -> Self { Self::with_resources(root, ()) }
+}
+
+impl<R, X> Framework<R, X>
+where
+	R: Actor + 'static,
+	X: 'static,
+{
+	/// Create a [`Framework`] handling a root [`Actor`], with resources that every handler can access.
+	pub fn with_resources(root: R, resources: X) -> Self {
+		Self {
+			root,
+			resources,
+			routes: HashMap::new(),
+			coverage: HashMap::new(),
+			unhandled: None,
+			handlers: DynHandlers::default(),
+			interceptors: Interceptors::default(),
+			settings: Settings::default(),
+			sequence: 0,
+			registry: MessageRegistry::default(),
+		}
+	}
+
+	/// Only send `M` to [`Actor`]s of type `A` with [`send`](Self::send).
+	///
+	/// Every call adds another type that receives `M`. Messages without any routes are sent to every [`Actor`].
+	pub fn route<M, A>(&mut self) { self.routes.entry(type_id::<M>()).or_default().insert(type_id::<A>()); }
+
+	/// Register a handler for messages of type `M` at runtime, like from a plugin or a script, which
+	/// [`send`](Self::send) calls along with the [`Receiver`]s of the [`Actor`]s.
+	///
+	/// Handlers are called after every [`Actor`] has received the message, including the broadcasts they sent, in
+	/// the order they were registered. They don't get a [`Context`], so they can only mutate the message.
+	///
+	/// # Safety
+	/// Messages are matched by type with their lifetimes erased, like with [`route`](Self::route), so a handler for
+	/// `M` also receives every message whose type only differs from `M` in its lifetimes, like `Msg<'a>` for a
+	/// handler of `Msg<'static>`. `M` must not have lifetime parameters, or every message of its type that is sent
+	/// must actually be an `M`.
+	pub unsafe fn on<M: 'static>(&mut self, handler: DynHandler<M>) { self.handlers.insert(handler); }
+
+	/// Register an interceptor for messages of type `M`, which [`send`](Self::send) calls before any [`Actor`]
+	/// receives the message, like to validate, rate-limit or log messages in one place instead of in every handler.
+	///
+	/// Interceptors can rewrite the message, or return `false` to cancel sending it, in which case no [`Actor`] or
+	/// handler from [`on`](Self::on) receives it and [`send`](Self::send) returns `0`. They are called in the order
+	/// they were registered, and the ones after an interceptor that canceled the message aren't called.
+	///
+	/// Only messages sent from outside the tree with [`send`](Self::send),
+	/// [`send_with_scratch`](Self::send_with_scratch), [`send_meta`](Self::send_meta),
+	/// [`send_enum`](Self::send_enum) and [`send_named`](Self::send_named) are intercepted, not the ones sent through
+	/// a [`Context`]. Messages sent with [`send_meta`](Self::send_meta) are matched as [`Meta<M>`].
+	///
+	/// # Safety
+	/// Like with [`on`](Self::on), messages are matched by type with their lifetimes erased, so `M` must not have
+	/// lifetime parameters, or every message of its type that is sent must actually be an `M`.
+	pub unsafe fn intercept<M: 'static>(&mut self, interceptor: Interceptor<M>) {
+		self.interceptors.insert(interceptor);
+	}
+
+	/// Record the latest `capacity` deliveries of messages to [`Actor`]s, to read with [`recent`](Self::recent), like
+	/// to find out how a message reached the wrong [`Actor`]. A capacity of `0` disables it, which is the default.
+	///
+	/// Deliveries to default [`Receiver`]s aren't recorded, and neither are queued broadcasts or the handlers from
+	/// [`on`](Self::on). Enabling it again clears the recorded deliveries.
+	pub fn enable_trace(&mut self, capacity: usize) {
+		self.settings.trace = (capacity > 0).then(|| Trace::new(capacity));
+	}
+
+	/// The latest deliveries recorded since [`enable_trace`](Self::enable_trace), from oldest to newest.
+	pub fn recent(&self) -> impl Iterator<Item = Delivery> + '_ { self.settings.trace.iter().flat_map(Trace::iter) }
+
+	/// Set how many messages sent through a [`Context`] can be nested in each other, like a handler that broadcasts
+	/// a message whose handler broadcasts another one. Sending one more panics, instead of overflowing the stack when
+	/// handlers keep sending messages to each other in a loop. The default is `128`.
+	pub fn set_reentrancy_limit(&mut self, limit: usize) { self.settings.reentrancy_limit = limit; }
+
+	/// Set how many times [`send`](Self::send) can send a message again because a handler called
+	/// [`Context::request_repeat`]. Repeating it once more panics, instead of looping forever when handlers never
+	/// settle. The default is `64`.
+	pub fn set_repeat_limit(&mut self, limit: usize) { self.settings.repeat_limit = limit; }
+
+	/// Make derived [`Actor`]s receive the messages sent by this [`Framework`] before their fields instead of after
+	/// them, so that parents are visited before their children, like with `#[actor(top_down)]` on every type.
+	///
+	/// By default, fields are visited first, then the [`Actor`] itself, so a struct receives a message after
+	/// everything it contains. Types with `#[actor(top_down)]` are always visited first.
+	pub fn set_top_down(&mut self, top_down: bool) { self.settings.top_down = top_down; }
+
+	/// Advance the logical clock that handlers read with [`Context::now`] by one, like at the start of every step
+	/// of a simulation. Returns the new time.
+	pub fn tick(&mut self) -> u64 {
+		self.settings.time += 1;
+		self.settings.time
+	}
+
+	/// Set the logical clock that handlers read with [`Context::now`], like to jump ahead. It starts at `0`.
+	pub fn set_time(&mut self, time: u64) { self.settings.time = time; }
+
+	/// Get the logical clock that handlers read with [`Context::now`].
+	pub fn time(&self) -> u64 { self.settings.time }
+
+	/// Warn when [`send`](Self::send) delivers a message that no [`Actor`] handles, which usually means a missing
+	/// [`Receiver`] or a message sent to the wrong [`Framework`]. Every message type is only warned about once.
+	///
+	/// Warnings are logged with the `log` feature, and printed to stderr otherwise. They are only checked in debug
+	/// builds, so this does nothing in release builds.
+	pub fn warn_on_unhandled(&mut self, warn: bool) { self.unhandled = warn.then(HashSet::new); }
 
 	/// Send a message to every [`Actor`] in the [`Framework`].
-	pub fn send<M>(&mut self, message: &mut M) {
-		let mut visitor = MessageVisitor {
+	///
+	/// Returns the number of [`Actor`]s that handled the message, not counting default [`Receiver`]s, plus the number
+	/// of handlers registered with [`on`](Self::on) that were called.
+	/// If the message was [`route`](Self::route)d, only [`Actor`]s of the routed types receive it.
+	///
+	/// If a handler calls [`Context::request_repeat`], the same message is sent again once every [`Actor`] and handler
+	/// has received it, until a pass doesn't request it anymore. Then the count is from the last pass.
+	pub fn send<M>(&mut self, message: &mut M) -> usize { self.send_scratch(message, std::ptr::null_mut::<()>(), None) }
+
+	/// Construct a message that was registered by name in the [`registry_mut`](Self::registry_mut) from its
+	/// arguments, and send it to every [`Actor`] like [`send`](Self::send), like for a console command.
+	///
+	/// Returns the number of [`Actor`]s that handled the message, or an error if no message is registered with the
+	/// name or the arguments don't fit it.
+	pub fn send_named(&mut self, name: &str, args: &[Value]) -> Result<usize, MessageError> {
+		let send = self.registry.get(name)?;
+		send(self, args)
+	}
+
+	/// Get the messages that can be sent with [`send_named`](Self::send_named).
+	pub fn registry(&self) -> &MessageRegistry<R, X> { &self.registry }
+
+	/// Get a mutable reference to the messages that can be sent with [`send_named`](Self::send_named), to register
+	/// more.
+	pub fn registry_mut(&mut self) -> &mut MessageRegistry<R, X> { &mut self.registry }
+
+	/// Send the message in the active variant of an enum defined with [`dispatch_enum!`], like an event from an
+	/// external source, to every [`Actor`] like [`send`](Self::send).
+	///
+	/// Returns the number of [`Actor`]s that handled the message.
+	pub fn send_enum<E: MessageEnum>(&mut self, message: E) -> usize { message.send_to(self) }
+
+	/// Send a message to every [`Actor`] like [`send`](Self::send), wrapped in a [`Meta`] with a sequence number and
+	/// the current time, and return it once it was handled.
+	///
+	/// [`Actor`]s receive the [`Meta<M>`], not `M`. Every handler during the dispatch, including for broadcasts sent
+	/// by the handlers, can read the metadata with [`Context::meta`].
+	pub fn send_meta<M>(&mut self, message: M) -> Meta<M> {
+		let mut message = Meta {
+			sequence: self.sequence,
+			time: Instant::now(),
 			message,
-			root: &mut self.root as *mut _,
 		};
+		self.sequence += 1;
+		let meta = message.meta();
+		self.send_scratch(&mut message, std::ptr::null_mut::<()>(), Some(meta));
+		message
+	}
+
+	/// Send a message to every [`Actor`] in the [`Framework`] like [`send`](Self::send), lending `scratch` to every
+	/// handler through [`Context::scratch`], like a buffer to reuse instead of allocating in every handler.
+	///
+	/// The scratch buffer is only available until this returns, including to broadcasts queued during it.
+	pub fn send_with_scratch<M, B: 'static>(&mut self, message: &mut M, scratch: &mut B) -> usize {
+		self.send_scratch(message, scratch, None)
+	}
+
+	/// Send several messages of the same type to every [`Actor`] in the [`Framework`] in a single traversal of the
+	/// tree, instead of walking it once per message, like the broadcasts queued with [`Context::queue_broadcast`].
+	///
+	/// Every [`Actor`] receives all of them in order, before the next [`Actor`] receives any. Unlike with
+	/// [`send`](Self::send), the messages aren't routed or intercepted, and the handlers from [`on`](Self::on) don't
+	/// receive them.
+	pub fn send_batch<M>(&mut self, messages: &mut [M]) {
+		Self::dispatch(
+			&mut self.root,
+			&mut self.resources,
+			&mut self.settings,
+			|root, dispatch| {
+				let mut visitor = BatchVisitor::new(messages, root as *mut _, dispatch);
+				root.accept(&mut visitor);
+			},
+		)
+	}
+
+	fn send_scratch<M>(&mut self, message: &mut M, scratch: *mut dyn Any, meta: Option<Meta>) -> usize {
+		if !self.interceptors.run(message) {
+			return 0;
+		}
+
+		let route = self.routes.get(&type_id::<M>());
+		let coverage = &*self.coverage.entry(type_id::<M>()).or_default();
+		let mut repeats = 0;
+		let handled = loop {
+			let (root, resources, settings) = (&mut self.root, &mut self.resources, &mut self.settings);
+			let handled = Self::dispatch_with_scratch(root, resources, settings, scratch, meta, |root, dispatch| {
+				let mut visitor = RouteVisitor {
+					inner: CountingVisitor {
+						inner: MessageVisitor::new(message, root as *mut _, dispatch),
+						handled: 0,
+					},
+					route,
+					coverage,
+				};
+				// Nothing in the tree can handle the message, so only the handlers from `on` need to run.
+				if !visitor.skips::<R>() {
+					root.accept(&mut visitor);
+				}
+				visitor.inner.handled
+			}) + self.handlers.call(message);
+
+			if !self.settings.repeat {
+				break handled;
+			}
+			repeats += 1;
+			if repeats > self.settings.repeat_limit {
+				panic!(
+					"send repeat limit exceeded ({}); a handler keeps calling `request_repeat`",
+					self.settings.repeat_limit
+				);
+			}
+		};
+
+		if cfg!(debug_assertions) && handled == 0 {
+			if let Some(unhandled) = &mut self.unhandled {
+				if unhandled.insert(type_id::<M>()) {
+					warn_unhandled(std::any::type_name::<M>());
+				}
+			}
+		}
+		handled
+	}
+
+	/// Send a message to every [`Actor`] in the [`Framework`], stopping once `budget` has elapsed.
+	///
+	/// At least one [`Actor`] receives the message, even if the budget is zero, so that resuming always makes
+	/// progress. Time is only checked between [`Actor`]s, so a slow handler can overrun the budget.
+	/// Use [`send_resume`](Self::send_resume) to continue where this stopped.
+	pub fn send_budgeted<M>(&mut self, message: &mut M, budget: Duration) -> DispatchResume {
+		self.send_resume(message, budget, DispatchResume { next: Some(0) })
+	}
+
+	/// Continue a dispatch from [`send_budgeted`](Self::send_budgeted) with a new budget.
+	/// Resuming a finished dispatch does nothing.
+	pub fn send_resume<M>(&mut self, message: &mut M, budget: Duration, resume: DispatchResume) -> DispatchResume {
+		let Some(skip) = resume.next else {
+			return resume;
+		};
+		Self::dispatch(
+			&mut self.root,
+			&mut self.resources,
+			&mut self.settings,
+			|root, dispatch| {
+				let mut visitor = BudgetVisitor {
+					inner: MessageVisitor::new(message, root as *mut _, dispatch),
+					start: Instant::now(),
+					budget,
+					skip,
+					index: 0,
+					stopped: None,
+				};
+				root.accept(&mut visitor);
+				DispatchResume { next: visitor.stopped }
+			},
+		)
+	}
+
+	/// Send several messages of different types to every [`Actor`] in the [`Framework`], in a single traversal.
+	///
+	/// Every [`Actor`] receives all the messages before the next [`Actor`] receives any, in tuple order unless it
+	/// implements [`HandleOrder`] for them, and [`Actor`]s are visited in the same order as [`send`](Self::send).
+	/// Returns the number of messages that were handled, summed over every [`Actor`].
+	pub fn send_tuple<M: MessageTuple>(&mut self, messages: &mut M) -> usize {
+		Self::dispatch(
+			&mut self.root,
+			&mut self.resources,
+			&mut self.settings,
+			|root, dispatch| {
+				let mut visitor = TupleVisitor {
+					messages,
+					root: root as *mut _,
+					dispatch,
+					visited: Visited::new(),
+					handled: 0,
+				};
+				root.accept(&mut visitor);
+				visitor.handled
+			},
+		)
+	}
+
+	/// Send a message to every [`Actor`] in the [`Framework`], calling `observe` with the type name of every
+	/// [`Actor`] that is visited, right before it receives the message, like to check which [`Actor`]s a message
+	/// reaches at all.
+	///
+	/// Unlike the count that [`send`](Self::send) returns, this includes the [`Actor`]s that don't handle the
+	/// message. Broadcasts sent by the handlers aren't observed.
+	/// Returns the number of [`Actor`]s that handled the message, like [`send`](Self::send).
+	pub fn send_observed<M>(&mut self, message: &mut M, observe: impl FnMut(&'static str)) -> usize {
+		Self::dispatch(
+			&mut self.root,
+			&mut self.resources,
+			&mut self.settings,
+			|root, dispatch| {
+				let mut visitor = ObserveVisitor {
+					inner: CountingVisitor {
+						inner: MessageVisitor::new(message, root as *mut _, dispatch),
+						handled: 0,
+					},
+					observe,
+				};
+				root.accept(&mut visitor);
+				visitor.inner.handled
+			},
+		)
+	}
+
+	/// Send a message to every [`Actor`] in the [`Framework`], except in the sub-trees where `prune` returns `true`
+	/// for an [`Actor`] of type `A`, like to skip everything in a hidden panel.
+	///
+	/// `prune` is checked before visiting each [`Actor`] of type `A` and its sub-[`Actor`]s, so a pruned
+	/// [`Actor`] doesn't receive the message either. That includes the [`Actor`]s that are derived or implemented with
+	/// [`impl_actor!`], [`leaf_actors!`] or [`container_actors!`], and a [`Wrap`] around a pruned [`Actor`], while
+	/// a pruned [`CowArc`] isn't even copied. This is like `#[actor(enable_field = "...")]`,
+	/// decided at runtime for a single message. The predicate takes a concrete type rather than [`Any`], since
+	/// [`Actor`]s don't have to be `'static`. Broadcasts sent by the handlers aren't pruned.
+	/// Returns the number of [`Actor`]s that handled the message, like [`send`](Self::send).
+	pub fn send_pruned<M, A>(&mut self, message: &mut M, prune: impl Fn(&A) -> bool) -> usize
+	where
+		A: 'static,
+	{
+		Self::dispatch(
+			&mut self.root,
+			&mut self.resources,
+			&mut self.settings,
+			|root, dispatch| {
+				let mut visitor = PruneVisitor {
+					inner: CountingVisitor {
+						inner: MessageVisitor::new(message, root as *mut _, dispatch),
+						handled: 0,
+					},
+					prune,
+					phantom: PhantomData,
+				};
+				root.accept(&mut visitor);
+				visitor.inner.handled
+			},
+		)
+	}
+
+	/// Call `f` on every [`Actor`] of type `A` in the [`Framework`], as if it was the [`Receiver`] of a message,
+	/// like for bulk processing that doesn't deserve its own message type.
+	///
+	/// Unlike [`for_each_mut`](Self::for_each_mut), this is a dispatch: `f` gets a [`Context`] that can send
+	/// messages, queue broadcasts and remove the [`Actor`], the deliveries are recorded by
+	/// [`enable_trace`](Self::enable_trace) with the type of `f` as the message, and the [`Actor`]s are visited in the
+	/// same order as [`send`](Self::send). Routes, interceptors and the handlers from [`on`](Self::on) don't apply,
+	/// since there is no message type. Returns the number of [`Actor`]s that `f` was called on.
+	pub fn send_callback<A, F>(&mut self, f: F) -> usize
+	where
+		A: 'static,
+		F: FnMut(&mut A, Context<A, R>),
+	{
+		Self::dispatch(
+			&mut self.root,
+			&mut self.resources,
+			&mut self.settings,
+			|root, dispatch| {
+				let mut visitor = CallbackVisitor {
+					f,
+					root: root as *mut R,
+					dispatch,
+					visited: Visited::new(),
+					called: 0,
+					phantom: PhantomData,
+				};
+				root.accept::<(), R>(&mut visitor);
+				visitor.called
+			},
+		)
+	}
+
+	/// Send a message to every [`Actor`] in the [`Framework`] like [`send`](Self::send), and return the type names
+	/// of the [`Actor`]s that called [`Context::mark_dirty`] while handling it or the broadcasts it caused, like to
+	/// find out which parts of a user interface need to be redrawn.
+	///
+	/// The names are in traversal order, once per [`Actor`], so [`Actor`]s of the same type can appear several
+	/// times. [`Actor`]s that were removed with [`Context::remove_self`] aren't included.
+	/// Unlike [`send`](Self::send), this doesn't run interceptors or the handlers from [`on`](Self::on), and
+	/// doesn't repeat the message.
+	pub fn send_dirty<M>(&mut self, message: &mut M) -> Vec<&'static str> {
+		let root = &mut self.root as *mut R;
+		let dispatch = Dispatch::new(
+			&mut self.resources,
+			std::ptr::null_mut::<()>(),
+			&mut self.settings,
+			None,
+		);
+		let mut visitor = MessageVisitor::new(message, root, &dispatch);
 		self.root.accept(&mut visitor);
+		dispatch.finish(root);
+		self.settings.repeat = dispatch.repeat();
+
+		let dirty = dispatch.take_dirty();
+		if dirty.is_empty() {
+			return Vec::new();
+		}
+		let mut visitor = DirtyVisitor {
+			dirty,
+			names: Vec::new(),
+		};
+		self.root.accept::<(), R>(&mut visitor);
+		visitor.names
+	}
+
+	/// Send a message to every [`Actor`] in the [`Framework`], stopping early if `token` is cancelled.
+	///
+	/// The token is checked before every [`Actor`], so [`Actor`]s that already received the message keep their
+	/// changes. Broadcasts queued before the cancellation are still delivered.
+	/// Returns the number of [`Actor`]s that handled the message before it was cancelled, like
+	/// [`send`](Self::send).
+	pub fn send_cancelable<M>(&mut self, message: &mut M, token: &CancelToken) -> usize {
+		Self::dispatch(
+			&mut self.root,
+			&mut self.resources,
+			&mut self.settings,
+			|root, dispatch| {
+				let mut visitor = CancelVisitor {
+					inner: CountingVisitor {
+						inner: MessageVisitor::new(message, root as *mut _, dispatch),
+						handled: 0,
+					},
+					token,
+				};
+				root.accept(&mut visitor);
+				visitor.inner.handled
+			},
+		)
+	}
+
+	/// Repeatedly send a message to every [`Actor`] in the [`Framework`], until a fixpoint is reached.
+	///
+	/// Every iteration creates a message with `make` and sends it. The loop stops after an iteration where `cont`
+	/// returns `false` for the sent message, or after `max_iters` iterations.
+	/// Returns the number of iterations performed.
+	pub fn send_while<M>(&mut self, mut make: impl FnMut() -> M, cont: impl Fn(&M) -> bool, max_iters: usize) -> usize {
+		for iteration in 1..=max_iters {
+			let mut message = make();
+			self.send(&mut message);
+			if !cont(&message) {
+				return iteration;
+			}
+		}
+		max_iters
+	}
+
+	/// Send a message to every [`Actor`] in the [`Framework`], awaiting each [`AsyncReceiver`] in turn.
+	///
+	/// Handlers run one at a time, in the same order as [`send`](Self::send). The returned future is not [`Send`],
+	/// so it must be driven by a single-threaded executor.
+	/// A suspended handler keeps exclusive access to the tree until it finishes, so messages sent through its
+	/// [`Context`] are delivered immediately, just like with [`send`](Self::send).
+	///
+	/// No references into the tree are held between handlers, so the tree is walked once for every [`Actor`] that
+	/// handles the message.
+	pub async fn send_async<M>(&mut self, message: &mut M) {
+		let dispatch = Dispatch::new(
+			&mut self.resources,
+			std::ptr::null_mut::<()>(),
+			&mut self.settings,
+			None,
+		);
+		let mut skip = 0;
+		let mut defaults = HashSet::new();
+		// Outlives every handler, since they can keep their `Context` until they finish.
+		let ignored = Cell::new(false);
+		loop {
+			let mut visitor = AsyncVisitor {
+				message,
+				root: &mut self.root as *mut _,
+				dispatch: &dispatch,
+				skip,
+				defaults: &mut defaults,
+				ignored: &ignored,
+				actor: std::ptr::null_mut(),
+				future: None,
+			};
+			self.root.accept(&mut visitor);
+			match visitor.future {
+				Some(future) => {
+					let _receiving = checked::Receiving::enter(visitor.actor, &mut self.root as *mut _);
+					future.await
+				},
+				None => break,
+			}
+			skip += 1;
+		}
+		dispatch.finish(&mut self.root);
+	}
+
+	/// Send a message to every [`Actor`] in the [`Framework`], ordered by their [`Priority`].
+	/// [`Actor`]s with the same priority receive the message in the same order as [`send`](Self::send).
+	///
+	/// This walks the tree once to find the priorities in use, and then once more for every distinct priority,
+	/// so prefer [`send`](Self::send) when the order doesn't matter.
+	pub fn send_prioritized<M>(&mut self, message: &mut M) {
+		let mut collector = PriorityCollector { priorities: Vec::new() };
+		self.root.accept::<M, R>(&mut collector);
+		let mut priorities = collector.priorities;
+		priorities.sort_unstable_by(|a, b| b.cmp(a));
+		priorities.dedup();
+
+		Self::dispatch(
+			&mut self.root,
+			&mut self.resources,
+			&mut self.settings,
+			|root, dispatch| {
+				for priority in priorities {
+					let mut visitor = PriorityVisitor {
+						inner: MessageVisitor::new(&mut *message, root as *mut _, dispatch),
+						priority,
+					};
+					root.accept(&mut visitor);
+				}
+			},
+		)
+	}
+
+	/// Send a [`Query`] to every [`Actor`] in the [`Framework`], and fold their responses into a single value.
+	///
+	/// Responses are folded in the same order that [`send`](Self::send) delivers messages, so `f` doesn't need to
+	/// be commutative. [`Actor`]s that don't respond are skipped.
+	pub fn send_fold<M, Acc>(&mut self, message: &mut M, init: Acc, f: impl FnMut(Acc, M::Response) -> Acc) -> Acc
+	where
+		M: Query,
+	{
+		Self::dispatch(
+			&mut self.root,
+			&mut self.resources,
+			&mut self.settings,
+			|root, dispatch| {
+				let mut visitor = FoldVisitor::new(message, root as *mut _, dispatch, init, f);
+				root.accept(&mut visitor);
+				visitor.finish()
+			},
+		)
+	}
+
+	/// Send a [`Query`] that [`Actor`]s respond to with a [`Result`] to every [`Actor`] in the [`Framework`], like a
+	/// validation pass, and collect all the errors instead of stopping at the first one.
+	///
+	/// Errors are in the same order that [`send`](Self::send) delivers messages, and the successful responses are
+	/// dropped, so the result is empty if nothing failed.
+	pub fn send_collecting_errors<M, T, E>(&mut self, message: &mut M) -> Vec<E>
+	where
+		M: Query<Response = Result<T, E>>,
+	{
+		self.send_fold(message, Vec::new(), |mut errors, response| {
+			if let Err(error) = response {
+				errors.push(error);
+			}
+			errors
+		})
+	}
+
+	/// Send a [`Query`] to the [`Actor`]s in the [`Framework`] in order, until one of them claims it by responding
+	/// `true`, like for hit testing or input focus. Returns the [`type_name`](std::any::type_name) of the
+	/// [`Actor`] that claimed it, or `None` if none did.
+	///
+	/// The order is the same that [`send`](Self::send) delivers messages in, so the first claim always wins.
+	/// [`Actor`]s that respond `false` or don't respond are skipped, and none after the claiming one receive the
+	/// [`Query`].
+	pub fn send_first<M>(&mut self, message: &mut M) -> Option<&'static str>
+	where
+		M: Query<Response = bool>,
+	{
+		Self::dispatch(
+			&mut self.root,
+			&mut self.resources,
+			&mut self.settings,
+			|root, dispatch| {
+				let mut visitor = ClaimVisitor {
+					message,
+					root: root as *mut _,
+					dispatch,
+					visited: Visited::new(),
+					claimed: None,
+				};
+				root.accept(&mut visitor);
+				visitor.claimed
+			},
+		)
 	}
 
 	/// Send a message to only a specific [`Actor`].
@@ -38,11 +721,123 @@ where
 	where
 		F: FnOnce(&mut R) -> &mut A,
 	{
-		let mut visitor = MessageVisitor {
-			message,
-			root: &mut self.root as *mut _,
+		Self::dispatch(
+			&mut self.root,
+			&mut self.resources,
+			&mut self.settings,
+			|root, dispatch| {
+				let mut visitor = MessageVisitor::new(message, root as *mut _, dispatch);
+				visitor.visit(getter(root));
+			},
+		)
+	}
+
+	/// Send a message to only a specific [`Actor`] by value, and replace it with the [`Actor`] its [`Transform`]
+	/// returns.
+	///
+	/// The [`Actor`] is swapped out for its [`Default`] while it is being transformed, so that is what the rest of
+	/// the tree sees if it is reached during the transformation. The old [`Actor`] and its sub-[`Actor`]s receive
+	/// [`Unmount`] before the swap, and the new ones receive [`Mount`] after it.
+	///
+	/// `getter`: A function that takes in the root and outputs the [`Actor`] to transform.
+	pub fn transform_to<M, F, A>(&mut self, message: &mut M, getter: F)
+	where
+		F: FnOnce(&mut R) -> &mut A,
+		A: Actor + Transform<M, R> + Default,
+	{
+		Self::dispatch(
+			&mut self.root,
+			&mut self.resources,
+			&mut self.settings,
+			|root, dispatch| {
+				let root_ptr = root as *mut R;
+				let actor = getter(root) as *mut A;
+				// SAFETY: `actor` is only borrowed for each of these steps, and the tree is only reached through
+				// `root`.
+				unsafe {
+					let mut unmount = Unmount;
+					(*actor).accept(&mut MessageVisitor::new(&mut unmount, root_ptr, dispatch));
+
+					let old = std::mem::take(&mut *actor);
+					let new = {
+						let _receiving = checked::Receiving::enter_owned(root_ptr);
+						old.transform(message, Context::with_dispatch(root_ptr, dispatch))
+					};
+					*actor = new;
+
+					let mut mount = Mount;
+					(*actor).accept(&mut MessageVisitor::new(&mut mount, root_ptr, dispatch));
+				}
+			},
+		)
+	}
+
+	/// Send a message to only the `n`th [`Actor`] of type `A` in the [`Framework`], counting from `0`, like to reach
+	/// one tile in a `Vec<Tile>` without a getter. Returns `false` if there are `n` or fewer of them.
+	///
+	/// The order is the same that [`send`](Self::send) delivers messages in, which is deterministic except for
+	/// containers like [`HashMap`] that don't have an order.
+	pub fn send_to_nth<M, A: 'static>(&mut self, n: usize, message: &mut M) -> bool {
+		Self::dispatch(
+			&mut self.root,
+			&mut self.resources,
+			&mut self.settings,
+			|root, dispatch| {
+				let mut visitor = TypeVisitor::<M, R, A> {
+					inner: MessageVisitor::new(message, root as *mut _, dispatch),
+					skip: n,
+					first: true,
+					delivered: 0,
+					phantom: PhantomData,
+				};
+				root.accept(&mut visitor);
+				visitor.delivered == 1
+			},
+		)
+	}
+
+	/// Send a message to only the [`Actor`] at a path like `"child.children.0"`, for when it is only known at runtime.
+	/// Returns `false` without sending if there is no [`Actor`] at the path.
+	///
+	/// Every segment is the name of a field, or an index into a tuple or collection, and an empty path is the root.
+	/// Fields that aren't visited, like skipped ones, can't be reached.
+	pub fn send_to_path<M>(&mut self, path: &str, message: &mut M) -> bool {
+		let segments: Vec<_> = if path.is_empty() {
+			Vec::new()
+		} else {
+			path.split('.').collect()
 		};
-		visitor.visit(getter(&mut self.root));
+		Self::dispatch(
+			&mut self.root,
+			&mut self.resources,
+			&mut self.settings,
+			|root, dispatch| {
+				let mut visitor = MessageVisitor::new(message, root as *mut _, dispatch);
+				root.accept_path(&segments, &mut visitor)
+			},
+		)
+	}
+
+	/// Send a message to only a specific [`Actor`], like [`send_to`](Self::send_to), and return whether it handled
+	/// it, that is, whether it has a [`Receiver`] for it other than the default one.
+	///
+	/// The message is delivered either way, since [`Receiver`]s written by hand can only be told apart from the
+	/// default one by delivering to them, but the default one ignores it.
+	///
+	/// `getter`: A function that takes in the root and outputs the [`Actor`] to send the message to.
+	pub fn send_to_checked<M, F, A>(&mut self, message: &mut M, getter: F) -> bool
+	where
+		F: FnOnce(&mut R) -> &mut A,
+	{
+		Self::dispatch(
+			&mut self.root,
+			&mut self.resources,
+			&mut self.settings,
+			|root, dispatch| {
+				let mut visitor = MessageVisitor::new(message, root as *mut _, dispatch);
+				visitor.deliver(getter(root)) == Some(true)
+			},
+		)
 	}
 
 	/// Send a message to a specific [`Actor`] and its sub-[`Actor`]s.
@@ -52,11 +847,25 @@ where
 	where
 		F: FnOnce(&mut R) -> &mut A,
 	{
-		let mut visitor = MessageVisitor {
-			message,
-			root: &mut self.root as *mut _,
-		};
-		getter(&mut self.root).accept(&mut visitor);
+		Self::dispatch(
+			&mut self.root,
+			&mut self.resources,
+			&mut self.settings,
+			|root, dispatch| {
+				let mut visitor = MessageVisitor::new(message, root as *mut _, dispatch);
+				getter(root).accept(&mut visitor);
+			},
+		)
+	}
+
+	/// Send a message to one of the roots of a multi-root [`Framework`] and its sub-[`Actor`]s.
+	/// See [`TupleRoot`].
+	pub fn send_to_root<const I: usize, M>(&mut self, message: &mut M)
+	where
+		R: TupleRoot<I>,
+		R::Root: Actor,
+	{
+		self.send_sub(message, |root| root.root_mut())
 	}
 
 	/// Send a message that contains references to fields or sub-fields.
@@ -95,6 +904,29 @@ where
 		self.send_to(&mut creator(fields), getter);
 	}
 
+	/// Check that the fields picked by `selector` don't contain any [`Actor`]s, like the `selector` of
+	/// [`send_with`](Self::send_with) must not, and panic if they do.
+	///
+	/// [`send_with`](Self::send_with) trusts the [`NotActor`] bound, which [`Actor`]s implemented by hand can forget to
+	/// un-implement. This catches that in tests, by traversing the fields like a broadcast. Only available in debug
+	/// builds.
+	#[cfg(debug_assertions)]
+	#[track_caller]
+	pub fn audit_not_actor<'a, S, F>(&'a mut self, selector: S)
+	where
+		S: FnOnce(&'a mut R) -> F,
+	{
+		let mut fields = selector(&mut self.root);
+		let mut visitor = CountVisitor(0);
+		fields.accept::<(), R>(&mut visitor);
+		assert!(
+			visitor.0 == 0,
+			"The fields selected as `{}` contain {} `Actor`s, so they can't be used with `send_with`",
+			std::any::type_name::<F>(),
+			visitor.0,
+		);
+	}
+
 	/// Send a message that contains references to fields or sub-fields.
 	/// This sends a message to a specific [`Actor`] and its sub-[`Actor`]s.
 	///
@@ -113,17 +945,294 @@ where
 		self.send_sub(&mut creator(fields), getter);
 	}
 
+	/// Broadcast a read-only view of fields or sub-fields to every [`Actor`] with a [`ReceiverRef`] for it, without
+	/// copying them into a message. Returns the number of [`Actor`]s that read the view.
+	///
+	/// Handlers only get `&self` and the view, and no [`Context`], so nothing can be mutated during the broadcast.
+	///
+	/// `selector`: A function that selects the fields to view.  
+	/// `viewer`: A function that generates the view to broadcast.
+	pub fn view_broadcast<'a, S, F, C, V>(&'a mut self, selector: S, viewer: C) -> usize
+	where
+		S: FnOnce(&'a R) -> F,
+		F: 'a + NotActor,
+		C: FnOnce(F) -> V,
+	{
+		// SAFETY: Like `send_with`, the selected fields don't contain any `Actor`s. Handlers can't mutate their
+		// `Actor`s either, so the view can't change while they read it.
+		let fields = selector(unsafe { &*(&self.root as *const _) });
+		let view = viewer(fields);
+		let mut visitor = ViewVisitor { view: &view, viewed: 0 };
+		self.root.accept::<V, R>(&mut visitor);
+		visitor.viewed
+	}
+
 	/// Get a reference to the root [`Actor`].
 	pub fn get(&self) -> &R { &self.root }
 
 	/// Get a mutable reference to the root [`Actor`].
 	/// This shouldn't be used very often: prefer sending events instead.
 	pub fn get_mut(&mut self) -> &mut R { &mut self.root }
+
+	/// Get a reference to the root [`Actor`] as a concrete type, or `None` if it is another type, like in code that
+	/// is generic over the root.
+	pub fn downcast_root_ref<T: Any>(&self) -> Option<&T> { (&self.root as &dyn Any).downcast_ref() }
+
+	/// Get a mutable reference to the root [`Actor`] as a concrete type, or `None` if it is another type, like in code
+	/// that is generic over the root. This shouldn't be used very often, like [`get_mut`](Self::get_mut).
+	///
+	/// The root itself must be the concrete type: a `Box<dyn Any>` root isn't looked into, and isn't visited as the
+	/// [`Actor`] inside it either.
+	pub fn downcast_root_mut<T: Any>(&mut self) -> Option<&mut T> { (&mut self.root as &mut dyn Any).downcast_mut() }
+
+	/// Send a message to every [`Actor`] that implements a trait, whatever its type, by calling `f` with it as the
+	/// trait object `T`. Returns the number of [`Actor`]s it was sent to.
+	///
+	/// Only the types registered in `filter` with [`register_trait_actors!`] are found, in traversal order.
+	/// The [`Actor`]s don't get a [`Context`], like with [`for_each_mut`](Self::for_each_mut).
+	pub fn send_trait<T: ?Sized, M>(
+		&mut self, message: &mut M, filter: &TraitFilter<T>, f: impl FnMut(&mut T, &mut M),
+	) -> usize {
+		let mut visitor = TraitVisitor {
+			message,
+			filter,
+			f,
+			sent: 0,
+		};
+		self.root.accept::<(), R>(&mut visitor);
+		visitor.sent
+	}
+
+	/// Send a message only to the [`Actor`]s that are tagged with the trait `T`, like
+	/// `send_to_impls::<dyn Damageable, _>(&mut message)`. See [`TraitTagged`] and [`trait_tags!`].
+	///
+	/// Unlike [`send_trait`](Self::send_trait), the [`Actor`]s receive the message through their [`Receiver`]s, with
+	/// a [`Context`], and the check is a bitmask test of constant tags instead of a cast by [`TypeId`]. The
+	/// sub-[`Actor`]s of an untagged [`Actor`] still receive it if they are tagged.
+	/// Returns the number of [`Actor`]s that handled the message, like [`send`](Self::send).
+	pub fn send_to_impls<T, M>(&mut self, message: &mut M) -> usize
+	where
+		T: ?Sized + TraitTag,
+	{
+		Self::dispatch(
+			&mut self.root,
+			&mut self.resources,
+			&mut self.settings,
+			|root, dispatch| {
+				let mut visitor = TagVisitor {
+					inner: CountingVisitor {
+						inner: MessageVisitor::new(message, root as *mut _, dispatch),
+						handled: 0,
+					},
+					mask: 1 << T::BIT,
+				};
+				root.accept(&mut visitor);
+				visitor.inner.handled
+			},
+		)
+	}
+
+	/// Create an independent [`Framework`] rooted at a clone of a sub-[`Actor`], like to try out messages without
+	/// affecting this one.
+	///
+	/// The fork has no resources or routes, and only shares what the [`Clone`] of `A` shares, like
+	/// [`Rc`](std::rc::Rc)s.
+	///
+	/// `getter`: A function that takes in the root and outputs the [`Actor`] to fork.
+	pub fn fork_sub<A>(&self, getter: impl FnOnce(&R) -> &A) -> Framework<A>
+	where
+		A: Actor + Clone + 'static,
+	{
+		Framework::new(getter(&self.root).clone())
+	}
+
+	/// Send a message to a clone of the tree instead of the tree itself, and return the clone, like to preview what
+	/// a risky message would change by comparing it with [`get`](Self::get). The [`Framework`] is untouched.
+	///
+	/// The message is sent like with [`send`](Self::send) to a [`Framework`] with clones of the root and the
+	/// resources, the same routes, limits and clock. The handlers from [`on`](Self::on) and the interceptors can't be
+	/// cloned, so they don't run, and the message isn't traced.
+	pub fn dry_run<M>(&self, message: &mut M) -> R
+	where
+		R: Clone,
+		X: Clone,
+	{
+		let mut framework = Framework::with_resources(self.root.clone(), self.resources.clone());
+		framework.routes = self.routes.clone();
+		framework.sequence = self.sequence;
+		framework.settings = self.settings.untraced();
+		framework.send(message);
+		framework.root
+	}
+
+	/// Move the root [`Actor`] out to process it by value, like to snapshot or consume it, and put back the root
+	/// that `f` returns. Returns the other output of `f`.
+	///
+	/// The [`Framework`] holds the [`Default`] root while `f` runs, and keeps it if `f` panics.
+	pub fn with_root_owned<T>(&mut self, f: impl FnOnce(R) -> (R, T)) -> T
+	where
+		R: Default,
+	{
+		let (root, output) = f(std::mem::take(&mut self.root));
+		self.root = root;
+		output
+	}
+
+	/// Call `f` on every [`Actor`] of type `A` in the [`Framework`], in traversal order, without allocating.
+	pub fn for_each_mut<A: 'static>(&mut self, f: impl FnMut(&mut A)) {
+		let mut visitor = ForEachVisitor {
+			f,
+			phantom: PhantomData,
+		};
+		self.root.accept::<(), R>(&mut visitor);
+	}
+
+	/// Count every [`Actor`] in the [`Framework`], whether or not it receives any messages.
+	///
+	/// This counts the root and every sub-[`Actor`] that un-implements [`NotActor`], that is, everything that a
+	/// broadcast visits. Skipped fields, markers, closed [`Gate`]s and dead [`Weak`](std::rc::Weak)s aren't
+	/// counted, and neither are fields like numbers that don't contain [`Actor`]s.
+	///
+	/// This takes `&mut self` because traversing the tree requires mutable access, like borrowing [`RefCell`]s.
+	///
+	/// [`RefCell`]: std::cell::RefCell
+	pub fn actor_count(&mut self) -> usize {
+		let mut visitor = CountVisitor(0);
+		self.root.accept::<(), R>(&mut visitor);
+		visitor.0
+	}
+
+	/// Get a reference to one of the roots of a multi-root [`Framework`]. See [`TupleRoot`].
+	pub fn root<const I: usize>(&self) -> &R::Root
+	where
+		R: TupleRoot<I>,
+	{
+		self.root.root()
+	}
+
+	/// Get a mutable reference to one of the roots of a multi-root [`Framework`]. See [`TupleRoot`].
+	pub fn root_mut<const I: usize>(&mut self) -> &mut R::Root
+	where
+		R: TupleRoot<I>,
+	{
+		self.root.root_mut()
+	}
+
+	/// Get a reference to the resources.
+	pub fn resources(&self) -> &X { &self.resources }
+
+	/// Get a mutable reference to the resources.
+	pub fn resources_mut(&mut self) -> &mut X { &mut self.resources }
+
+	/// Run a dispatch, and then deliver the broadcasts it queued.
+	fn dispatch<T>(
+		root: &mut R, resources: &mut X, settings: &mut Settings, f: impl FnOnce(&mut R, &Dispatch<R>) -> T,
+	) -> T {
+		Self::dispatch_with_scratch(root, resources, settings, std::ptr::null_mut::<()>(), None, f)
+	}
+
+	fn dispatch_with_scratch<T>(
+		root: &mut R, resources: &mut X, settings: &mut Settings, scratch: *mut dyn Any, meta: Option<Meta>,
+		f: impl FnOnce(&mut R, &Dispatch<R>) -> T,
+	) -> T {
+		let dispatch = Dispatch::new(resources, scratch, settings, meta);
+		let output = f(root, &dispatch);
+		dispatch.finish(root);
+		settings.repeat = dispatch.repeat();
+		output
+	}
+}
+
+/// Warn that a message was not handled by any [`Actor`], for [`Framework::warn_on_unhandled`].
+#[cold]
+fn warn_unhandled(message: &str) {
+	#[cfg(feature = "log")]
+	log::warn!("`{message}` was sent, but no `Actor` handled it");
+	#[cfg(not(feature = "log"))]
+	eprintln!("warning: `{message}` was sent, but no `Actor` handled it");
+}
+
+/// Send a message to every [`Actor`] in a borrowed tree, like one that is a field of a larger struct or behind a
+/// `&mut`, without moving it into a [`Framework`]. Returns the number of [`Actor`]s that handled the message, like
+/// [`Framework::send`].
+///
+/// `root` is what every [`Context`] created during the dispatch treats as the root. There are no resources,
+/// routes or handlers from [`on`](Framework::on), and the limits are the defaults.
+pub fn dispatch_into<M, R>(root: &mut R, message: &mut M) -> usize
+where
+	R: Actor,
+{
+	// SAFETY: `root` is borrowed mutably until this returns, so nothing else can access the tree in the meantime.
+	unsafe { dispatch_message(root, message) }
+}
+
+/// Send a message to every [`Actor`] in a tree that isn't owned by a [`Framework`].
+/// Returns the number of [`Actor`]s that handled the message, like [`Framework::send`].
+///
+/// This is the same dispatch that [`Framework::send`] uses, for building containers that own their [`Actor`]s
+/// differently. `root` is what every [`Context`] created during the dispatch treats as the root.
+///
+/// # Safety
+/// `root` must be valid for reads and writes until this returns, and nothing may access the tree it points to in
+/// the meantime, except through the [`Context`]s given to handlers.
+/// In particular, handlers must not call this again on the same tree: they should use their [`Context`] instead.
+pub unsafe fn dispatch_message<M, R>(root: *mut R, message: &mut M) -> usize
+where
+	R: Actor,
+{
+	let dispatch = Dispatch::new(&mut (), std::ptr::null_mut::<()>(), &mut Settings::default(), None);
+	let mut visitor = CountingVisitor {
+		inner: MessageVisitor::new(message, root, &dispatch),
+		handled: 0,
+	};
+	(*root).accept(&mut visitor);
+	dispatch.finish(root);
+	visitor.handled
 }
 
 struct MessageVisitor<'a, M, R> {
 	message: &'a mut M,
 	root: *mut R,
+	dispatch: *const Dispatch<R>,
+	visited: Visited,
+	/// The number of [`Actor`]s that handled the message so far.
+	handled: usize,
+}
+
+impl<'a, M, R> MessageVisitor<'a, M, R> {
+	#[inline(always)]
+	fn new(message: &'a mut M, root: *mut R, dispatch: *const Dispatch<R>) -> Self {
+		Self {
+			message,
+			root,
+			dispatch,
+			visited: Visited::new(),
+			handled: 0,
+		}
+	}
+
+	/// Deliver the message to `actor`, returning whether it handled it, or `None` if it already received it during
+	/// this traversal.
+	#[inline(always)]
+	fn deliver<A>(&mut self, actor: &mut A) -> Option<bool>
+	where
+		A: Actor + Receiver<M, R>,
+	{
+		if !self.visited.insert(actor) {
+			return None;
+		}
+		let _receiving = checked::Receiving::enter(actor, self.root);
+		let context = Context::with_dispatch(self.root, self.dispatch);
+		let handled = receive_handled(actor, self.message, context);
+		if handled {
+			self.handled += 1;
+			// SAFETY: The dispatch, if there is one, outlives every `Context` made from it.
+			if let Some(dispatch) = unsafe { self.dispatch.as_ref() } {
+				dispatch.record::<A, M>();
+			}
+		}
+		Some(handled)
+	}
 }
 
 impl<M, R> ActorVisitor<M, R> for MessageVisitor<'_, M, R> {
@@ -132,41 +1241,1009 @@ impl<M, R> ActorVisitor<M, R> for MessageVisitor<'_, M, R> {
 	where
 		A: Actor + Receiver<M, R>,
 	{
-		let context = Context::new(self.root);
-		actor.receive(self.message, context);
+		self.deliver(actor);
+	}
+
+	#[inline(always)]
+	fn top_down(&self) -> bool {
+		// SAFETY: The dispatch, if there is one, outlives the visitor.
+		unsafe { self.dispatch.as_ref() }.is_some_and(Dispatch::top_down)
 	}
+
+	#[inline(always)]
+	fn handled(&self) -> Option<usize> { Some(self.handled) }
+
+	#[inline(always)]
+	fn mutates(&self) -> bool { false }
 }
 
-/// A macro for easily implementing [`Receiver`] for your types.
-///
-/// ## Examples:
-/// A type without generics:
-/// ```
-/// # #![feature(min_specialization)]
-/// # use send::receive;
-///
-/// struct MyActor;
-/// struct MyMessage;
-///
-/// receive! {
-/// 	MyMessage => MyActor = (&mut self, _message, _context) {
-/// 		// Some code here
-/// 	}
-/// }
-/// ```
-///
-/// With generics:
-/// ```
-/// # #![feature(min_specialization)]
+struct CountingVisitor<'a, M, R> {
+	inner: MessageVisitor<'a, M, R>,
+	handled: usize,
+}
+
+impl<M, R> ActorVisitor<M, R> for CountingVisitor<'_, M, R> {
+	#[inline(always)]
+	fn visit<A>(&mut self, actor: &mut A)
+	where
+		A: Actor + Receiver<M, R>,
+	{
+		if self.inner.deliver(actor) == Some(true) {
+			self.handled += 1;
+		}
+	}
+
+	#[inline(always)]
+	fn removes(&self) -> bool { self.inner.removes() }
+
+	#[inline(always)]
+	fn is_removed<B>(&self, actor: &B) -> bool { self.inner.is_removed(actor) }
+
+	#[inline(always)]
+	fn edit<C>(&mut self, collection: &mut C) { self.inner.edit(collection) }
+
+	#[inline(always)]
+	fn skips<B>(&self) -> bool { self.inner.skips::<B>() }
+
+	#[inline(always)]
+	fn top_down(&self) -> bool { self.inner.top_down() }
+
+	#[inline(always)]
+	fn prunes<B: ?Sized>(&self, actor: &B) -> bool { self.inner.prunes(actor) }
+
+	#[inline(always)]
+	fn handled(&self) -> Option<usize> { self.inner.handled() }
+
+	#[inline(always)]
+	fn mutates(&self) -> bool { self.inner.mutates() }
+}
+
+struct AsyncVisitor<'a, M, R> {
+	message: *mut M,
+	root: *mut R,
+	dispatch: *const Dispatch<R>,
+	skip: usize,
+	/// The [`Actor`] types that only have the default [`AsyncReceiver`], found out the first time each one is visited,
+	/// since calling `receive` again could run the side effects of the others before their turn.
+	defaults: &'a mut HashSet<TypeId>,
+	/// Set by the default [`AsyncReceiver`], through the [`Context`] it is given.
+	ignored: &'a Cell<bool>,
+	actor: *mut (),
+	future: Option<Pin<Box<dyn Future<Output = ()>>>>,
+}
+
+impl<M, R: 'static> ActorVisitor<M, R> for AsyncVisitor<'_, M, R> {
+	#[inline(always)]
+	fn visit<A>(&mut self, actor: &mut A)
+	where
+		A: Actor + Receiver<M, R>,
+	{
+		if self.future.is_some() || self.defaults.contains(&type_id::<A>()) {
+			return;
+		}
+
+		if self.skip > 0 {
+			self.skip -= 1;
+			return;
+		}
+
+		let pointer = actor as *mut A as *mut ();
+		let context = Context::with_dispatch(self.root, self.dispatch).reporting(self.ignored);
+		// SAFETY: The future is awaited to completion by `send_async` before the tree or message are touched again,
+		// so extending the borrows to `'static` never lets them alias.
+		let future = AsyncReceiver::receive(actor, unsafe { &mut *self.message }, context);
+		if self.ignored.replace(false) {
+			self.defaults.insert(type_id::<A>());
+			return;
+		}
+		self.actor = pointer;
+		self.future = Some(unsafe {
+			std::mem::transmute::<Pin<Box<dyn Future<Output = ()> + '_>>, Pin<Box<dyn Future<Output = ()>>>>(future)
+		});
+	}
+
+	#[inline(always)]
+	fn top_down(&self) -> bool {
+		// SAFETY: The dispatch, if there is one, outlives the visitor.
+		unsafe { self.dispatch.as_ref() }.is_some_and(Dispatch::top_down)
+	}
+}
+
+struct PriorityCollector {
+	priorities: Vec<i32>,
+}
+
+impl<M, R> ActorVisitor<M, R> for PriorityCollector {
+	#[inline(always)]
+	fn visit<A>(&mut self, _: &mut A)
+	where
+		A: Actor + Receiver<M, R>,
+	{
+		self.priorities.push(<A as Priority<M>>::priority());
+	}
+
+	#[inline(always)]
+	fn mutates(&self) -> bool { false }
+}
+
+struct PriorityVisitor<'a, M, R> {
+	inner: MessageVisitor<'a, M, R>,
+	priority: i32,
+}
+
+impl<M, R> ActorVisitor<M, R> for PriorityVisitor<'_, M, R> {
+	#[inline(always)]
+	fn visit<A>(&mut self, actor: &mut A)
+	where
+		A: Actor + Receiver<M, R>,
+	{
+		if <A as Priority<M>>::priority() == self.priority {
+			self.inner.visit(actor);
+		}
+	}
+
+	#[inline(always)]
+	fn removes(&self) -> bool { self.inner.removes() }
+
+	#[inline(always)]
+	fn is_removed<B>(&self, actor: &B) -> bool { self.inner.is_removed(actor) }
+
+	#[inline(always)]
+	fn edit<C>(&mut self, collection: &mut C) { self.inner.edit(collection) }
+
+	#[inline(always)]
+	fn skips<B>(&self) -> bool { self.inner.skips::<B>() }
+
+	#[inline(always)]
+	fn top_down(&self) -> bool { self.inner.top_down() }
+
+	#[inline(always)]
+	fn prunes<B: ?Sized>(&self, actor: &B) -> bool { self.inner.prunes(actor) }
+
+	#[inline(always)]
+	fn handled(&self) -> Option<usize> { self.inner.handled() }
+
+	#[inline(always)]
+	fn mutates(&self) -> bool { self.inner.mutates() }
+}
+
+struct BudgetVisitor<'a, M, R> {
+	inner: MessageVisitor<'a, M, R>,
+	start: Instant,
+	budget: Duration,
+	skip: usize,
+	index: usize,
+	stopped: Option<usize>,
+}
+
+impl<M, R> ActorVisitor<M, R> for BudgetVisitor<'_, M, R> {
+	#[inline(always)]
+	fn visit<A>(&mut self, actor: &mut A)
+	where
+		A: Actor + Receiver<M, R>,
+	{
+		if self.stopped.is_some() {
+			return;
+		}
+		if self.index > self.skip && self.start.elapsed() >= self.budget {
+			self.stopped = Some(self.index);
+			return;
+		}
+		if self.index >= self.skip {
+			self.inner.visit(actor);
+		}
+		self.index += 1;
+	}
+
+	#[inline(always)]
+	fn removes(&self) -> bool { self.inner.removes() }
+
+	#[inline(always)]
+	fn is_removed<B>(&self, actor: &B) -> bool { self.inner.is_removed(actor) }
+
+	#[inline(always)]
+	fn edit<C>(&mut self, collection: &mut C) { self.inner.edit(collection) }
+
+	#[inline(always)]
+	fn skips<B>(&self) -> bool { self.inner.skips::<B>() }
+
+	#[inline(always)]
+	fn top_down(&self) -> bool { self.inner.top_down() }
+
+	#[inline(always)]
+	fn prunes<B: ?Sized>(&self, actor: &B) -> bool { self.inner.prunes(actor) }
+
+	#[inline(always)]
+	fn handled(&self) -> Option<usize> { self.inner.handled() }
+
+	#[inline(always)]
+	fn mutates(&self) -> bool { self.inner.mutates() }
+}
+
+struct RouteVisitor<'a, M, R> {
+	inner: CountingVisitor<'a, M, R>,
+	route: Option<&'a HashSet<TypeId>>,
+	/// Skips the sub-trees that can't handle the message.
+	coverage: &'a Coverage,
+}
+
+impl<M, R> ActorVisitor<M, R> for RouteVisitor<'_, M, R> {
+	#[inline(always)]
+	fn visit<A>(&mut self, actor: &mut A)
+	where
+		A: Actor + Receiver<M, R>,
+	{
+		if self.route.is_none_or(|route| route.contains(&type_id::<A>())) {
+			self.inner.visit(actor);
+		}
+	}
+
+	#[inline(always)]
+	fn skips<A>(&self) -> bool { !self.coverage.covers::<A, M, R>() || self.inner.skips::<A>() }
+
+	#[inline(always)]
+	fn removes(&self) -> bool { self.inner.removes() }
+
+	#[inline(always)]
+	fn is_removed<B>(&self, actor: &B) -> bool { self.inner.is_removed(actor) }
+
+	#[inline(always)]
+	fn edit<C>(&mut self, collection: &mut C) { self.inner.edit(collection) }
+
+	#[inline(always)]
+	fn top_down(&self) -> bool { self.inner.top_down() }
+
+	#[inline(always)]
+	fn prunes<B: ?Sized>(&self, actor: &B) -> bool { self.inner.prunes(actor) }
+
+	#[inline(always)]
+	fn handled(&self) -> Option<usize> { self.inner.handled() }
+
+	#[inline(always)]
+	fn mutates(&self) -> bool { self.inner.mutates() }
+}
+
+struct ObserveVisitor<'a, M, R, F> {
+	inner: CountingVisitor<'a, M, R>,
+	observe: F,
+}
+
+impl<M, R, F> ActorVisitor<M, R> for ObserveVisitor<'_, M, R, F>
+where
+	F: FnMut(&'static str),
+{
+	#[inline(always)]
+	fn visit<A>(&mut self, actor: &mut A)
+	where
+		A: Actor + Receiver<M, R>,
+	{
+		(self.observe)(std::any::type_name::<A>());
+		self.inner.visit(actor);
+	}
+
+	#[inline(always)]
+	fn removes(&self) -> bool { self.inner.removes() }
+
+	#[inline(always)]
+	fn is_removed<B>(&self, actor: &B) -> bool { self.inner.is_removed(actor) }
+
+	#[inline(always)]
+	fn edit<C>(&mut self, collection: &mut C) { self.inner.edit(collection) }
+
+	#[inline(always)]
+	fn skips<B>(&self) -> bool { self.inner.skips::<B>() }
+
+	#[inline(always)]
+	fn top_down(&self) -> bool { self.inner.top_down() }
+
+	#[inline(always)]
+	fn prunes<B: ?Sized>(&self, actor: &B) -> bool { self.inner.prunes(actor) }
+
+	#[inline(always)]
+	fn handled(&self) -> Option<usize> { self.inner.handled() }
+
+	#[inline(always)]
+	fn mutates(&self) -> bool { self.inner.mutates() }
+}
+
+struct PruneVisitor<'a, M, R, A, F> {
+	inner: CountingVisitor<'a, M, R>,
+	prune: F,
+	phantom: PhantomData<fn(&A)>,
+}
+
+impl<M, R, A, F> ActorVisitor<M, R> for PruneVisitor<'_, M, R, A, F>
+where
+	A: 'static,
+	F: Fn(&A) -> bool,
+{
+	#[inline(always)]
+	fn visit<B>(&mut self, actor: &mut B)
+	where
+		B: Actor + Receiver<M, R>,
+	{
+		self.inner.visit(actor);
+	}
+
+	#[inline(always)]
+	fn removes(&self) -> bool { self.inner.removes() }
+
+	#[inline(always)]
+	fn is_removed<B>(&self, actor: &B) -> bool { self.inner.is_removed(actor) }
+
+	#[inline(always)]
+	fn edit<C>(&mut self, collection: &mut C) { self.inner.edit(collection) }
+
+	#[inline(always)]
+	fn skips<B>(&self) -> bool { self.inner.skips::<B>() }
+
+	#[inline(always)]
+	fn top_down(&self) -> bool { self.inner.top_down() }
+
+	#[inline(always)]
+	fn prunes<B: ?Sized>(&self, actor: &B) -> bool {
+		// SAFETY: `B` is `A`, since every `Actor` in the tree is `'static` like the root.
+		type_id::<B>() == TypeId::of::<A>() && (self.prune)(unsafe { &*(actor as *const B as *const A) })
+			|| self.inner.prunes(actor)
+	}
+
+	#[inline(always)]
+	fn handled(&self) -> Option<usize> { self.inner.handled() }
+
+	#[inline(always)]
+	fn mutates(&self) -> bool { self.inner.mutates() }
+}
+
+/// Collects the names of the [`Actor`]s that were marked dirty, in traversal order.
+struct DirtyVisitor {
+	dirty: Vec<(*const (), TypeId)>,
+	names: Vec<&'static str>,
+}
+
+impl<M, R> ActorVisitor<M, R> for DirtyVisitor {
+	#[inline(always)]
+	fn visit<A>(&mut self, actor: &mut A)
+	where
+		A: Actor + Receiver<M, R>,
+	{
+		if self.dirty.contains(&(actor as *const A as *const (), type_id::<A>())) {
+			self.names.push(std::any::type_name::<A>());
+		}
+	}
+
+	#[inline(always)]
+	fn mutates(&self) -> bool { false }
+}
+
+struct CancelVisitor<'a, M, R> {
+	inner: CountingVisitor<'a, M, R>,
+	token: &'a CancelToken,
+}
+
+impl<M, R> ActorVisitor<M, R> for CancelVisitor<'_, M, R> {
+	#[inline(always)]
+	fn visit<A>(&mut self, actor: &mut A)
+	where
+		A: Actor + Receiver<M, R>,
+	{
+		if !self.token.is_cancelled() {
+			self.inner.visit(actor);
+		}
+	}
+
+	#[inline(always)]
+	fn removes(&self) -> bool { self.inner.removes() }
+
+	#[inline(always)]
+	fn is_removed<B>(&self, actor: &B) -> bool { self.inner.is_removed(actor) }
+
+	#[inline(always)]
+	fn edit<C>(&mut self, collection: &mut C) { self.inner.edit(collection) }
+
+	#[inline(always)]
+	fn skips<B>(&self) -> bool { self.inner.skips::<B>() }
+
+	#[inline(always)]
+	fn top_down(&self) -> bool { self.inner.top_down() }
+
+	#[inline(always)]
+	fn prunes<B: ?Sized>(&self, actor: &B) -> bool { self.inner.prunes(actor) }
+
+	#[inline(always)]
+	fn handled(&self) -> Option<usize> { self.inner.handled() }
+
+	#[inline(always)]
+	fn mutates(&self) -> bool { self.inner.mutates() }
+}
+
+struct TupleVisitor<'a, M, R> {
+	messages: &'a mut M,
+	root: *mut R,
+	dispatch: *const Dispatch<R>,
+	visited: Visited,
+	handled: usize,
+}
+
+impl<M, R> ActorVisitor<M, R> for TupleVisitor<'_, M, R>
+where
+	M: MessageTuple,
+{
+	#[inline(always)]
+	fn visit<A>(&mut self, actor: &mut A)
+	where
+		A: Actor + Receiver<M, R>,
+	{
+		if !self.visited.insert(actor) {
+			return;
+		}
+		let mut each = DeliverEach {
+			actor,
+			root: self.root,
+			dispatch: self.dispatch,
+			handled: 0,
+		};
+		let mut positions = HandlePositions::<A> {
+			positions: None,
+			phantom: PhantomData,
+		};
+		self.messages.for_each(&mut positions);
+		if positions.positions.is_none() {
+			self.messages.for_each(&mut each);
+		} else {
+			// Only allocate for the `Actor`s that have an order.
+			positions.positions = Some(Vec::new());
+			self.messages.for_each(&mut positions);
+			let positions = positions.positions.unwrap();
+			// A stable sort keeps the messages without a position in tuple order, after the others.
+			let mut order: Vec<_> = (0..positions.len()).collect();
+			order.sort_by_key(|&index| positions[index].unwrap_or(usize::MAX));
+			for index in order {
+				self.messages.for_each(&mut OnlyAt {
+					inner: &mut each,
+					index,
+					current: 0,
+				});
+			}
+		}
+		self.handled += each.handled;
+	}
+
+	#[inline(always)]
+	fn top_down(&self) -> bool {
+		// SAFETY: The dispatch, if there is one, outlives the visitor.
+		unsafe { self.dispatch.as_ref() }.is_some_and(Dispatch::top_down)
+	}
+
+	#[inline(always)]
+	fn handled(&self) -> Option<usize> { Some(self.handled) }
+
+	#[inline(always)]
+	fn mutates(&self) -> bool { false }
+}
+
+/// Collects the [`HandleOrder`] of `A` for every message in a [`MessageTuple`], or, while `positions` is `None`,
+/// only sets it to `Some` if any message has a position.
+struct HandlePositions<A> {
+	positions: Option<Vec<Option<usize>>>,
+	phantom: PhantomData<*const A>,
+}
+
+impl<A> EachMessage for HandlePositions<A> {
+	#[inline(always)]
+	fn call<M>(&mut self, _: &mut M) {
+		let position = <A as HandleOrder<M>>::position();
+		match &mut self.positions {
+			Some(positions) => positions.push(position),
+			None if position.is_some() => self.positions = Some(Vec::new()),
+			None => {},
+		}
+	}
+}
+
+/// Only calls `inner` on the message at `index` of a [`MessageTuple`].
+struct OnlyAt<'a, E> {
+	inner: &'a mut E,
+	index: usize,
+	current: usize,
+}
+
+impl<E: EachMessage> EachMessage for OnlyAt<'_, E> {
+	#[inline(always)]
+	fn call<M>(&mut self, message: &mut M) {
+		if self.current == self.index {
+			self.inner.call(message);
+		}
+		self.current += 1;
+	}
+}
+
+/// Delivers every message in a [`MessageTuple`] to a single [`Actor`].
+struct DeliverEach<'a, A, R> {
+	actor: &'a mut A,
+	root: *mut R,
+	dispatch: *const Dispatch<R>,
+	handled: usize,
+}
+
+impl<A, R> EachMessage for DeliverEach<'_, A, R>
+where
+	A: Actor,
+{
+	#[inline(always)]
+	fn call<M>(&mut self, message: &mut M) {
+		let mut visitor = CountingVisitor {
+			inner: MessageVisitor::new(message, self.root, self.dispatch),
+			handled: 0,
+		};
+		visitor.visit(&mut *self.actor);
+		self.handled += visitor.handled;
+	}
+}
+
+/// Delivers a message only to [`Actor`]s of type `A`, or only to the first of them.
+struct TypeVisitor<'a, M, R, A> {
+	inner: MessageVisitor<'a, M, R>,
+	/// The number of [`Actor`]s of type `A` to pass over before delivering.
+	skip: usize,
+	first: bool,
+	delivered: usize,
+	phantom: PhantomData<fn(&mut A)>,
+}
+
+impl<M, R, A> ActorVisitor<M, R> for TypeVisitor<'_, M, R, A>
+where
+	A: 'static,
+{
+	#[inline(always)]
+	fn visit<B>(&mut self, actor: &mut B)
+	where
+		B: Actor + Receiver<M, R>,
+	{
+		if self.first && self.delivered > 0 {
+			return;
+		}
+		if type_id::<B>() != TypeId::of::<A>() {
+			return;
+		}
+		if self.skip > 0 {
+			self.skip -= 1;
+		} else if self.inner.deliver(actor).is_some() {
+			self.delivered += 1;
+		}
+	}
+
+	#[inline(always)]
+	fn removes(&self) -> bool { self.inner.removes() }
+
+	#[inline(always)]
+	fn is_removed<B>(&self, actor: &B) -> bool { self.inner.is_removed(actor) }
+
+	#[inline(always)]
+	fn edit<C>(&mut self, collection: &mut C) { self.inner.edit(collection) }
+
+	#[inline(always)]
+	fn skips<B>(&self) -> bool { self.inner.skips::<B>() }
+
+	#[inline(always)]
+	fn top_down(&self) -> bool { self.inner.top_down() }
+
+	#[inline(always)]
+	fn prunes<B: ?Sized>(&self, actor: &B) -> bool { self.inner.prunes(actor) }
+
+	#[inline(always)]
+	fn handled(&self) -> Option<usize> { self.inner.handled() }
+
+	#[inline(always)]
+	fn mutates(&self) -> bool { self.inner.mutates() }
+}
+
+struct ForEachVisitor<A, F> {
+	f: F,
+	phantom: PhantomData<fn(&mut A)>,
+}
+
+impl<M, R, A, F> ActorVisitor<M, R> for ForEachVisitor<A, F>
+where
+	A: 'static,
+	F: FnMut(&mut A),
+{
+	#[inline(always)]
+	fn visit<B>(&mut self, actor: &mut B)
+	where
+		B: Actor + Receiver<M, R>,
+	{
+		if type_id::<B>() == TypeId::of::<A>() {
+			// SAFETY: `B` is `A`, since every `Actor` in the tree is `'static` like the root.
+			(self.f)(unsafe { &mut *(actor as *mut B as *mut A) });
+		}
+	}
+}
+
+/// Calls `f` with a [`Context`] on every [`Actor`] of type `A`, like a [`Receiver`].
+struct CallbackVisitor<A, R, F> {
+	f: F,
+	root: *mut R,
+	dispatch: *const Dispatch<R>,
+	visited: Visited,
+	called: usize,
+	phantom: PhantomData<fn(&mut A)>,
+}
+
+impl<M, R, A, F> ActorVisitor<M, R> for CallbackVisitor<A, R, F>
+where
+	A: 'static,
+	F: FnMut(&mut A, Context<A, R>),
+{
+	#[inline(always)]
+	fn visit<B>(&mut self, actor: &mut B)
+	where
+		B: Actor + Receiver<M, R>,
+	{
+		if type_id::<B>() != TypeId::of::<A>() || !self.visited.insert(actor) {
+			return;
+		}
+		// SAFETY: `B` is `A`, since every `Actor` in the tree is `'static` like the root.
+		let actor = unsafe { &mut *(actor as *mut B as *mut A) };
+		let _receiving = checked::Receiving::enter(actor, self.root);
+		(self.f)(actor, Context::with_dispatch(self.root, self.dispatch));
+		// SAFETY: The dispatch outlives the visitor.
+		if let Some(dispatch) = unsafe { self.dispatch.as_ref() } {
+			dispatch.record::<A, F>();
+		}
+		self.called += 1;
+	}
+
+	#[inline(always)]
+	fn top_down(&self) -> bool {
+		// SAFETY: The dispatch outlives the visitor.
+		unsafe { self.dispatch.as_ref() }.is_some_and(Dispatch::top_down)
+	}
+}
+
+/// Only visits the [`Actor`]s whose [`TraitTagged`] tags contain `mask`.
+struct TagVisitor<'a, M, R> {
+	inner: CountingVisitor<'a, M, R>,
+	mask: u64,
+}
+
+impl<M, R> ActorVisitor<M, R> for TagVisitor<'_, M, R> {
+	#[inline(always)]
+	fn visit<A>(&mut self, actor: &mut A)
+	where
+		A: Actor + Receiver<M, R>,
+	{
+		if <A as TraitTagged>::trait_tags() & self.mask != 0 {
+			self.inner.visit(actor);
+		}
+	}
+
+	#[inline(always)]
+	fn removes(&self) -> bool { self.inner.removes() }
+
+	#[inline(always)]
+	fn is_removed<B>(&self, actor: &B) -> bool { self.inner.is_removed(actor) }
+
+	#[inline(always)]
+	fn edit<C>(&mut self, collection: &mut C) { self.inner.edit(collection) }
+
+	#[inline(always)]
+	fn skips<B>(&self) -> bool { self.inner.skips::<B>() }
+
+	#[inline(always)]
+	fn top_down(&self) -> bool { self.inner.top_down() }
+
+	#[inline(always)]
+	fn prunes<B: ?Sized>(&self, actor: &B) -> bool { self.inner.prunes(actor) }
+
+	#[inline(always)]
+	fn handled(&self) -> Option<usize> { self.inner.handled() }
+
+	#[inline(always)]
+	fn mutates(&self) -> bool { self.inner.mutates() }
+}
+
+struct TraitVisitor<'a, T: ?Sized, M, F> {
+	message: &'a mut M,
+	filter: &'a TraitFilter<T>,
+	f: F,
+	sent: usize,
+}
+
+impl<N, R, T: ?Sized, M, F> ActorVisitor<N, R> for TraitVisitor<'_, T, M, F>
+where
+	F: FnMut(&mut T, &mut M),
+{
+	#[inline(always)]
+	fn visit<A>(&mut self, actor: &mut A)
+	where
+		A: Actor + Receiver<N, R>,
+	{
+		if let Some(actor) = self.filter.cast(type_id::<A>(), actor as *mut A as *mut ()) {
+			// SAFETY: The filter only casts to the same `Actor`, and every `Actor` in the tree is `'static` like the
+			// root, so it is the registered type.
+			(self.f)(unsafe { &mut *actor }, self.message);
+			self.sent += 1;
+		}
+	}
+}
+
+struct ViewVisitor<'a, V> {
+	view: &'a V,
+	viewed: usize,
+}
+
+impl<V, R> ActorVisitor<V, R> for ViewVisitor<'_, V> {
+	#[inline(always)]
+	fn visit<A>(&mut self, actor: &mut A)
+	where
+		A: Actor + Receiver<V, R>,
+	{
+		VIEW_IGNORED.set(false);
+		<A as ReceiverRef<V>>::receive_ref(actor, self.view);
+		if !VIEW_IGNORED.get() {
+			self.viewed += 1;
+		}
+	}
+}
+
+struct CountVisitor(usize);
+
+impl<M, R> ActorVisitor<M, R> for CountVisitor {
+	#[inline(always)]
+	fn visit<A>(&mut self, _: &mut A)
+	where
+		A: Actor + Receiver<M, R>,
+	{
+		self.0 += 1;
+	}
+
+	#[inline(always)]
+	fn mutates(&self) -> bool { false }
+}
+
+struct FoldVisitor<'a, M, R, Acc, F> {
+	message: &'a mut M,
+	root: *mut R,
+	dispatch: *const Dispatch<R>,
+	visited: Visited,
+	acc: Option<Acc>,
+	f: F,
+}
+
+impl<'a, M, R, Acc, F> FoldVisitor<'a, M, R, Acc, F> {
+	fn new(message: &'a mut M, root: *mut R, dispatch: *const Dispatch<R>, init: Acc, f: F) -> Self {
+		Self {
+			message,
+			root,
+			dispatch,
+			visited: Visited::new(),
+			acc: Some(init),
+			f,
+		}
+	}
+
+	/// Get the folded value.
+	fn finish(self) -> Acc { self.acc.unwrap() }
+}
+
+impl<M, R, Acc, F> ActorVisitor<M, R> for FoldVisitor<'_, M, R, Acc, F>
+where
+	M: Query,
+	F: FnMut(Acc, M::Response) -> Acc,
+{
+	#[inline(always)]
+	fn visit<A>(&mut self, actor: &mut A)
+	where
+		A: Actor + Receiver<M, R>,
+	{
+		if !self.visited.insert(actor) {
+			return;
+		}
+		let _receiving = checked::Receiving::enter(actor, self.root);
+		let context = Context::with_dispatch(self.root, self.dispatch);
+		if let Some(response) = actor.respond(self.message, context) {
+			let acc = self.acc.take().unwrap();
+			self.acc = Some((self.f)(acc, response));
+		}
+	}
+
+	#[inline(always)]
+	fn top_down(&self) -> bool {
+		// SAFETY: The dispatch, if there is one, outlives the visitor.
+		unsafe { self.dispatch.as_ref() }.is_some_and(Dispatch::top_down)
+	}
+}
+
+/// Forwards every visit, and records whether any of the visited [`Actor`]s handles the message.
+struct HandledVisitor<'a, V> {
+	inner: &'a mut V,
+	handled: bool,
+}
+
+impl<'a, V> HandledVisitor<'a, V> {
+	#[inline(always)]
+	fn new(inner: &'a mut V) -> Self { Self { inner, handled: false } }
+}
+
+impl<M, R, V> ActorVisitor<M, R> for HandledVisitor<'_, V>
+where
+	V: ActorVisitor<M, R>,
+{
+	#[inline(always)]
+	fn visit<A>(&mut self, actor: &mut A)
+	where
+		A: Actor + Receiver<M, R>,
+	{
+		let handled = self.inner.handled();
+		self.inner.visit(actor);
+		self.handled |= self.inner.handled() != handled;
+	}
+
+	#[inline(always)]
+	fn skips<A>(&self) -> bool { self.inner.skips::<A>() }
+
+	#[inline(always)]
+	fn removes(&self) -> bool { self.inner.removes() }
+
+	#[inline(always)]
+	fn is_removed<B>(&self, actor: &B) -> bool { self.inner.is_removed(actor) }
+
+	#[inline(always)]
+	fn edit<C>(&mut self, collection: &mut C) { self.inner.edit(collection) }
+
+	#[inline(always)]
+	fn top_down(&self) -> bool { self.inner.top_down() }
+
+	#[inline(always)]
+	fn prunes<B: ?Sized>(&self, actor: &B) -> bool { self.inner.prunes(actor) }
+
+	#[inline(always)]
+	fn handled(&self) -> Option<usize> { self.inner.handled() }
+
+	#[inline(always)]
+	fn mutates(&self) -> bool { self.inner.mutates() }
+}
+
+/// Forwards every visit except the one to a specific [`Actor`] of type `A`, and optionally the ones to the [`Actor`]s
+/// that contain it, like its parent.
+struct ExceptVisitor<'a, V, A> {
+	inner: &'a mut V,
+	except: *const A,
+	enclosing: bool,
+}
+
+impl<'a, V, A> ExceptVisitor<'a, V, A> {
+	#[inline(always)]
+	fn new(inner: &'a mut V, except: *const A) -> Self {
+		Self {
+			inner,
+			except,
+			enclosing: false,
+		}
+	}
+
+	/// Also skip the [`Actor`]s whose memory contains the one at `except`.
+	#[inline(always)]
+	fn enclosing(inner: &'a mut V, except: *const A) -> Self {
+		Self {
+			inner,
+			except,
+			enclosing: true,
+		}
+	}
+}
+
+impl<M, R, V, A> ActorVisitor<M, R> for ExceptVisitor<'_, V, A>
+where
+	V: ActorVisitor<M, R>,
+{
+	#[inline(always)]
+	fn visit<B>(&mut self, actor: &mut B)
+	where
+		B: Actor + Receiver<M, R>,
+	{
+		let start = actor as *const B as usize;
+		let except = self.except as usize;
+		let is_except = if self.enclosing {
+			start <= except && except + size_of::<A>() <= start + size_of::<B>()
+		} else {
+			// Sub-`Actor`s can share the address of their parent, so the type is checked too.
+			start == except && type_id::<B>() == type_id::<A>()
+		};
+		if !is_except {
+			self.inner.visit(actor);
+		}
+	}
+
+	#[inline(always)]
+	fn removes(&self) -> bool { self.inner.removes() }
+
+	#[inline(always)]
+	fn is_removed<B>(&self, actor: &B) -> bool { self.inner.is_removed(actor) }
+
+	#[inline(always)]
+	fn skips<B>(&self) -> bool { self.inner.skips::<B>() }
+
+	#[inline(always)]
+	fn edit<C>(&mut self, collection: &mut C) { self.inner.edit(collection) }
+
+	#[inline(always)]
+	fn top_down(&self) -> bool { self.inner.top_down() }
+
+	#[inline(always)]
+	fn prunes<B: ?Sized>(&self, actor: &B) -> bool { self.inner.prunes(actor) }
+
+	#[inline(always)]
+	fn handled(&self) -> Option<usize> { self.inner.handled() }
+
+	#[inline(always)]
+	fn mutates(&self) -> bool { self.inner.mutates() }
+}
+
+struct ClaimVisitor<'a, M, R> {
+	message: &'a mut M,
+	root: *mut R,
+	dispatch: *const Dispatch<R>,
+	visited: Visited,
+	claimed: Option<&'static str>,
+}
+
+impl<M, R> ActorVisitor<M, R> for ClaimVisitor<'_, M, R>
+where
+	M: Query<Response = bool>,
+{
+	#[inline(always)]
+	fn visit<A>(&mut self, actor: &mut A)
+	where
+		A: Actor + Receiver<M, R>,
+	{
+		if self.claimed.is_some() || !self.visited.insert(actor) {
+			return;
+		}
+		let _receiving = checked::Receiving::enter(actor, self.root);
+		let context = Context::with_dispatch(self.root, self.dispatch);
+		if actor.respond(self.message, context) == Some(true) {
+			self.claimed = Some(std::any::type_name::<A>());
+		}
+	}
+
+	#[inline(always)]
+	fn top_down(&self) -> bool {
+		// SAFETY: The dispatch, if there is one, outlives the visitor.
+		unsafe { self.dispatch.as_ref() }.is_some_and(Dispatch::top_down)
+	}
+}
+
+/// A macro for easily implementing [`Receiver`] for your types.
+///
+/// ## Examples:
+/// A type without generics:
+/// ```
+/// # #![feature(min_specialization)]
+/// # use send::receive;
+///
+/// struct MyActor;
+/// struct MyMessage;
+///
+/// receive! {
+///     MyMessage => MyActor = (&mut self, _message, _context) {
+///         // Some code here
+///     }
+/// }
+/// ```
+///
+/// With generics:
+/// ```
+/// # #![feature(min_specialization)]
 /// # use send::receive;
 ///
 /// struct MyActor;
 /// struct MyMessage<const Idx: u8>;
 ///
 /// receive! {
-/// 	%(const Idx: u8) MyMessage<Idx> => MyActor = (&mut self, _message, _context) {
-/// 		// Some code here
-/// 	}
+///     %(const Idx: u8) MyMessage<Idx> => MyActor = (&mut self, _message, _context) {
+///         // Some code here
+///     }
 /// }
 /// ```
 /// or just:
@@ -178,26 +2255,305 @@ impl<M, R> ActorVisitor<M, R> for MessageVisitor<'_, M, R> {
 /// struct MyMessage<const Idx: u8>;
 ///
 /// receive! {
-/// 	MyMessage<1> => MyActor = (&mut self, _message, _context) {
-/// 		// Some code here
-/// 	}
+///     MyMessage<1> => MyActor = (&mut self, _message, _context) {
+///         // Some code here
+///     }
 /// }
 /// ```
 ///
 /// Note the funky `%(...)` syntax. This is due to declarative macro limitations.
+///
+/// Only handling messages that match a pattern, with an optional guard:
+/// ```
+/// # #![feature(min_specialization)]
+/// # use send::receive;
+///
+/// struct Player;
+/// enum Command {
+///     Move { dx: i32, dy: i32 },
+///     Jump,
+/// }
+///
+/// receive! {
+///     Command => Player = match (&mut self, _context) {
+///         Command::Move { dx, dy } if *dx != 0 => {
+///             // Some code here
+///         }
+///         Command::Jump => {
+///             // Some code here
+///         }
+///     }
+/// }
+/// ```
+/// Messages that don't match any pattern are ignored.
+///
+/// An [`AsyncReceiver`] for [`Framework::send_async`]:
+/// ```
+/// # #![feature(min_specialization)]
+/// # use send::receive;
+///
+/// struct MyActor;
+/// struct MyMessage;
+///
+/// receive! {
+///     MyMessage => MyActor = async (&mut self, _message, _context) {
+///         // Some code here, which can `.await`
+///     }
+/// }
+/// ```
+///
+/// A [`Responder`] for a [`Query`], where the block evaluates to the response:
+/// ```
+/// # #![feature(min_specialization)]
+/// # use send::{receive, Query};
+///
+/// struct Body {
+///     mass: f64,
+/// }
+/// struct Mass;
+///
+/// impl Query for Mass {
+///     type Response = f64;
+/// }
+///
+/// receive! {
+///     Mass => Body = respond (&mut self, _query, _context) {
+///         self.mass
+///     }
+/// }
+/// ```
+///
+/// Handlers can require the root [`Actor`] to implement a trait, by naming its type in a `where` clause after the
+/// target type, like to read global settings from it. This needs `#![feature(specialization)]`, since
+/// `min_specialization` can't specialize on trait bounds:
+/// ```
+/// # #![feature(specialization)]
+/// # #![feature(negative_impls)]
+/// # #![allow(incomplete_features)]
+/// # use send::{receive, Actor, Framework};
+/// trait Clocked {
+///     const TICK_MS: u64;
+/// }
+///
+/// #[derive(Actor)]
+/// struct Simulation {
+///     timer: Timer,
+/// }
+///
+/// impl Clocked for Simulation {
+///     const TICK_MS: u64 = 20;
+/// }
+///
+/// #[derive(Actor)]
+/// struct Timer {
+///     elapsed: u64,
+/// }
+///
+/// struct Tick;
+///
+/// receive! {
+///     Tick => Timer where Root: Clocked = (&mut self, _tick, _context) {
+///         self.elapsed += Root::TICK_MS;
+///     }
+/// }
+///
+/// let mut framework = Framework::new(Simulation { timer: Timer { elapsed: 0 } });
+/// framework.send(&mut Tick);
+/// assert_eq!(framework.get().timer.elapsed, 20);
+///
+/// // Other roots get the default `Receiver`, which does nothing.
+/// let mut framework = Framework::new(Timer { elapsed: 0 });
+/// framework.send(&mut Tick);
+/// assert_eq!(framework.get().elapsed, 0);
+/// ```
+///
+/// Common syntax mistakes, like a missing `=` or `%`, get an error that says what was expected:
+/// ```compile_fail
+/// # #![feature(min_specialization)]
+/// # use send::receive;
+///
+/// struct MyActor;
+/// struct MyMessage;
+///
+/// receive! {
+///     // error: expected `=` after the target type `MyActor` in receive!
+///     MyMessage => MyActor (&mut self, _message, _context) {}
+/// }
+/// ```
 #[macro_export]
 macro_rules! receive {
-	($(%$generics:tt)? $message_ty:ty => $on:ty = (&mut $self:ident, $message:pat, $context:pat) $code:block $($rest:tt)*) => {
-		$crate::receive! { $message_ty, $on, $self, $message, $context, $code, $($generics)? }
+	// Must come before the other arms, since `$on:ty` would try to parse `MyActor (...)` as `Fn(...)` sugar.
+	($(%$generics:tt)? $message_ty:ty => $on:ident (&mut $($_args:tt)*) $($_rest:tt)*) => {
+		compile_error!(concat!("expected `=` after the target type `", stringify!($on), "` in receive!"));
+	};
+
+	($(%$generics:tt)? $message_ty:ty => $on:ty = $($_kind:ident)? | $($_rest:tt)*) => {
+		compile_error!("expected `(&mut self, message, context)` in receive!, found closure syntax `|...|`");
+	};
+
+	($(%$generics:tt)? $message_ty:ty => $on:ty = $($_kind:ident)? (self $($_args:tt)*) $($_rest:tt)*) => {
+		compile_error!("expected `&mut self` in receive!, found `self`");
+	};
+
+	// Name the root type, so the arms below always have one.
+	($(%$generics:tt)? $message_ty:ty => $on:ty where $root:ident: $bound:path = $($rest:tt)*) => {
+		$crate::receive! { @root [$root: $bound] $(%$generics)? $message_ty => $on = $($rest)* }
+	};
+
+	($(%$generics:tt)? $message_ty:ty => $on:ty = $($rest:tt)*) => {
+		$crate::receive! { @root [_RootTy] $(%$generics)? $message_ty => $on = $($rest)* }
+	};
+
+	(@root $root:tt $(%$generics:tt)? $message_ty:ty => $on:ty = async (&mut $self:ident, $message:pat, $context:pat) $code:block $($rest:tt)*) => {
+		$crate::receive! { async $message_ty, $on, $self, $message, $context, $code, $root, $($generics)? }
+
+		$crate::receive! { $($rest)* }
+	};
+
+	(@root $root:tt $(%$generics:tt)? $message_ty:ty => $on:ty = respond (&mut $self:ident, $message:pat, $context:pat) $code:block $($rest:tt)*) => {
+		$crate::receive! { respond $message_ty, $on, $self, $message, $context, $code, $root, $($generics)? }
+
+		$crate::receive! { $($rest)* }
+	};
+
+	(@root $root:tt $(%$generics:tt)? $message_ty:ty => $on:ty = match (&mut $self:ident, $context:pat) {
+		$($pattern:pat $(if $guard:expr)? => $body:block)*
+	} $($rest:tt)*) => {
+		$crate::receive! {
+			$message_ty, $on, $self, message, $context,
+			{
+				match message {
+					$($pattern $(if $guard)? => $body)*
+					#[allow(unreachable_patterns)]
+					_ => {},
+				}
+			},
+			$root,
+			$($generics)?
+		}
+
+		$crate::receive! { $($rest)* }
+	};
+
+	(@root $root:tt $(%$generics:tt)? $message_ty:ty => $on:ty = (&mut $self:ident, $message:pat, $context:pat) $code:block $($rest:tt)*) => {
+		$crate::receive! { $message_ty, $on, $self, $message, $context, $code, $root, $($generics)? }
 
 		$crate::receive! { $($rest)* }
 	};
 
-	($message_ty:ty, $on:ty, $self:ident, $message:pat, $context:pat, $code:block, $( ( $($generics:tt)* ) )?) => {
-        impl<_RootTy, $($($generics)*)?> $crate::Receiver<$message_ty, _RootTy> for $on {
-            fn receive(&mut $self, $message: &mut $message_ty, $context: $crate::Context<$on, _RootTy>) $code
+	// An empty bound list after the root type is allowed, so there is no need for separate arms without a bound.
+	($message_ty:ty, $on:ty, $self:ident, $message:pat, $context:pat, $code:block, [$root:ident $(: $bound:path)?], $( ( $($generics:tt)* ) )?) => {
+        impl<$($($generics)*,)? $root> $crate::Receiver<$message_ty, $root> for $on
+        where
+            $root: $($bound)?
+        {
+            fn receive(&mut $self, $message: &mut $message_ty, $context: $crate::Context<$on, $root>) $code
+        }
+
+        impl<$($($generics)*,)? $root> $crate::Handles<$message_ty, $root> for $on
+        where
+            $root: $($bound)?
+        {
+            fn handles() -> bool { true }
+        }
+    };
+
+	(async $message_ty:ty, $on:ty, $self:ident, $message:pat, $context:pat, $code:block, [$root:ident $(: $bound:path)?], $( ( $($generics:tt)* ) )?) => {
+        impl<$($($generics)*,)? $root> $crate::AsyncReceiver<$message_ty, $root> for $on
+        where
+            $root: $($bound)?
+        {
+            fn receive<'__receive>(
+                &'__receive mut $self, $message: &'__receive mut $message_ty, $context: $crate::Context<$on, $root>,
+            ) -> ::std::pin::Pin<::std::boxed::Box<dyn ::std::future::Future<Output = ()> + '__receive>>
+            where
+                $root: '__receive,
+            {
+                ::std::boxed::Box::pin(async move $code)
+            }
+        }
+    };
+
+	(respond $message_ty:ty, $on:ty, $self:ident, $message:pat, $context:pat, $code:block, [$root:ident $(: $bound:path)?], $( ( $($generics:tt)* ) )?) => {
+        impl<$($($generics)*,)? $root> $crate::Responder<$message_ty, $root> for $on
+        where
+            $root: $($bound)?
+        {
+            fn respond(
+                &mut $self, $message: &mut $message_ty, $context: $crate::Context<$on, $root>,
+            ) -> ::std::option::Option<<$message_ty as $crate::Query>::Response> {
+                ::std::option::Option::Some($code)
+            }
         }
     };
 
 	() => {};
+
+	($(%$generics:tt)? $message_ty:ident -> $($_rest:tt)*) => {
+		compile_error!(concat!(
+			"expected `=>` after the message type `", stringify!($message_ty), "` in receive!, found `->`"
+		));
+	};
+
+	(($($generics:tt)*) $message_ty:ident $($_rest:tt)*) => {
+		compile_error!(concat!(
+			"expected `%` before the generics `(", stringify!($($generics)*), ")` in receive!"
+		));
+	};
+}
+
+/// A macro for running hooks around the [`Receiver`] of an [`Actor`] wrapped in a [`Wrap`], like for logging or
+/// validation, without changing the [`Receiver`] itself. Both hooks are optional, and get the message.
+///
+/// ## Examples:
+/// ```
+/// # #![feature(min_specialization)]
+/// # use send::{receive, wrap_receiver};
+///
+/// struct Counter(u32);
+/// struct Add(u32);
+///
+/// receive! {
+///     Add => Counter = (&mut self, add, _) {
+///         self.0 += add.0;
+///     }
+/// }
+///
+/// wrap_receiver! {
+///     before: |add| { assert!(add.0 < 100, "too much") },
+///     after: |add| { println!("added {}", add.0) },
+///     Add => Counter
+/// }
+/// ```
+// Skipped by rustfmt, which would format the `|...|` in the matchers like closures.
+#[rustfmt::skip]
+#[macro_export]
+macro_rules! wrap_receiver {
+	(before: |$before_message:pat_param| $before:block, after: |$after_message:pat_param| $after:block, $message_ty:ty => $on:ty $(,)?) => {
+		$crate::wrap_receiver! { @impl $message_ty, $on, |$before_message| $before, |$after_message| $after }
+	};
+
+	(before: |$before_message:pat_param| $before:block, $message_ty:ty => $on:ty $(,)?) => {
+		$crate::wrap_receiver! { @impl $message_ty, $on, |$before_message| $before, |_| {} }
+	};
+
+	(after: |$after_message:pat_param| $after:block, $message_ty:ty => $on:ty $(,)?) => {
+		$crate::wrap_receiver! { @impl $message_ty, $on, |_| {}, |$after_message| $after }
+	};
+
+	(@impl $message_ty:ty, $on:ty, |$before_message:pat_param| $before:block, |$after_message:pat_param| $after:block) => {
+		impl<_RootTy> $crate::Receiver<$message_ty, _RootTy> for $crate::Wrap<$on> {
+			fn receive(&mut self, message: &mut $message_ty, context: $crate::Context<Self, _RootTy>) {
+				{
+					let $before_message = &mut *message;
+					$before
+				}
+				self.receive_inner(message, context);
+				{
+					let $after_message = &mut *message;
+					$after
+				}
+			}
+		}
+	};
 }
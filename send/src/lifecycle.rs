@@ -0,0 +1,21 @@
+/// Sent to an [`Actor`](crate::Actor) and its sub-[`Actor`](crate::Actor)s when they are added to the tree at
+/// runtime, for example with [`Context::spawn`](crate::Context::spawn), or when a saved tree is loaded with
+/// `Framework::load`.
+///
+/// Implement [`Receiver<Mount, R>`](crate::Receiver) to run setup code.
+pub struct Mount;
+
+/// Sent to an [`Actor`](crate::Actor) and its sub-[`Actor`](crate::Actor)s right before they are removed from the
+/// tree at runtime, for example with [`Context::despawn`](crate::Context::despawn).
+///
+/// Implement [`Receiver<Unmount, R>`](crate::Receiver) to run teardown code.
+pub struct Unmount;
+
+/// Resets every [`Actor`](crate::Actor) marked with `#[actor(resettable)]` to its [`Default`] when broadcast.
+///
+/// Resetting an [`Actor`](crate::Actor) also resets its sub-[`Actor`](crate::Actor)s to what its [`Default`] holds,
+/// whether or not they are resettable themselves. By default, sub-[`Actor`](crate::Actor)s receive it first, so that
+/// is where they end up. But with [`Framework::set_top_down`](crate::Framework::set_top_down), or for types with
+/// `#[actor(top_down)]`, the [`Actor`](crate::Actor) is reset first, and the resettable sub-[`Actor`](crate::Actor)s
+/// of the new value are then reset to their own [`Default`].
+pub struct Reset;
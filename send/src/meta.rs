@@ -0,0 +1,42 @@
+//! Messages with metadata, for [`Framework::send_meta`](crate::Framework::send_meta).
+
+use std::ops::{Deref, DerefMut};
+use std::time::Instant;
+
+/// A message with metadata that is filled in by [`Framework::send_meta`](crate::Framework::send_meta), like for
+/// event sourcing.
+///
+/// [`Actor`](crate::Actor)s receive the whole `Meta<M>`, so they handle it with `Meta<M> => MyActor` in
+/// [`receive!`](crate::receive). It dereferences to the message. A `Meta` without a message is the metadata alone,
+/// like from [`Context::meta`](crate::Context::meta).
+#[derive(Clone, Copy, Debug)]
+pub struct Meta<M = ()> {
+	/// The number of messages sent with [`send_meta`](crate::Framework::send_meta) before this one, by the same
+	/// [`Framework`](crate::Framework).
+	pub sequence: u64,
+	/// When the message was sent.
+	pub time: Instant,
+	/// The message itself.
+	pub message: M,
+}
+
+impl<M> Meta<M> {
+	/// The metadata, without the message.
+	pub fn meta(&self) -> Meta {
+		Meta {
+			sequence: self.sequence,
+			time: self.time,
+			message: (),
+		}
+	}
+}
+
+impl<M> Deref for Meta<M> {
+	type Target = M;
+
+	fn deref(&self) -> &M { &self.message }
+}
+
+impl<M> DerefMut for Meta<M> {
+	fn deref_mut(&mut self) -> &mut M { &mut self.message }
+}
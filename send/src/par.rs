@@ -0,0 +1,49 @@
+//! Sending messages to the elements of a collection in parallel with `rayon`, with [`Framework::send_par`].
+
+use rayon::prelude::*;
+
+use crate::{Actor, Framework};
+
+/// A message that can be sent to many [`Actor`]s at once with [`Framework::send_par`], like a per-frame update of
+/// independent entities.
+///
+/// Every thread reads the same message, so it is only borrowed immutably.
+pub trait ParMessage: Sync {}
+
+/// Receive a [`ParMessage`] in parallel with the other elements of a collection.
+///
+/// Unlike a [`Receiver`](crate::Receiver), this doesn't get a [`Context`](crate::Context), since sending messages
+/// from several threads at once would alias the tree. Handlers can only mutate `self`.
+pub trait ParReceiver<M: ParMessage>: Send {
+	/// Receive the message.
+	fn receive_par(&mut self, message: &M);
+}
+
+impl<R, X> Framework<R, X>
+where
+	R: Actor + 'static,
+	X: 'static,
+{
+	/// Send a message to every element of a collection of [`Actor`]s in parallel, split into one chunk for every
+	/// thread of the `rayon` thread pool, and return the number of elements.
+	///
+	/// Only the elements themselves receive the message, not their sub-[`Actor`]s, and no other [`Actor`] receives
+	/// it.
+	///
+	/// `getter`: A function that takes in the root and outputs the elements to send the message to.
+	pub fn send_par<M, F, T>(&mut self, message: &M, getter: F) -> usize
+	where
+		M: ParMessage,
+		F: FnOnce(&mut R) -> &mut [T],
+		T: ParReceiver<M>,
+	{
+		let elements = getter(&mut self.root);
+		let chunk = elements.len().div_ceil(rayon::current_num_threads()).max(1);
+		elements.par_chunks_mut(chunk).for_each(|chunk| {
+			for element in chunk {
+				element.receive_par(message);
+			}
+		});
+		elements.len()
+	}
+}
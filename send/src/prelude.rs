@@ -0,0 +1,12 @@
+//! The commonly used parts of `send`.
+//!
+//! ```
+//! # #![feature(min_specialization)]
+//! # #![feature(negative_impls)]
+//! use send::prelude::*;
+//! ```
+//!
+//! [`ActorVisitor`](crate::ActorVisitor) is not included, since it is only needed when implementing [`Actor`] by
+//! hand.
+
+pub use crate::{receive, Actor, Context, Framework, NotActor, Receiver};
@@ -0,0 +1,135 @@
+//! Sending messages by name at runtime, for [`Framework::send_named`](crate::Framework::send_named).
+
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::rc::Rc;
+
+use crate::{Actor, Framework};
+
+/// An argument for a message that is sent by name, like from a console command.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+	/// An integer.
+	Int(i64),
+	/// A number that isn't an integer.
+	Float(f64),
+	/// Anything else, like a name.
+	String(String),
+}
+
+impl Value {
+	/// Parse a console token as an integer, or else as a float, or else keep it as a string.
+	pub fn parse(token: &str) -> Self {
+		if let Ok(int) = token.parse() {
+			Self::Int(int)
+		} else if let Ok(float) = token.parse() {
+			Self::Float(float)
+		} else {
+			Self::String(token.to_string())
+		}
+	}
+
+	/// Get the integer, if this is one.
+	pub fn as_int(&self) -> Option<i64> {
+		match self {
+			Self::Int(int) => Some(*int),
+			_ => None,
+		}
+	}
+
+	/// Get the number as a float, if this is one, including integers.
+	pub fn as_float(&self) -> Option<f64> {
+		match self {
+			Self::Int(int) => Some(*int as f64),
+			Self::Float(float) => Some(*float),
+			Self::String(_) => None,
+		}
+	}
+
+	/// Get the string, if this is one.
+	pub fn as_str(&self) -> Option<&str> {
+		match self {
+			Self::String(string) => Some(string),
+			_ => None,
+		}
+	}
+}
+
+impl From<i64> for Value {
+	fn from(int: i64) -> Self { Self::Int(int) }
+}
+
+impl From<f64> for Value {
+	fn from(float: f64) -> Self { Self::Float(float) }
+}
+
+impl From<&str> for Value {
+	fn from(string: &str) -> Self { Self::String(string.to_string()) }
+}
+
+impl From<String> for Value {
+	fn from(string: String) -> Self { Self::String(string) }
+}
+
+/// An error from sending a message by name with [`Framework::send_named`](crate::Framework::send_named).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MessageError {
+	/// No message is registered with the name.
+	Unknown(String),
+	/// The arguments don't fit the message, with a description of what was expected.
+	Arguments(String),
+}
+
+impl Display for MessageError {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		match self {
+			Self::Unknown(name) => write!(f, "no message is registered as `{name}`"),
+			Self::Arguments(expected) => write!(f, "invalid arguments: {expected}"),
+		}
+	}
+}
+
+impl std::error::Error for MessageError {}
+
+type SendFn<R, X> = Rc<dyn Fn(&mut Framework<R, X>, &[Value]) -> Result<usize, MessageError>>;
+
+/// The messages that can be sent by name with [`Framework::send_named`](crate::Framework::send_named), like from a
+/// console, with the functions that construct them from their arguments.
+pub struct MessageRegistry<R, X = ()> {
+	messages: HashMap<String, SendFn<R, X>>,
+}
+
+impl<R, X> MessageRegistry<R, X>
+where
+	R: Actor + 'static,
+	X: 'static,
+{
+	/// Register a message by name, replacing any message that was registered with the same name before.
+	///
+	/// `constructor`: A function that constructs the message from its arguments, or returns
+	/// [`MessageError::Arguments`] if they don't fit.
+	pub fn register<M>(
+		&mut self, name: impl Into<String>, constructor: impl Fn(&[Value]) -> Result<M, MessageError> + 'static,
+	) {
+		let send = move |framework: &mut Framework<R, X>, args: &[Value]| Ok(framework.send(&mut constructor(args)?));
+		self.messages.insert(name.into(), Rc::new(send));
+	}
+
+	/// The names of the registered messages, in no particular order.
+	pub fn names(&self) -> impl Iterator<Item = &str> { self.messages.keys().map(String::as_str) }
+
+	pub(crate) fn get(&self, name: &str) -> Result<SendFn<R, X>, MessageError> {
+		self.messages
+			.get(name)
+			.cloned()
+			.ok_or_else(|| MessageError::Unknown(name.to_string()))
+	}
+}
+
+impl<R, X> Default for MessageRegistry<R, X> {
+	fn default() -> Self {
+		Self {
+			messages: HashMap::new(),
+		}
+	}
+}
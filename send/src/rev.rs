@@ -0,0 +1,44 @@
+use crate::{Actor, ActorVisitor};
+
+/// A tuple of [`Actor`]s that are visited in reverse order, so `Rev<(A, B, C)>` visits `C`, then `B`, then `A`.
+///
+/// This only changes the order of the elements of the tuple, not the order inside each of them.
+/// Paths still use the indices of the tuple, so `"0"` is `A`.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Rev<T>(pub T);
+
+macro_rules! rev {
+	($(($($ty:ident $index:tt),+))*) => {
+		$(
+			unsafe impl<$($ty),+> Actor for Rev<($($ty,)+)> {
+				#[inline(always)]
+				fn accept<T, R>(&mut self, visitor: &mut impl ActorVisitor<T, R>) {
+					rev!(@reverse self visitor [$($index)+] []);
+				}
+
+				#[inline(always)]
+				fn accept_path<T, R>(&mut self, path: &[&str], visitor: &mut impl ActorVisitor<T, R>) -> bool {
+					self.0.accept_path(path, visitor)
+				}
+			}
+		)*
+	};
+
+	(@reverse $self:ident $visitor:ident [$first:tt $($rest:tt)*] [$($reversed:tt)*]) => {
+		rev!(@reverse $self $visitor [$($rest)*] [$first $($reversed)*])
+	};
+
+	(@reverse $self:ident $visitor:ident [] [$($index:tt)*]) => {
+		$($self.0.$index.accept($visitor);)*
+	};
+}
+
+rev! {
+	(A 0, B 1)
+	(A 0, B 1, C 2)
+	(A 0, B 1, C 2, D 3)
+	(A 0, B 1, C 2, D 3, E 4)
+	(A 0, B 1, C 2, D 3, E 4, F 5)
+	(A 0, B 1, C 2, D 3, E 4, F 5, G 6)
+	(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7)
+}
@@ -0,0 +1,73 @@
+//! Deterministic random numbers for every [`Actor`], for [`Context::rng`](crate::Context::rng).
+//!
+//! [`Actor`]s don't know their path, so an [`Actor`] is identified by its type and by how many [`Actor`]s of the same
+//! type are visited before it, which stays the same across runs as long as the tree is built the same way.
+
+use rand_chacha::rand_core::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::types::type_id;
+use crate::{Actor, ActorVisitor, Framework, Receiver};
+
+impl<R, X> Framework<R, X>
+where
+	R: Actor + 'static,
+	X: 'static,
+{
+	/// Set the seed that the random number generators from [`Context::rng`](crate::Context::rng) are derived from.
+	/// The default is `0`.
+	pub fn set_seed(&mut self, seed: u64) { self.settings.seed = seed; }
+}
+
+/// Counts the [`Actor`]s of type `S` that are visited before the one at `target`.
+pub(crate) struct OrdinalVisitor<S> {
+	target: *const S,
+	count: usize,
+	found: bool,
+}
+
+impl<S> OrdinalVisitor<S> {
+	pub fn new(target: *const S) -> Self {
+		Self {
+			target,
+			count: 0,
+			found: false,
+		}
+	}
+
+	/// The position of the target among the [`Actor`]s of its type, or `None` if it wasn't visited.
+	pub fn ordinal(&self) -> Option<usize> { self.found.then_some(self.count) }
+}
+
+impl<M, R, S> ActorVisitor<M, R> for OrdinalVisitor<S> {
+	fn visit<A>(&mut self, actor: &mut A)
+	where
+		A: Actor + Receiver<M, R>,
+	{
+		if self.found || type_id::<A>() != type_id::<S>() {
+			return;
+		}
+		if actor as *const A as *const () == self.target as *const () {
+			self.found = true;
+		} else {
+			self.count += 1;
+		}
+	}
+
+	#[inline(always)]
+	fn mutates(&self) -> bool { false }
+}
+
+/// Create the random number generator of the `ordinal`th [`Actor`] of type `name`, in the `dispatch`th dispatch.
+pub(crate) fn generator(seed: u64, dispatch: u64, name: &str, ordinal: usize) -> ChaCha8Rng {
+	// FNV-1a, since the hashers in `std` aren't guaranteed to be stable across releases.
+	let name = name.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+		(hash ^ byte as u64).wrapping_mul(0x100_0000_01b3)
+	});
+
+	let mut bytes = [0; 32];
+	for (chunk, word) in bytes.chunks_exact_mut(8).zip([seed, dispatch, name, ordinal as u64]) {
+		chunk.copy_from_slice(&word.to_le_bytes());
+	}
+	ChaCha8Rng::from_seed(bytes)
+}
@@ -0,0 +1,71 @@
+/// Access to one of the roots of a [`Framework`](super::Framework) with several independent [`Actor`](super::Actor)
+/// trees.
+///
+/// A `Framework<(A, B, C)>` is the supported way to have several roots. Every tree receives broadcasts, including
+/// the ones sent by handlers in other trees, since the tuple is the root that every [`Context`](super::Context) sees.
+/// This is implemented for tuples of up to 8 roots, where `I` is the index of the root in the tuple.
+pub trait TupleRoot<const I: usize> {
+	type Root;
+
+	/// Get a reference to the root at index `I`.
+	fn root(&self) -> &Self::Root;
+
+	/// Get a mutable reference to the root at index `I`.
+	fn root_mut(&mut self) -> &mut Self::Root;
+}
+
+macro_rules! tuple_root {
+	($index:tt => $root:ident in ($($ty:ident),*)) => {
+		impl<$($ty),*> TupleRoot<$index> for ($($ty,)*) {
+			type Root = $root;
+
+			fn root(&self) -> &$root { &self.$index }
+
+			fn root_mut(&mut self) -> &mut $root { &mut self.$index }
+		}
+	};
+}
+
+tuple_root!(0 => A in (A));
+
+tuple_root!(0 => A in (A, B));
+tuple_root!(1 => B in (A, B));
+
+tuple_root!(0 => A in (A, B, C));
+tuple_root!(1 => B in (A, B, C));
+tuple_root!(2 => C in (A, B, C));
+
+tuple_root!(0 => A in (A, B, C, D));
+tuple_root!(1 => B in (A, B, C, D));
+tuple_root!(2 => C in (A, B, C, D));
+tuple_root!(3 => D in (A, B, C, D));
+
+tuple_root!(0 => A in (A, B, C, D, E));
+tuple_root!(1 => B in (A, B, C, D, E));
+tuple_root!(2 => C in (A, B, C, D, E));
+tuple_root!(3 => D in (A, B, C, D, E));
+tuple_root!(4 => E in (A, B, C, D, E));
+
+tuple_root!(0 => A in (A, B, C, D, E, F));
+tuple_root!(1 => B in (A, B, C, D, E, F));
+tuple_root!(2 => C in (A, B, C, D, E, F));
+tuple_root!(3 => D in (A, B, C, D, E, F));
+tuple_root!(4 => E in (A, B, C, D, E, F));
+tuple_root!(5 => F in (A, B, C, D, E, F));
+
+tuple_root!(0 => A in (A, B, C, D, E, F, G));
+tuple_root!(1 => B in (A, B, C, D, E, F, G));
+tuple_root!(2 => C in (A, B, C, D, E, F, G));
+tuple_root!(3 => D in (A, B, C, D, E, F, G));
+tuple_root!(4 => E in (A, B, C, D, E, F, G));
+tuple_root!(5 => F in (A, B, C, D, E, F, G));
+tuple_root!(6 => G in (A, B, C, D, E, F, G));
+
+tuple_root!(0 => A in (A, B, C, D, E, F, G, H));
+tuple_root!(1 => B in (A, B, C, D, E, F, G, H));
+tuple_root!(2 => C in (A, B, C, D, E, F, G, H));
+tuple_root!(3 => D in (A, B, C, D, E, F, G, H));
+tuple_root!(4 => E in (A, B, C, D, E, F, G, H));
+tuple_root!(5 => F in (A, B, C, D, E, F, G, H));
+tuple_root!(6 => G in (A, B, C, D, E, F, G, H));
+tuple_root!(7 => H in (A, B, C, D, E, F, G, H));
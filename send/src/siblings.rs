@@ -0,0 +1,129 @@
+//! Finding the siblings of an [`Actor`](crate::Actor), for [`Context::send_siblings`](crate::Context::send_siblings).
+//!
+//! [`Actor`](crate::Actor)s are visited in post-order, and don't say who their parent is, so the structure of the
+//! tree is rebuilt from the number of sub-[`Actor`](crate::Actor)s that every [`Actor`](crate::Actor) has.
+
+use std::any::type_name;
+use std::vec::IntoIter;
+
+use crate::{Actor, ActorVisitor, CountVisitor, MessageVisitor, Receiver};
+
+/// An [`Actor`] in post-order.
+struct Node {
+	address: *const (),
+	name: &'static str,
+	descendants: usize,
+}
+
+struct NodeVisitor {
+	nodes: Vec<Node>,
+}
+
+impl<M, R> ActorVisitor<M, R> for NodeVisitor {
+	fn visit<A>(&mut self, actor: &mut A)
+	where
+		A: Actor + Receiver<M, R>,
+	{
+		let mut counter = CountVisitor(0);
+		actor.accept::<M, R>(&mut counter);
+		self.nodes.push(Node {
+			address: actor as *const A as *const (),
+			name: type_name::<A>(),
+			// The counter also visited `actor`.
+			descendants: counter.0 - 1,
+		});
+	}
+
+	#[inline(always)]
+	fn mutates(&self) -> bool { false }
+}
+
+/// Find the post-order positions of the siblings of `from` in the tree at `root`, in order.
+///
+/// # Safety
+/// The tree must not be borrowed, except by `from`, which must not be accessed during the call.
+pub(crate) unsafe fn find<S, M, R>(root: *mut R, from: *const S) -> Vec<usize>
+where
+	R: Actor,
+{
+	let mut visitor = NodeVisitor { nodes: Vec::new() };
+	(*root).accept::<M, R>(&mut visitor);
+	let nodes = visitor.nodes;
+
+	// Every node comes right after its sub-trees, so the sub-trees on top of the stack are its children.
+	let mut parents = vec![None; nodes.len()];
+	let mut stack: Vec<usize> = Vec::new();
+	for (index, node) in nodes.iter().enumerate() {
+		let mut remaining = node.descendants;
+		while remaining > 0 {
+			let child = stack.pop().expect("`Actor::accept` visited an inconsistent tree");
+			parents[child] = Some(index);
+			remaining -= nodes[child].descendants + 1;
+		}
+		stack.push(index);
+	}
+
+	let name = type_name::<S>();
+	let Some(this) = nodes
+		.iter()
+		.position(|node| node.address == from as *const () && node.name == name)
+	else {
+		return Vec::new();
+	};
+	(0..nodes.len())
+		.filter(|&index| index != this && parents[index] == parents[this])
+		.collect()
+}
+
+/// Delivers a message to the [`Actor`]s at some post-order positions.
+pub(crate) struct SiblingVisitor<'a, M, R> {
+	inner: MessageVisitor<'a, M, R>,
+	siblings: IntoIter<usize>,
+	next: Option<usize>,
+	index: usize,
+}
+
+impl<'a, M, R> SiblingVisitor<'a, M, R> {
+	pub fn new(inner: MessageVisitor<'a, M, R>, siblings: Vec<usize>) -> Self {
+		let mut siblings = siblings.into_iter();
+		Self {
+			inner,
+			next: siblings.next(),
+			siblings,
+			index: 0,
+		}
+	}
+}
+
+impl<M, R> ActorVisitor<M, R> for SiblingVisitor<'_, M, R> {
+	fn visit<A>(&mut self, actor: &mut A)
+	where
+		A: Actor + Receiver<M, R>,
+	{
+		if self.next == Some(self.index) {
+			self.inner.visit(actor);
+			self.next = self.siblings.next();
+		}
+		self.index += 1;
+	}
+
+	// Nothing is skipped or pruned, since the siblings are found by their position among every `Actor` in the tree.
+	#[inline(always)]
+	fn removes(&self) -> bool { self.inner.removes() }
+
+	#[inline(always)]
+	fn is_removed<B>(&self, actor: &B) -> bool { self.inner.is_removed(actor) }
+
+	#[inline(always)]
+	fn edit<C>(&mut self, collection: &mut C) { self.inner.edit(collection) }
+
+	// The positions of the siblings are in post-order, so the tree is always visited in post-order too.
+	#[inline(always)]
+	fn top_down(&self) -> bool { false }
+
+	#[inline(always)]
+	fn handled(&self) -> Option<usize> { self.inner.handled() }
+
+	#[inline(always)]
+	fn mutates(&self) -> bool { self.inner.mutates() }
+}
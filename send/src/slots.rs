@@ -0,0 +1,91 @@
+use crate::{Actor, ActorVisitor};
+
+/// A fixed-capacity set of [`Actor`]s, stored inline.
+///
+/// Only occupied slots are visited. Unlike an [`Arena`](crate::Arena), indices are plain slot numbers and are reused
+/// as soon as the slot is freed.
+/// Use [`Context::spawn_slot`](crate::Context::spawn_slot) and
+/// [`Context::despawn_slot`](crate::Context::despawn_slot) to run the [`Mount`](crate::Mount) and
+/// [`Unmount`](crate::Unmount) lifecycle messages.
+pub struct Slots<T, const N: usize> {
+	slots: [Option<T>; N],
+}
+
+impl<T, const N: usize> Slots<T, N> {
+	/// Create an empty [`Slots`].
+	pub fn new() -> Self {
+		Self {
+			slots: std::array::from_fn(|_| None),
+		}
+	}
+
+	/// Insert a value into the first free slot, returning its index.
+	/// Returns the value back if every slot is occupied.
+	pub fn insert(&mut self, value: T) -> Result<usize, T> {
+		match self.slots.iter().position(Option::is_none) {
+			Some(index) => {
+				self.slots[index] = Some(value);
+				Ok(index)
+			},
+			None => Err(value),
+		}
+	}
+
+	/// Remove a value, returning it if the slot was occupied.
+	pub fn remove(&mut self, index: usize) -> Option<T> { self.slots.get_mut(index)?.take() }
+
+	/// Get a reference to a value.
+	pub fn get(&self, index: usize) -> Option<&T> { self.slots.get(index)?.as_ref() }
+
+	/// Get a mutable reference to a value.
+	pub fn get_mut(&mut self, index: usize) -> Option<&mut T> { self.slots.get_mut(index)?.as_mut() }
+
+	/// Check if the slot at `index` is occupied.
+	pub fn contains(&self, index: usize) -> bool { self.get(index).is_some() }
+
+	/// The number of occupied slots.
+	pub fn len(&self) -> usize { self.slots.iter().filter(|slot| slot.is_some()).count() }
+
+	/// Check if no slots are occupied.
+	pub fn is_empty(&self) -> bool { self.slots.iter().all(Option::is_none) }
+
+	/// The total number of slots.
+	pub const fn capacity(&self) -> usize { N }
+
+	/// Iterate over all values and their indices.
+	pub fn iter(&self) -> impl Iterator<Item = (usize, &T)> {
+		self.slots
+			.iter()
+			.enumerate()
+			.filter_map(|(index, slot)| slot.as_ref().map(|value| (index, value)))
+	}
+
+	/// Iterate mutably over all values and their indices.
+	pub fn iter_mut(&mut self) -> impl Iterator<Item = (usize, &mut T)> {
+		self.slots
+			.iter_mut()
+			.enumerate()
+			.filter_map(|(index, slot)| slot.as_mut().map(|value| (index, value)))
+	}
+}
+
+impl<T, const N: usize> Default for Slots<T, N> {
+	fn default() -> Self { Self::new() }
+}
+
+unsafe impl<T, const N: usize> Actor for Slots<T, N> {
+	#[inline(always)]
+	fn accept<V, R>(&mut self, visitor: &mut impl ActorVisitor<V, R>) {
+		for value in self.slots.iter_mut().flatten() {
+			value.accept(visitor);
+		}
+		if visitor.removes() {
+			for slot in self.slots.iter_mut() {
+				if slot.as_ref().is_some_and(|value| visitor.is_removed(value)) {
+					*slot = None;
+				}
+			}
+		}
+		visitor.edit(self);
+	}
+}
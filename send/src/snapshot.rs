@@ -0,0 +1,46 @@
+//! Saving and loading the tree with `serde`, with [`Framework::snapshot`] and [`Framework::load`].
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{Actor, Framework, Mount};
+
+impl<R> Framework<R>
+where
+	R: Actor + 'static,
+{
+	/// Deserialize a tree that was saved with [`snapshot`](Self::snapshot), and send [`Mount`] to every [`Actor`] in
+	/// it, like they were just added.
+	pub fn load<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>
+	where
+		R: Deserialize<'de>,
+	{
+		Self::load_with_resources(deserializer, ())
+	}
+}
+
+impl<R, X> Framework<R, X>
+where
+	R: Actor + 'static,
+	X: 'static,
+{
+	/// Serialize the whole tree, like to save it.
+	///
+	/// This serializes the root [`Actor`], so derive [`Serialize`] alongside [`Actor`], and skip the fields that
+	/// shouldn't be saved with `#[serde(skip)]`. The resources aren't serialized.
+	pub fn snapshot<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		R: Serialize,
+	{
+		self.root.serialize(serializer)
+	}
+
+	/// Deserialize a tree like [`load`](Self::load), with resources that every handler can access.
+	pub fn load_with_resources<'de, D: Deserializer<'de>>(deserializer: D, resources: X) -> Result<Self, D::Error>
+	where
+		R: Deserialize<'de>,
+	{
+		let mut framework = Self::with_resources(R::deserialize(deserializer)?, resources);
+		framework.send(&mut Mount);
+		Ok(framework)
+	}
+}
@@ -0,0 +1,69 @@
+//! Pausing a [`Framework`] and resuming it later exactly where it was, with [`Framework::pause`] and
+//! [`Framework::resume`].
+
+use crate::{Actor, Framework};
+
+/// The execution state of a [`Framework`] between two messages, from [`Framework::pause`].
+///
+/// Broadcasts queued with [`Context::queue_broadcast`](crate::Context::queue_broadcast) are always delivered before
+/// the message that queued them returns, and nothing is being received between messages, so this is only:
+/// - A clone of the root [`Actor`].
+/// - The sequence number of the next message from [`send_meta`](Framework::send_meta).
+/// - The logical time from [`tick`](Framework::tick).
+/// - With the `rand` feature, the seed and the number of dispatches that [`Context::rng`](crate::Context::rng) is
+///   derived from.
+///
+/// The resources, routes, handlers and interceptors are configuration rather than state, and aren't captured.
+#[derive(Clone, Debug)]
+pub struct FrameworkState<R> {
+	root: R,
+	sequence: u64,
+	time: u64,
+	#[cfg(feature = "rand")]
+	seed: u64,
+	#[cfg(feature = "rand")]
+	dispatches: u64,
+}
+
+impl<R> FrameworkState<R> {
+	/// Get the captured root [`Actor`], like to inspect it.
+	pub fn root(&self) -> &R { &self.root }
+}
+
+impl<R, X> Framework<R, X>
+where
+	R: Actor + 'static,
+	X: 'static,
+{
+	/// Capture the execution state of the [`Framework`], to [`resume`](Self::resume) from it later, like to replay
+	/// the same sequence of messages while debugging non-determinism. See [`FrameworkState`] for what is captured.
+	pub fn pause(&self) -> FrameworkState<R>
+	where
+		R: Clone,
+	{
+		FrameworkState {
+			root: self.root.clone(),
+			sequence: self.sequence,
+			time: self.settings.time,
+			#[cfg(feature = "rand")]
+			seed: self.settings.seed,
+			#[cfg(feature = "rand")]
+			dispatches: self.settings.dispatches,
+		}
+	}
+
+	/// Go back to a state captured with [`pause`](Self::pause), replacing the root [`Actor`].
+	///
+	/// The [`Actor`]s in the state aren't sent [`Mount`](crate::Mount), and the ones being replaced aren't sent
+	/// [`Unmount`](crate::Unmount), since this continues as if the messages in between were never sent.
+	pub fn resume(&mut self, state: FrameworkState<R>) {
+		self.root = state.root;
+		self.sequence = state.sequence;
+		self.settings.time = state.time;
+		#[cfg(feature = "rand")]
+		{
+			self.settings.seed = state.seed;
+			self.settings.dispatches = state.dispatches;
+		}
+	}
+}
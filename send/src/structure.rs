@@ -0,0 +1,98 @@
+//! Adding and removing [`Actor`]s in a single pass over the tree, with [`Framework::restructure`].
+
+use std::any::TypeId;
+use std::marker::PhantomData;
+
+use crate::types::type_id;
+use crate::{Actor, ActorVisitor, Framework, Receiver};
+
+/// A pass that edits the structure of the tree, like inserting and removing elements of every collection of type
+/// `A`, or of the collections in every [`Actor`] of type `A`, with [`Framework::restructure`].
+///
+/// Unlike a [`Receiver`], this gets the [`Actor`] or collection without a [`Context`](crate::Context), so it can't
+/// send messages, but it is free to change what it contains. Closures that take `&mut A` implement this.
+pub trait StructuralVisitor<A> {
+	/// Edit `actor`, which is an [`Actor`] or a collection of them, like a `Vec<A>` or an
+	/// [`Arena`](crate::Arena). Everything it contains was already visited, and nothing is being iterated inside it.
+	fn visit(&mut self, actor: &mut A);
+}
+
+impl<A, F> StructuralVisitor<A> for F
+where
+	F: FnMut(&mut A),
+{
+	#[inline(always)]
+	fn visit(&mut self, actor: &mut A) { self(actor) }
+}
+
+impl<R, X> Framework<R, X>
+where
+	R: Actor + 'static,
+	X: 'static,
+{
+	/// Visit every [`Actor`] or collection of type `A` in the tree with a [`StructuralVisitor`] that can add and
+	/// remove sub-[`Actor`]s, like growing or pruning a scene graph. Returns the number of visited [`Actor`]s and
+	/// collections.
+	///
+	/// A collection is visited once all of its elements were, between the visits of its siblings, so its elements
+	/// can be inserted and removed without invalidating the traversal, even at the root. Every [`Actor`] is visited
+	/// after all of its sub-[`Actor`]s, even when the [`Framework`] is [`set_top_down`](Self::set_top_down), so edits
+	/// only touch parts of the tree that are already done:
+	/// - Every `A` that was in the tree when the pass started is visited once, unless it was removed before. The
+	///   collections that can grow are [`Vec`], [`VecDeque`](std::collections::VecDeque),
+	///   [`LinkedList`](std::collections::LinkedList), [`HashMap`](std::collections::HashMap),
+	///   [`BTreeMap`](std::collections::BTreeMap), [`Slots`](crate::Slots), [`Arena`](crate::Arena), and the ones from
+	///   `indexmap`.
+	/// - [`Actor`]s added during the pass are not visited until the next one. The exception is types with
+	///   `#[actor(top_down)]`, which are always visited before their sub-[`Actor`]s, so the ones added to them are
+	///   visited in the same pass, and the removed ones aren't.
+	/// - Removed [`Actor`]s are dropped without receiving [`Unmount`](crate::Unmount), and added ones don't receive
+	///   [`Mount`](crate::Mount). Send those separately, or use [`Context::spawn`](crate::Context::spawn) and
+	///   [`Context::despawn`](crate::Context::despawn) from a handler.
+	///
+	/// Only the contents of the visited [`Actor`] can be edited, so an [`Actor`] can't remove itself. Mark it
+	/// with [`Context::remove_self`](crate::Context::remove_self) instead, or remove it from its parent.
+	pub fn restructure<A: 'static>(&mut self, visitor: impl StructuralVisitor<A>) -> usize {
+		let mut visitor = RestructureVisitor {
+			inner: visitor,
+			visited: 0,
+			phantom: PhantomData,
+		};
+		self.root.accept::<(), R>(&mut visitor);
+		visitor.visited
+	}
+}
+
+/// Calls a [`StructuralVisitor`] on every [`Actor`] of type `A`.
+struct RestructureVisitor<A, V> {
+	inner: V,
+	visited: usize,
+	phantom: PhantomData<fn(&mut A)>,
+}
+
+impl<M, R, A, V> ActorVisitor<M, R> for RestructureVisitor<A, V>
+where
+	A: 'static,
+	V: StructuralVisitor<A>,
+{
+	#[inline(always)]
+	fn visit<B>(&mut self, actor: &mut B)
+	where
+		B: Actor + Receiver<M, R>,
+	{
+		<Self as ActorVisitor<M, R>>::edit(self, actor);
+	}
+
+	#[inline(always)]
+	fn edit<C>(&mut self, collection: &mut C) {
+		if type_id::<C>() == TypeId::of::<A>() {
+			// SAFETY: `C` is `A`, since everything in the tree is `'static` like the root.
+			self.inner.visit(unsafe { &mut *(collection as *mut C as *mut A) });
+			self.visited += 1;
+		}
+	}
+
+	// Always visit after the sub-`Actor`s, since they can't be edited while they are being iterated.
+	#[inline(always)]
+	fn top_down(&self) -> bool { false }
+}
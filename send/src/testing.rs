@@ -0,0 +1,37 @@
+//! Helpers for unit-testing the [`Receiver`](crate::Receiver)s of a single [`Actor`], without building a whole tree.
+//!
+//! Only available with the `testing` feature, which is meant to be enabled for tests only.
+
+use crate::{Actor, Framework};
+
+/// Send a message to `actor` as the root of its own [`Framework`], and return it for assertions.
+///
+/// Its sub-[`Actor`]s receive the message too, and the dispatch is complete, including queued broadcasts.
+pub fn expect_receive<A, M>(actor: A, message: &mut M) -> A
+where
+	A: Actor + 'static,
+{
+	let mut framework = Framework::new(actor);
+	framework.send(message);
+	framework.root
+}
+
+/// Assert that `actor` doesn't handle `message`, that is, that it uses the default [`Receiver`](crate::Receiver)
+/// that does nothing.
+///
+/// Only `actor` itself receives the message, as the root of its own [`Framework`], like in [`expect_receive`].
+/// [`Receiver`](crate::Receiver)s that are only implemented for a specific root can be checked with
+/// [`Framework::send_to_checked`] directly.
+#[track_caller]
+pub fn assert_no_handler<A, M>(actor: A, message: &mut M)
+where
+	A: Actor + 'static,
+{
+	let mut framework = Framework::new(actor);
+	assert!(
+		!framework.send_to_checked(message, |root| root),
+		"`{}` handles `{}`",
+		std::any::type_name::<A>(),
+		std::any::type_name::<M>(),
+	);
+}
@@ -0,0 +1,156 @@
+//! Delivering messages only to the [`Actor`]s that are interested in them, with [`Framework::publish`].
+//!
+//! Subscriptions are part of the state of each [`Actor`], usually in a [`Topics`] field, so they are spawned and
+//! despawned along with it, and [`Actor`]s can subscribe and unsubscribe from their own handlers.
+
+use crate::{Actor, ActorVisitor, CountingVisitor, Framework, MessageVisitor, Receiver};
+
+/// The name of a topic that messages can be published to, with [`Framework::publish`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct TopicId(&'static str);
+
+impl TopicId {
+	/// Create a [`TopicId`]. Topics with the same name are the same topic.
+	pub const fn new(name: &'static str) -> Self { Self(name) }
+
+	/// The name of the topic.
+	pub const fn name(&self) -> &'static str { self.0 }
+}
+
+/// The topics an [`Actor`] is subscribed to.
+///
+/// Use `#[actor(topics = "...")]` when deriving [`Actor`] to implement [`Subscriber`] with a field of this type.
+#[derive(Clone, Default, Debug)]
+pub struct Topics {
+	topics: Vec<TopicId>,
+}
+
+impl Topics {
+	/// Create an empty [`Topics`], subscribed to nothing.
+	pub fn new() -> Self { Self::default() }
+
+	/// Subscribe to `topic`. Returns `false` if it was already subscribed.
+	pub fn subscribe(&mut self, topic: TopicId) -> bool {
+		if self.contains(topic) {
+			return false;
+		}
+		self.topics.push(topic);
+		true
+	}
+
+	/// Unsubscribe from `topic`. Returns `false` if it wasn't subscribed.
+	pub fn unsubscribe(&mut self, topic: TopicId) -> bool {
+		let len = self.topics.len();
+		self.topics.retain(|&subscribed| subscribed != topic);
+		self.topics.len() != len
+	}
+
+	/// Check if `topic` is subscribed to.
+	pub fn contains(&self, topic: TopicId) -> bool { self.topics.contains(&topic) }
+
+	/// Unsubscribe from every topic.
+	pub fn clear(&mut self) { self.topics.clear(); }
+
+	/// Iterate over the subscribed topics, in the order they were subscribed to.
+	pub fn iter(&self) -> impl Iterator<Item = TopicId> + '_ { self.topics.iter().copied() }
+}
+
+impl FromIterator<TopicId> for Topics {
+	fn from_iter<I: IntoIterator<Item = TopicId>>(iter: I) -> Self {
+		let mut topics = Self::new();
+		for topic in iter {
+			topics.subscribe(topic);
+		}
+		topics
+	}
+}
+
+/// An [`Actor`] that can be subscribed to topics, to receive the messages published to them.
+///
+/// Every type is a [`Subscriber`] that isn't subscribed to anything, unless it implements this, usually with
+/// `#[actor(topics = "...")]`.
+pub trait Subscriber {
+	/// Check if the [`Actor`] is subscribed to `topic`.
+	fn is_subscribed(&self, topic: TopicId) -> bool;
+}
+
+impl<T> Subscriber for T {
+	#[inline(always)]
+	default fn is_subscribed(&self, _: TopicId) -> bool { false }
+}
+
+impl<R, X> Framework<R, X>
+where
+	R: Actor + 'static,
+	X: 'static,
+{
+	/// Send a message only to the [`Actor`]s that are subscribed to `topic`, see [`Subscriber`].
+	/// Their sub-[`Actor`]s only receive it if they are subscribed too.
+	///
+	/// Subscriptions are checked right before each [`Actor`] would receive the message, so an [`Actor`] that
+	/// subscribes while handling it only receives it if it wasn't visited yet.
+	/// Returns the number of [`Actor`]s that handled the message, like [`send`](Self::send).
+	pub fn publish<M>(&mut self, topic: TopicId, message: &mut M) -> usize {
+		Self::dispatch(
+			&mut self.root,
+			&mut self.resources,
+			&mut self.settings,
+			|root, dispatch| {
+				let mut visitor = TopicVisitor {
+					inner: CountingVisitor {
+						inner: MessageVisitor::new(message, root as *mut _, dispatch),
+						handled: 0,
+					},
+					topic,
+				};
+				root.accept(&mut visitor);
+				visitor.inner.handled
+			},
+		)
+	}
+}
+
+/// Only visits the [`Actor`]s that are subscribed to `topic`.
+pub(crate) struct TopicVisitor<V> {
+	pub inner: V,
+	pub topic: TopicId,
+}
+
+impl<M, R, V> ActorVisitor<M, R> for TopicVisitor<V>
+where
+	V: ActorVisitor<M, R>,
+{
+	#[inline(always)]
+	fn visit<A>(&mut self, actor: &mut A)
+	where
+		A: Actor + Receiver<M, R>,
+	{
+		if <A as Subscriber>::is_subscribed(actor, self.topic) {
+			self.inner.visit(actor);
+		}
+	}
+
+	#[inline(always)]
+	fn removes(&self) -> bool { self.inner.removes() }
+
+	#[inline(always)]
+	fn is_removed<B>(&self, actor: &B) -> bool { self.inner.is_removed(actor) }
+
+	#[inline(always)]
+	fn skips<B>(&self) -> bool { self.inner.skips::<B>() }
+
+	#[inline(always)]
+	fn edit<C>(&mut self, collection: &mut C) { self.inner.edit(collection) }
+
+	#[inline(always)]
+	fn top_down(&self) -> bool { self.inner.top_down() }
+
+	#[inline(always)]
+	fn prunes<B: ?Sized>(&self, actor: &B) -> bool { self.inner.prunes(actor) }
+
+	#[inline(always)]
+	fn handled(&self) -> Option<usize> { self.inner.handled() }
+
+	#[inline(always)]
+	fn mutates(&self) -> bool { self.inner.mutates() }
+}
@@ -0,0 +1,39 @@
+//! A record of the latest deliveries, for [`Framework::enable_trace`](crate::Framework::enable_trace).
+
+use std::collections::VecDeque;
+
+/// A message that was delivered to an [`Actor`](crate::Actor), as recorded by
+/// [`Framework::enable_trace`](crate::Framework::enable_trace).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Delivery {
+	/// The type name of the [`Actor`](crate::Actor) that received the message.
+	pub actor: &'static str,
+	/// The type name of the message.
+	pub message: &'static str,
+}
+
+/// A ring buffer of the latest [`Delivery`]s.
+pub(crate) struct Trace {
+	deliveries: VecDeque<Delivery>,
+	capacity: usize,
+}
+
+impl Trace {
+	pub fn new(capacity: usize) -> Self {
+		Self {
+			deliveries: VecDeque::with_capacity(capacity),
+			capacity,
+		}
+	}
+
+	/// Record a delivery, forgetting the oldest one if the buffer is full.
+	pub fn push(&mut self, delivery: Delivery) {
+		if self.deliveries.len() == self.capacity {
+			self.deliveries.pop_front();
+		}
+		self.deliveries.push_back(delivery);
+	}
+
+	/// The recorded deliveries, from oldest to newest.
+	pub fn iter(&self) -> impl Iterator<Item = Delivery> + '_ { self.deliveries.iter().copied() }
+}
@@ -0,0 +1,34 @@
+/// A tuple of messages that can be sent in a single traversal, with
+/// [`Framework::send_tuple`](crate::Framework::send_tuple).
+///
+/// This is implemented for tuples of up to 8 messages.
+pub trait MessageTuple {
+	/// Call `each` on every message, in order.
+	fn for_each(&mut self, each: &mut impl EachMessage);
+}
+
+/// A function that can be called on messages of any type, for [`MessageTuple`].
+pub trait EachMessage {
+	/// Call the function on a message.
+	fn call<M>(&mut self, message: &mut M);
+}
+
+macro_rules! message_tuple {
+	($($ty:ident => $index:tt),*) => {
+		impl<$($ty),*> MessageTuple for ($($ty,)*) {
+			#[inline(always)]
+			fn for_each(&mut self, each: &mut impl EachMessage) {
+				$(each.call(&mut self.$index);)*
+			}
+		}
+	};
+}
+
+message_tuple!(A => 0);
+message_tuple!(A => 0, B => 1);
+message_tuple!(A => 0, B => 1, C => 2);
+message_tuple!(A => 0, B => 1, C => 2, D => 3);
+message_tuple!(A => 0, B => 1, C => 2, D => 3, E => 4);
+message_tuple!(A => 0, B => 1, C => 2, D => 3, E => 4, F => 5);
+message_tuple!(A => 0, B => 1, C => 2, D => 3, E => 4, F => 5, G => 6);
+message_tuple!(A => 0, B => 1, C => 2, D => 3, E => 4, F => 5, G => 6, H => 7);
@@ -0,0 +1,28 @@
+//! [`TypeId`]s for types that aren't `'static`, which [`Actor`](crate::Actor)s and messages don't have to be.
+
+use std::any::TypeId;
+use std::marker::PhantomData;
+
+trait NonStaticAny {
+	fn type_id(&self) -> TypeId
+	where
+		Self: 'static;
+}
+
+impl<T: ?Sized> NonStaticAny for PhantomData<T> {
+	fn type_id(&self) -> TypeId
+	where
+		Self: 'static,
+	{
+		TypeId::of::<T>()
+	}
+}
+
+/// Get the [`TypeId`] of `T` with its lifetimes erased, so `Foo<'a>` and `Foo<'static>` are the same type.
+pub(crate) fn type_id<T: ?Sized>() -> TypeId {
+	let phantom = PhantomData::<T>;
+	let any: &dyn NonStaticAny = &phantom;
+	// SAFETY: Lifetimes don't exist at runtime, and the `TypeId` doesn't borrow from `T`.
+	let any = unsafe { std::mem::transmute::<&dyn NonStaticAny, &(dyn NonStaticAny + 'static)>(any) };
+	any.type_id()
+}
@@ -0,0 +1,69 @@
+//! Running hooks around the receivers of an [`Actor`], for [`wrap_receiver!`](crate::wrap_receiver).
+
+use crate::actor::receive_handled;
+use crate::{Actor, ActorVisitor, Context, ExceptVisitor, Query, Receiver, ReceiverRef, Responder};
+
+/// An [`Actor`] that receives messages in place of the one it wraps, so that [`wrap_receiver!`](crate::wrap_receiver)
+/// can run hooks around its receivers, like for logging or validation.
+///
+/// Messages without hooks are forwarded to the inner [`Actor`] unchanged, and so are [`Query`]s and views. The inner
+/// [`Actor`] isn't visited itself, but its sub-[`Actor`]s are, and paths go through to its fields.
+#[repr(transparent)]
+pub struct Wrap<A>(pub A);
+
+impl<A> Wrap<A> {
+	/// Deliver a message to the inner [`Actor`], skipping the hooks.
+	///
+	/// The [`Wrap`] counts as handling the message either way, since its hooks did.
+	pub fn receive_inner<M, R>(&mut self, message: &mut M, context: Context<Self, R>) {
+		receive_handled(&mut self.0, message, context.cast());
+	}
+}
+
+unsafe impl<A> Actor for Wrap<A> {
+	#[inline(always)]
+	fn accept<V, R>(&mut self, visitor: &mut impl ActorVisitor<V, R>) {
+		// It stands in for the inner `Actor`, so it is pruned along with it.
+		if visitor.prunes(self) || visitor.prunes(&self.0) {
+			return;
+		}
+		let except = &self.0 as *const A;
+		// Like a derived `Actor`, it is visited after its sub-`Actor`s unless the visitor asks for the opposite.
+		if visitor.top_down() {
+			visitor.visit(self);
+			self.0.accept(&mut ExceptVisitor::new(&mut *visitor, except));
+		} else {
+			self.0.accept(&mut ExceptVisitor::new(&mut *visitor, except));
+			visitor.visit(self);
+		}
+	}
+
+	#[inline(always)]
+	fn accept_path<V, R>(&mut self, path: &[&str], visitor: &mut impl ActorVisitor<V, R>) -> bool {
+		if path.is_empty() {
+			visitor.visit(self);
+			true
+		} else {
+			self.0.accept_path(path, visitor)
+		}
+	}
+}
+
+impl<M, R, A> Receiver<M, R> for Wrap<A> {
+	#[inline(always)]
+	default fn receive(&mut self, message: &mut M, context: Context<Self, R>) {
+		self.0.receive(message, context.cast())
+	}
+}
+
+impl<M: Query, R, A> Responder<M, R> for Wrap<A> {
+	#[inline(always)]
+	default fn respond(&mut self, query: &mut M, context: Context<Self, R>) -> Option<M::Response> {
+		self.0.respond(query, context.cast())
+	}
+}
+
+impl<M, A> ReceiverRef<M> for Wrap<A> {
+	#[inline(always)]
+	default fn receive_ref(&self, view: &M) { self.0.receive_ref(view) }
+}
@@ -1,26 +1,80 @@
 #![feature(min_specialization)]
 #![feature(negative_impls)]
 
-use send::{receive, Actor, Framework};
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell, UnsafeCell};
+use std::collections::{BTreeMap, HashMap, LinkedList};
+use std::future::Future;
+use std::marker::PhantomData;
+use std::ops::{ControlFlow, Deref, DerefMut};
+use std::pin::{pin, Pin};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context as TaskContext, Poll, Waker};
+use std::time::Duration;
 
-#[derive(Actor)]
+use send::prelude::*;
+use send::{dispatch_enum, is_actor, receive_enum, register_trait_actors, send_to_each, trait_tags, wrap_receiver};
+use send::{
+	ActorMeta,
+	ActorVisitor,
+	Arena,
+	AsyncReceiver,
+	CancelToken,
+	ChannelActor,
+	Covers,
+	CowArc,
+	Delivery,
+	EventAdapter,
+	Gate,
+	Index,
+	MessageError,
+	Meta,
+	Mount,
+	Priority,
+	Query,
+	ReceiverRef,
+	Reset,
+	Responder,
+	Rev,
+	Slots,
+	TopicId,
+	Topics,
+	TraitFilter,
+	TraitTagged,
+	Transform,
+	Unmount,
+	Value,
+	Wrap,
+};
+
+#[derive(Actor, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[actor(resettable)]
 struct Root {
 	data: Data,
 	counter: u16,
 	child: Child,
 }
 
-#[derive(Actor)]
+#[derive(Actor, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[actor(resettable)]
 struct Child {
 	counter: u16,
 	child: ChildChild,
 }
 
-#[derive(Actor)]
+#[derive(Actor, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[actor(resettable)]
 struct ChildChild {
 	counter: u16,
 }
 
+#[derive(Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct Data {
 	data: u16,
 }
@@ -53,6 +107,17 @@ receive! {
 	}
 }
 
+fn framework() -> Framework<Root> {
+	Framework::new(Root {
+		data: Data { data: 1 },
+		counter: 2,
+		child: Child {
+			counter: 2,
+			child: ChildChild { counter: 2 },
+		},
+	})
+}
+
 #[test]
 fn test() {
 	let mut framework = Framework::new(Root {
@@ -84,3 +149,3618 @@ fn test() {
 	assert_eq!(framework.get().child.counter, 1);
 	assert_eq!(framework.get().child.child.counter, 6);
 }
+
+#[test]
+fn references() {
+	let mut framework = Framework::new((
+		Box::leak(Box::new(ChildChild { counter: 0 })) as &mut ChildChild,
+		&*Box::leak(Box::new(ChildChild { counter: 0 })),
+	));
+
+	// The mutable reference forwards to its referent, and the shared one is skipped.
+	framework.send(&mut Increment(1));
+	assert_eq!(framework.get().0.counter, 1);
+	assert_eq!(framework.get().1.counter, 0);
+}
+
+#[derive(Actor)]
+struct Ring<'a, T: Copy + Into<u16>, const N: usize>
+where
+	T: 'a,
+{
+	slots: [&'a mut ChildChild; N],
+	step: T,
+}
+
+receive! {
+	%('a, T: Copy + Into<u16>, const N: usize) Increment => Ring<'a, T, N> = (&mut self, message, _) {
+		for slot in self.slots.iter_mut() {
+			slot.counter += message.0 * self.step.into();
+		}
+	}
+}
+
+#[test]
+fn generics() {
+	let mut framework = Framework::new(Ring {
+		slots: [
+			Box::leak(Box::new(ChildChild { counter: 0 })),
+			Box::leak(Box::new(ChildChild { counter: 0 })),
+		],
+		step: 2u8,
+	});
+
+	// Each slot receives the message itself, and is then incremented by the ring.
+	framework.send(&mut Increment(1));
+	assert_eq!(framework.get().slots[0].counter, 3);
+	assert_eq!(framework.get().slots[1].counter, 3);
+}
+
+struct Log(Vec<&'static str>);
+
+receive! {
+	Log => Root = (&mut self, log, _) { log.0.push("Root") }
+	Log => Child = (&mut self, log, _) { log.0.push("Child") }
+	Log => ChildChild = (&mut self, log, _) { log.0.push("ChildChild") }
+}
+
+impl Priority<Log> for Root {
+	fn priority() -> i32 { 1 }
+}
+
+impl Priority<Log> for Child {
+	fn priority() -> i32 { -1 }
+}
+
+#[test]
+fn prioritized() {
+	let mut framework = framework();
+
+	let mut log = Log(Vec::new());
+	framework.send(&mut log);
+	assert_eq!(log.0, ["ChildChild", "Child", "Root"]);
+
+	let mut log = Log(Vec::new());
+	framework.send_prioritized(&mut log);
+	assert_eq!(log.0, ["Root", "ChildChild", "Child"]);
+}
+
+#[derive(Actor, Debug, PartialEq)]
+enum Light {
+	Red,
+	Green,
+	Yellow,
+}
+
+struct Advance;
+
+struct ToRed;
+
+struct ToGreen;
+
+struct ToYellow;
+
+receive! {
+	Advance => Light = (&mut self, _, context) {
+		match self {
+			Light::Red => context.send_to_self(self, &mut ToGreen),
+			Light::Green => context.send_to_self(self, &mut ToYellow),
+			Light::Yellow => context.send_to_self(self, &mut ToRed),
+		}
+	}
+
+	ToRed => Light = (&mut self, _, _) { *self = Light::Red }
+	ToGreen => Light = (&mut self, _, _) { *self = Light::Green }
+	ToYellow => Light = (&mut self, _, _) { *self = Light::Yellow }
+}
+
+#[test]
+fn send_to_self() {
+	let mut framework = Framework::new(Light::Red);
+
+	framework.send(&mut Advance);
+	assert_eq!(framework.get(), &Light::Green);
+	framework.send(&mut Advance);
+	assert_eq!(framework.get(), &Light::Yellow);
+	framework.send(&mut Advance);
+	assert_eq!(framework.get(), &Light::Red);
+}
+
+#[derive(Actor)]
+struct Leaf {
+	counter: u16,
+}
+
+receive! {
+	Increment => Leaf = (&mut self, message, _) {
+		self.counter += message.0;
+	}
+}
+
+#[derive(Actor)]
+struct Skipping {
+	#[actor(skip)]
+	skipped: Leaf,
+	visited: Leaf,
+	slots: Vec<Slot>,
+}
+
+#[derive(Actor)]
+enum Slot {
+	Named {
+		#[actor(skip)]
+		skipped: Leaf,
+		visited: Leaf,
+	},
+	Tuple(Leaf, #[actor(skip)] Leaf, Leaf),
+}
+
+#[test]
+fn skip() {
+	let leaf = || Leaf { counter: 0 };
+	let mut framework = Framework::new(Skipping {
+		skipped: leaf(),
+		visited: leaf(),
+		slots: vec![
+			Slot::Named {
+				skipped: leaf(),
+				visited: leaf(),
+			},
+			Slot::Tuple(leaf(), leaf(), leaf()),
+		],
+	});
+
+	framework.send(&mut Increment(1));
+	let root = framework.get();
+	assert_eq!(root.skipped.counter, 0);
+	assert_eq!(root.visited.counter, 1);
+	match &root.slots[0] {
+		Slot::Named { skipped, visited } => {
+			assert_eq!(skipped.counter, 0);
+			assert_eq!(visited.counter, 1);
+		},
+		_ => unreachable!(),
+	}
+	match &root.slots[1] {
+		Slot::Tuple(first, skipped, last) => {
+			assert_eq!(first.counter, 1);
+			assert_eq!(skipped.counter, 0);
+			assert_eq!(last.counter, 1);
+		},
+		_ => unreachable!(),
+	}
+}
+
+#[derive(Actor)]
+struct World {
+	entities: Arena<Entity>,
+}
+
+#[derive(Actor)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Entity {
+	mounted: bool,
+	counter: u16,
+}
+
+struct Spawn(Option<Index>);
+
+struct Despawn(Index, Option<Entity>);
+
+receive! {
+	Spawn => World = (&mut self, message, context) {
+		let entity = Entity { mounted: false, counter: 0 };
+		message.0 = Some(context.spawn(self, |world| &mut world.entities, entity));
+	}
+
+	Despawn => World = (&mut self, message, context) {
+		message.1 = context.despawn(self, |world| &mut world.entities, message.0);
+	}
+}
+
+receive! {
+	Mount => Entity = (&mut self, _, _) { self.mounted = true }
+	Unmount => Entity = (&mut self, _, _) { self.mounted = false }
+	Increment => Entity = (&mut self, message, _) { self.counter += message.0 }
+}
+
+#[test]
+fn arena() {
+	let mut framework = Framework::new(World { entities: Arena::new() });
+
+	let mut spawns: Vec<_> = (0..3).map(|_| Spawn(None)).collect();
+	for spawn in spawns.iter_mut() {
+		framework.send(spawn);
+	}
+	let indices: Vec<_> = spawns.into_iter().map(|spawn| spawn.0.unwrap()).collect();
+	assert!(framework.get().entities.iter().all(|(_, entity)| entity.mounted));
+
+	let mut despawn = Despawn(indices[1], None);
+	framework.send(&mut despawn);
+	assert!(!despawn.1.unwrap().mounted);
+	assert_eq!(framework.get().entities.len(), 2);
+
+	framework.send(&mut Increment(1));
+	let entities = &framework.get().entities;
+	assert_eq!(entities.get(indices[0]).unwrap().counter, 1);
+	assert!(entities.get(indices[1]).is_none());
+	assert_eq!(entities.get(indices[2]).unwrap().counter, 1);
+
+	let mut spawn = Spawn(None);
+	framework.send(&mut spawn);
+	assert_ne!(spawn.0, Some(indices[1]));
+	assert!(framework.get().entities.get(indices[1]).is_none());
+	assert_eq!(framework.get().entities.get(spawn.0.unwrap()).unwrap().counter, 0);
+}
+
+#[derive(Actor)]
+struct Garrison {
+	posts: Slots<Entity, 3>,
+}
+
+struct Station(Option<Result<usize, Entity>>);
+
+struct Relieve(usize, Option<Entity>);
+
+receive! {
+	Station => Garrison = (&mut self, message, context) {
+		let entity = Entity { mounted: false, counter: 0 };
+		message.0 = Some(context.spawn_slot(self, |garrison| &mut garrison.posts, entity));
+	}
+
+	Relieve => Garrison = (&mut self, message, context) {
+		message.1 = context.despawn_slot(self, |garrison| &mut garrison.posts, message.0);
+	}
+}
+
+#[test]
+fn slots() {
+	let mut framework = Framework::new(Garrison { posts: Slots::new() });
+
+	for expected in 0..2 {
+		let mut station = Station(None);
+		framework.send(&mut station);
+		assert_eq!(station.0.unwrap().ok(), Some(expected));
+	}
+	assert!(framework.get().posts.iter().all(|(_, entity)| entity.mounted));
+
+	framework.send(&mut Increment(1));
+	let posts = &framework.get().posts;
+	assert_eq!(posts.len(), 2);
+	assert_eq!(posts.get(0).unwrap().counter, 1);
+	assert_eq!(posts.get(1).unwrap().counter, 1);
+	assert!(posts.get(2).is_none());
+
+	let mut relieve = Relieve(0, None);
+	framework.send(&mut relieve);
+	assert!(!relieve.1.unwrap().mounted);
+	let mut relieve = Relieve(0, None);
+	framework.send(&mut relieve);
+	assert!(relieve.1.is_none());
+
+	framework.send(&mut Increment(1));
+	assert_eq!(framework.get().posts.get(1).unwrap().counter, 2);
+
+	for expected in [0, 2] {
+		let mut station = Station(None);
+		framework.send(&mut station);
+		assert_eq!(station.0.unwrap().ok(), Some(expected));
+	}
+	let mut station = Station(None);
+	framework.send(&mut station);
+	assert!(!station.0.unwrap().unwrap_err().mounted);
+	assert_eq!(framework.get().posts.len(), 3);
+}
+
+#[derive(Actor)]
+enum Signal {
+	Red(Entity),
+	Green(Entity),
+}
+
+impl Default for Signal {
+	fn default() -> Self {
+		Self::Red(Entity {
+			mounted: false,
+			counter: 0,
+		})
+	}
+}
+
+struct Next(Vec<bool>);
+
+impl<R: Actor> Transform<Next, R> for Signal {
+	fn transform(mut self, message: &mut Next, context: Context<Self, R>) -> Self {
+		// The rest of the tree can still be reached, but this isn't in it anymore.
+		context.broadcast(&mut self, &mut Increment(1));
+		match self {
+			Signal::Red(entity) => {
+				message.0.push(entity.mounted);
+				Signal::Green(entity)
+			},
+			Signal::Green(entity) => {
+				message.0.push(entity.mounted);
+				Signal::Red(entity)
+			},
+		}
+	}
+}
+
+#[test]
+fn transform_to() {
+	let mut framework = Framework::new((Signal::default(), Leaf { counter: 0 }));
+	framework.send(&mut Mount);
+
+	let mut next = Next(Vec::new());
+	framework.transform_to(&mut next, |root| &mut root.0);
+	let Signal::Green(entity) = &framework.get().0 else {
+		panic!("not transformed")
+	};
+	assert!(entity.mounted);
+	assert_eq!(entity.counter, 0);
+	assert_eq!(framework.get().1.counter, 1);
+
+	framework.transform_to(&mut next, |root| &mut root.0);
+	assert!(matches!(framework.get().0, Signal::Red(_)));
+	// The old `Actor` was unmounted before being transformed.
+	assert_eq!(next.0, [false, false]);
+}
+
+#[derive(Actor)]
+struct HandWritten {
+	received: u16,
+}
+
+impl<R> Receiver<Increment, R> for HandWritten {
+	fn receive(&mut self, message: &mut Increment, _: Context<Self, R>) { self.received += message.0; }
+}
+
+#[test]
+fn send_hand_written() {
+	let mut framework = Framework::new(HandWritten { received: 0 });
+	assert_eq!(framework.send(&mut Increment(2)), 1);
+	assert_eq!(framework.get().received, 2);
+
+	// It counts as handling the messages it receives, unlike the default `Receiver`.
+	assert!(framework.send_to_checked(&mut Increment(1), |root| root));
+	assert!(!framework.send_to_checked(&mut Decrement(1), |root| root));
+	assert_eq!(framework.get().received, 3);
+}
+
+#[test]
+fn actor_count() {
+	// `Root`, `Child` and `ChildChild`, but not `Data`.
+	assert_eq!(framework().actor_count(), 3);
+
+	let mut framework = Framework::new((Gate::new(Leaf { counter: 0 }, false), Vec::<Leaf>::new()));
+	assert_eq!(framework.actor_count(), 0);
+	framework.get_mut().0.set_open(true);
+	framework.get_mut().1.push(Leaf { counter: 0 });
+	assert_eq!(framework.actor_count(), 2);
+}
+
+#[test]
+fn send_to_checked() {
+	let mut framework = framework();
+
+	assert!(framework.send_to_checked(&mut Increment(1), |root| &mut root.child));
+	assert_eq!(framework.get().child.counter, 3);
+
+	assert!(!framework.send_to_checked(&mut Decrement(1), |root| &mut root.child.child));
+	assert!(!framework.send_to_checked(&mut Increment(1), |root| &mut root.data));
+	assert_eq!(framework.get().child.child.counter, 2);
+}
+
+#[derive(Actor)]
+struct Downloader {
+	workers: Vec<Worker>,
+	fetched: u16,
+}
+
+#[derive(Actor)]
+struct Worker {
+	id: u16,
+	fetched: bool,
+}
+
+struct Fetch(Vec<u16>);
+
+/// Returns `Pending` once, like an I/O operation that isn't ready yet.
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+	type Output = ();
+
+	fn poll(mut self: Pin<&mut Self>, cx: &mut TaskContext) -> Poll<()> {
+		if self.0 {
+			Poll::Ready(())
+		} else {
+			self.0 = true;
+			cx.waker().wake_by_ref();
+			Poll::Pending
+		}
+	}
+}
+
+fn block_on<F: Future>(future: F) -> F::Output {
+	let mut future = pin!(future);
+	let mut cx = TaskContext::from_waker(Waker::noop());
+	loop {
+		if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+			return output;
+		}
+	}
+}
+
+receive! {
+	Fetch => Worker = async (&mut self, message, _) {
+		YieldNow(false).await;
+		self.fetched = true;
+		message.0.push(self.id);
+	}
+
+	Fetch => Downloader = async (&mut self, message, _) {
+		YieldNow(false).await;
+		self.fetched = message.0.len() as u16;
+	}
+}
+
+#[test]
+fn send_async() {
+	let mut framework = Framework::new(Downloader {
+		workers: (0..3).map(|id| Worker { id, fetched: false }).collect(),
+		fetched: 0,
+	});
+
+	let mut fetch = Fetch(Vec::new());
+	framework.send(&mut fetch);
+	assert!(fetch.0.is_empty());
+
+	block_on(framework.send_async(&mut fetch));
+	assert_eq!(fetch.0, [0, 1, 2]);
+	assert!(framework.get().workers.iter().all(|worker| worker.fetched));
+	assert_eq!(framework.get().fetched, 3);
+}
+
+#[derive(Actor)]
+struct Mirror {
+	id: u16,
+	calls: u16,
+}
+
+impl<R> AsyncReceiver<Fetch, R> for Mirror {
+	fn receive<'a>(&'a mut self, message: &'a mut Fetch, _: Context<Self, R>) -> Pin<Box<dyn Future<Output = ()> + 'a>>
+	where
+		R: 'a,
+	{
+		self.calls += 1;
+		Box::pin(async move {
+			YieldNow(false).await;
+			message.0.push(self.id);
+		})
+	}
+}
+
+#[test]
+fn send_async_by_hand() {
+	let mut framework = Framework::new((
+		Leaf { counter: 0 },
+		Mirror { id: 7, calls: 0 },
+		Worker { id: 0, fetched: false },
+	));
+
+	let mut fetch = Fetch(Vec::new());
+	block_on(framework.send_async(&mut fetch));
+	assert_eq!(fetch.0, [7, 0]);
+	// Found out to have a handler without calling it again before its turn.
+	assert_eq!(framework.get().1.calls, 1);
+}
+
+struct Report<'a>(&'a Data, u16);
+
+receive! {
+	%('a) Report<'a> => Root = (&mut self, report, _) {
+		report.1 += report.0.data;
+		self.counter += report.0.data;
+	}
+
+	%('a) Report<'a> => ChildChild = (&mut self, report, _) {
+		report.1 += report.0.data;
+		self.counter += report.0.data;
+	}
+}
+
+#[test]
+fn send_with_fields() {
+	let mut framework = framework();
+
+	framework.send_with(|root| &root.data, |data| Report(data, 0));
+	assert_eq!(framework.get().counter, 3);
+	assert_eq!(framework.get().child.child.counter, 3);
+}
+
+#[derive(Actor)]
+struct Announcer {
+	heard: u16,
+	listeners: Vec<Listener>,
+}
+
+#[derive(Actor)]
+struct Listener;
+
+struct Announce;
+
+struct Heard<'a>(&'a mut u16);
+
+receive! {
+	Announce => Announcer = (&mut self, _, context) {
+		context.broadcast_with(self, |announcer| &mut announcer.heard, Heard);
+	}
+}
+
+receive! {
+	%('a) Heard<'a> => Listener = (&mut self, heard, _) {
+		*heard.0 += 1;
+	}
+
+	// Never called, since the fields in the message are borrowed from the sender.
+	%('a) Heard<'a> => Announcer = (&mut self, _, _) {
+		self.heard += 100;
+	}
+}
+
+#[test]
+fn broadcast_with_fields() {
+	let mut framework = Framework::new(Announcer {
+		heard: 0,
+		listeners: vec![Listener, Listener, Listener],
+	});
+
+	framework.send(&mut Announce);
+	assert_eq!(framework.get().heard, 3);
+}
+
+#[derive(Actor)]
+struct Stage {
+	announcer: Announcer,
+	heard: u16,
+}
+
+receive! {
+	// Never called either, since `Stage` contains the sender and its borrowed fields.
+	%('a) Heard<'a> => Stage = (&mut self, _, _) {
+		self.heard += 1;
+	}
+}
+
+#[test]
+fn broadcast_with_parent() {
+	let mut framework = Framework::new(Stage {
+		announcer: Announcer {
+			heard: 0,
+			listeners: vec![Listener, Listener],
+		},
+		heard: 0,
+	});
+
+	framework.send(&mut Announce);
+	assert_eq!(framework.get().announcer.heard, 2);
+	assert_eq!(framework.get().heard, 0);
+}
+
+#[cfg(feature = "checked")]
+#[derive(Actor)]
+struct Node {
+	#[actor(recursive)]
+	children: Vec<Node>,
+}
+
+#[cfg(feature = "checked")]
+struct Misuse;
+
+#[cfg(feature = "checked")]
+receive! {
+	Misuse => Node = (&mut self, _, context) {
+		// `self` stays usable after this, even though the broadcast can mutate it.
+		if let Some(child) = self.children.first_mut() {
+			context.broadcast(child, &mut Increment(1));
+		}
+	}
+}
+
+#[cfg(feature = "checked")]
+#[test]
+#[should_panic(expected = "`from` must be the `Actor` that received the `Context`")]
+fn checked_from() {
+	let mut framework = Framework::new(Node {
+		children: vec![Node { children: Vec::new() }],
+	});
+
+	framework.send(&mut Misuse);
+}
+
+struct Relax(bool);
+
+receive! {
+	Relax => Leaf = (&mut self, relax, _) {
+		if self.counter < 3 {
+			self.counter += 1;
+			relax.0 = true;
+		}
+	}
+}
+
+#[test]
+fn send_while() {
+	let leaves = || (0..3).map(|counter| Leaf { counter }).collect::<Vec<_>>();
+
+	let mut framework = Framework::new(leaves());
+	assert_eq!(framework.send(&mut Relax(false)), 3);
+	assert_eq!(framework.send(&mut Increment(0)), 3);
+	assert_eq!(framework.send(&mut Decrement(0)), 0);
+
+	let mut framework = Framework::new(leaves());
+	assert_eq!(framework.send_while(|| Relax(false), |relax| relax.0, 10), 4);
+	assert!(framework.get().iter().all(|leaf| leaf.counter == 3));
+
+	let mut framework = Framework::new(leaves());
+	assert_eq!(framework.send_while(|| Relax(false), |relax| relax.0, 2), 2);
+	assert_eq!(framework.get()[0].counter, 2);
+}
+
+#[derive(Actor)]
+struct Player {
+	x: i32,
+	y: i32,
+	jumps: u16,
+}
+
+enum Command {
+	Move { dx: i32, dy: i32 },
+	Jump,
+	Wait,
+}
+
+receive! {
+	Command => Player = match (&mut self, _) {
+		Command::Move { dx, dy } if *dx != 0 || *dy != 0 => {
+			self.x += *dx;
+			self.y += *dy;
+		}
+		Command::Jump => {
+			self.jumps += 1;
+		}
+	}
+}
+
+#[test]
+fn receive_patterns() {
+	let mut framework = Framework::new(Player { x: 0, y: 0, jumps: 0 });
+
+	framework.send(&mut Command::Move { dx: 1, dy: -1 });
+	framework.send(&mut Command::Move { dx: 0, dy: 0 });
+	framework.send(&mut Command::Jump);
+	framework.send(&mut Command::Wait);
+
+	let player = framework.get();
+	assert_eq!((player.x, player.y, player.jumps), (1, -1, 1));
+}
+
+struct Ping(Vec<u16>);
+
+receive! {
+	Ping => Leaf = (&mut self, ping, context) {
+		ping.0.push(self.counter);
+		context.queue_broadcast(Increment(1));
+	}
+}
+
+#[test]
+fn queue_broadcast() {
+	let mut framework = Framework::new((0..3).map(|_| Leaf { counter: 0 }).collect::<Vec<_>>());
+
+	let mut ping = Ping(Vec::new());
+	framework.send(&mut ping);
+	// Nothing is delivered until the dispatch is done, and then every leaf gets every queued message.
+	assert_eq!(ping.0, [0, 0, 0]);
+	assert!(framework.get().iter().all(|leaf| leaf.counter == 3));
+}
+
+#[test]
+fn unsafe_cell() {
+	let mut framework = Framework::new(UnsafeCell::new(Child {
+		counter: 1,
+		child: ChildChild { counter: 0 },
+	}));
+
+	framework.send(&mut Increment(2));
+	let child = framework.get_mut().get_mut();
+	assert_eq!((child.counter, child.child.counter), (2, 2));
+
+	assert!(framework.send_to_path("child", &mut Increment(1)));
+	assert_eq!(framework.get_mut().get_mut().child.counter, 3);
+}
+
+#[test]
+fn control_flow_and_poll() {
+	let mut framework = Framework::new((
+		ControlFlow::<Leaf, Leaf>::Continue(Leaf { counter: 0 }),
+		ControlFlow::<Leaf, Leaf>::Break(Leaf { counter: 0 }),
+		Poll::Ready(Leaf { counter: 0 }),
+		Poll::<Leaf>::Pending,
+	));
+
+	assert_eq!(framework.send(&mut Increment(1)), 3);
+	assert!(framework.send_to_path("2", &mut Increment(1)));
+	assert!(!framework.send_to_path("3", &mut Increment(1)));
+
+	let (ControlFlow::Continue(continued), ControlFlow::Break(broken), Poll::Ready(ready), Poll::Pending) =
+		framework.get()
+	else {
+		unreachable!();
+	};
+	assert_eq!((continued.counter, broken.counter, ready.counter), (1, 1, 2));
+}
+
+#[test]
+fn with_root_owned() {
+	let mut framework = Framework::new(vec![Leaf { counter: 1 }, Leaf { counter: 2 }]);
+
+	let total = framework.with_root_owned(|mut leaves| {
+		let total: u16 = leaves.iter().map(|leaf| leaf.counter).sum();
+		leaves.push(Leaf { counter: total });
+		(leaves, total)
+	});
+	assert_eq!(total, 3);
+
+	assert_eq!(framework.send(&mut Increment(1)), 3);
+	assert_eq!(framework.get()[2].counter, 4);
+}
+
+wrap_receiver! {
+	before: |increment| { increment.0 *= 10 },
+	after: |increment| { increment.0 /= 10 },
+	Increment => Child
+}
+
+#[test]
+fn wrap() {
+	let mut framework = Framework::new(Wrap(Child {
+		counter: 1,
+		child: ChildChild { counter: 0 },
+	}));
+
+	// `ChildChild` and the `Wrap`, but not the `Child` inside it again.
+	let mut increment = Increment(1);
+	assert_eq!(framework.send(&mut increment), 2);
+	assert_eq!(increment.0, 1);
+	// `Decrement` has no hooks, so it is forwarded as is.
+	assert_eq!(framework.get().0.counter, 10);
+	assert_eq!(framework.get().0.child.counter, 1);
+
+	assert!(framework.send_to_path("child", &mut Increment(1)));
+	assert_eq!(framework.get().0.child.counter, 2);
+	assert_eq!(framework.get().0.counter, 9);
+}
+
+#[test]
+fn send_to_nth() {
+	let mut framework = Framework::new((
+		vec![Leaf { counter: 0 }, Leaf { counter: 0 }],
+		Child {
+			counter: 1,
+			child: ChildChild { counter: 0 },
+		},
+		Leaf { counter: 0 },
+	));
+
+	assert!(framework.send_to_nth::<_, Leaf>(1, &mut Increment(1)));
+	assert!(framework.send_to_nth::<_, Leaf>(2, &mut Increment(2)));
+	assert!(!framework.send_to_nth::<_, Leaf>(3, &mut Increment(3)));
+	assert!(framework.send_to_nth::<_, Child>(0, &mut Increment(1)));
+	assert_eq!(framework.get().0[0].counter, 0);
+	assert_eq!(framework.get().0[1].counter, 1);
+	assert_eq!(framework.get().1.counter, 2);
+	assert_eq!(framework.get().1.child.counter, 0);
+	assert_eq!(framework.get().2.counter, 2);
+}
+
+#[cfg(feature = "testing")]
+#[test]
+fn testing() {
+	use send::testing::{assert_no_handler, expect_receive};
+
+	assert_eq!(expect_receive(Leaf { counter: 1 }, &mut Increment(2)).counter, 3);
+
+	// `ChildChild` decrements its parent when incremented.
+	let child = Child {
+		counter: 1,
+		child: ChildChild { counter: 0 },
+	};
+	let child = expect_receive(child, &mut Increment(2));
+	assert_eq!((child.counter, child.child.counter), (2, 2));
+
+	assert_no_handler(Leaf { counter: 0 }, &mut Decrement(1));
+	assert_no_handler(Data { data: 0 }, &mut Increment(1));
+}
+
+#[cfg(feature = "testing")]
+#[test]
+#[should_panic(expected = "handles")]
+fn testing_handler() { send::testing::assert_no_handler(Leaf { counter: 0 }, &mut Increment(1)); }
+
+#[derive(Actor, Clone)]
+struct Setting {
+	value: u16,
+}
+
+receive! {
+	Increment => Setting = (&mut self, message, _) {
+		self.value += message.0;
+	}
+}
+
+static DEFAULT_SETTING: Setting = Setting { value: 5 };
+
+#[test]
+fn copy_on_write() {
+	let shared = CowArc::new(Setting { value: 1 });
+	let leaf = Arc::new(Leaf { counter: 0 });
+	let mut framework = Framework::new((
+		shared.clone(),
+		shared.clone(),
+		Cow::Borrowed(&DEFAULT_SETTING),
+		leaf.clone(),
+	));
+
+	// Nothing handles `Decrement`, so the copies are dropped, and the originals are shared again.
+	assert_eq!(framework.send(&mut Decrement(1)), 0);
+	let (first, second, default, _) = framework.get();
+	assert!(Arc::ptr_eq(first.as_arc(), second.as_arc()));
+	assert!(matches!(default, Cow::Borrowed(_)));
+
+	// The shared `Arc` isn't visited at all.
+	assert_eq!(framework.send(&mut Increment(1)), 3);
+	let (first, second, default, leaf_ref) = framework.get();
+	assert!(!Arc::ptr_eq(first.as_arc(), second.as_arc()));
+	assert_eq!((first.value, second.value, shared.value), (2, 2, 1));
+	assert!(matches!(default, Cow::Owned(Setting { value: 6 })));
+	assert_eq!(leaf_ref.counter, 0);
+
+	drop(leaf);
+	assert_eq!(framework.send(&mut Increment(1)), 4);
+	assert_eq!(framework.get().3.counter, 1);
+}
+
+#[test]
+fn copy_on_write_for_each_mut() {
+	let shared = CowArc::new(Setting { value: 1 });
+	let mut framework = Framework::new((shared.clone(), Cow::Borrowed(&DEFAULT_SETTING)));
+
+	// The copies are kept, since they might have been changed.
+	framework.for_each_mut(|setting: &mut Setting| setting.value += 1);
+	let (copy, default) = framework.get();
+	assert!(!Arc::ptr_eq(copy.as_arc(), shared.as_arc()));
+	assert_eq!((copy.value, shared.value), (2, 1));
+	assert!(matches!(default, Cow::Owned(Setting { value: 6 })));
+	assert_eq!(DEFAULT_SETTING.value, 5);
+}
+
+static COUNTED_CLONES: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Actor)]
+#[actor(handles(Increment))]
+struct Counted {
+	received: u16,
+}
+
+impl Clone for Counted {
+	fn clone(&self) -> Self {
+		COUNTED_CLONES.fetch_add(1, Ordering::Relaxed);
+		Self {
+			received: self.received,
+		}
+	}
+}
+
+impl<R> Receiver<Increment, R> for Counted {
+	fn receive(&mut self, message: &mut Increment, _: Context<Self, R>) { self.received += message.0; }
+}
+
+#[test]
+fn copy_on_write_sparse() {
+	let shared = CowArc::new(Counted { received: 0 });
+	let mut framework = Framework::new((shared.clone(), shared.clone()));
+
+	// Nothing in `Counted` can handle `Decrement`, so it isn't even copied.
+	assert_eq!(framework.send(&mut Decrement(1)), 0);
+	assert_eq!(COUNTED_CLONES.load(Ordering::Relaxed), 0);
+
+	assert_eq!(framework.send(&mut Increment(1)), 2);
+	assert_eq!(COUNTED_CLONES.load(Ordering::Relaxed), 2);
+	assert_eq!((framework.get().0.received, shared.received), (1, 0));
+}
+
+#[test]
+fn warn_on_unhandled() {
+	let mut framework = framework();
+	framework.warn_on_unhandled(true);
+
+	// Prints a warning the first time only.
+	assert_eq!(framework.send(&mut Ping(Vec::new())), 0);
+	assert_eq!(framework.send(&mut Ping(Vec::new())), 0);
+	assert_eq!(framework.send(&mut Increment(1)), 3);
+}
+
+#[test]
+fn reset() {
+	let mut framework = framework();
+	framework.send(&mut Increment(1));
+
+	assert_eq!(framework.send(&mut Reset), 3);
+	let root = framework.get();
+	assert_eq!((root.data.data, root.counter), (0, 0));
+	assert_eq!((root.child.counter, root.child.child.counter), (0, 0));
+}
+
+#[derive(Actor, Default)]
+#[actor(resettable)]
+struct Knob {
+	value: u16,
+}
+
+#[derive(Actor)]
+#[actor(resettable)]
+struct Console {
+	knob: Knob,
+}
+
+impl Default for Console {
+	fn default() -> Self {
+		Self {
+			knob: Knob { value: 9 },
+		}
+	}
+}
+
+#[test]
+fn reset_order() {
+	// The `Console` is reset last, so its `Default` wins.
+	let mut framework = Framework::new(Console::default());
+	framework.get_mut().knob.value = 1;
+	framework.send(&mut Reset);
+	assert_eq!(framework.get().knob.value, 9);
+
+	// The `Console` is reset first, then the `Knob` it holds.
+	framework.set_top_down(true);
+	framework.send(&mut Reset);
+	assert_eq!(framework.get().knob.value, 0);
+}
+
+trait Counter {
+	fn count(&self) -> u16;
+}
+
+impl Counter for Child {
+	fn count(&self) -> u16 { self.counter }
+}
+
+impl Counter for ChildChild {
+	fn count(&self) -> u16 { self.counter }
+}
+
+static COUNTERS: TraitFilter<dyn Counter> = register_trait_actors!(dyn Counter: Child, ChildChild);
+
+#[test]
+fn send_trait() {
+	let mut framework = framework();
+	framework.get_mut().child.child.counter = 5;
+
+	let mut counts = Vec::new();
+	let sent = framework.send_trait(&mut counts, &COUNTERS, |counter, counts| counts.push(counter.count()));
+	// `Root` isn't registered.
+	assert_eq!(sent, 2);
+	assert_eq!(counts, [5, 2]);
+}
+
+#[derive(Actor)]
+struct Gossip {
+	heard: u16,
+}
+
+enum Rumor {
+	Start { others: bool },
+	Spread,
+}
+
+receive! {
+	Rumor => Gossip = (&mut self, rumor, context) {
+		match rumor {
+			Rumor::Start { others: true } => context.broadcast_others(self, &mut Rumor::Spread),
+			Rumor::Start { others: false } => context.broadcast(self, &mut Rumor::Spread),
+			Rumor::Spread => self.heard += 1,
+		}
+	}
+}
+
+#[test]
+fn broadcast_others() {
+	let mut framework = Framework::new(vec![Gossip { heard: 0 }, Gossip { heard: 0 }]);
+
+	framework.send_to(&mut Rumor::Start { others: true }, |gossips| &mut gossips[0]);
+	assert_eq!(framework.get()[0].heard, 0);
+	assert_eq!(framework.get()[1].heard, 1);
+
+	// With `broadcast`, the sender hears itself too.
+	framework.send_to(&mut Rumor::Start { others: false }, |gossips| &mut gossips[0]);
+	assert_eq!(framework.get()[0].heard, 1);
+	assert_eq!(framework.get()[1].heard, 2);
+}
+
+// Forgets to un-implement `NotActor`.
+#[cfg(debug_assertions)]
+struct Sneaky {
+	leaf: Leaf,
+}
+
+#[cfg(debug_assertions)]
+unsafe impl Actor for Sneaky {
+	fn accept<V, R>(&mut self, visitor: &mut impl ActorVisitor<V, R>) { self.leaf.accept(visitor); }
+}
+
+#[cfg(debug_assertions)]
+#[test]
+fn audit_not_actor() {
+	let mut framework = framework();
+	framework.audit_not_actor(|root| (&mut root.data, &mut root.counter));
+
+	let mut framework = Framework::new((
+		Sneaky {
+			leaf: Leaf { counter: 0 },
+		},
+		Leaf { counter: 0 },
+	));
+	framework.audit_not_actor(|root| &mut root.1.counter);
+	let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+		framework.audit_not_actor(|root| &mut root.0);
+	}));
+	assert!(result.is_err());
+}
+
+#[test]
+fn fork_sub() {
+	let framework = Framework::new((Leaf { counter: 0 }, vec![Setting { value: 1 }, Setting { value: 2 }]));
+
+	let mut fork = framework.fork_sub(|root| &root.1);
+	assert_eq!(fork.send(&mut Increment(10)), 2);
+	assert_eq!(fork.get()[1].value, 12);
+	assert_eq!(framework.get().1[1].value, 2);
+}
+
+#[test]
+fn dry_run() {
+	let mut framework = Framework::new(vec![Setting { value: 1 }, Setting { value: 2 }]);
+	framework.route::<Increment, Setting>();
+
+	let preview = framework.dry_run(&mut Increment(10));
+	assert_eq!(preview[0].value, 11);
+	assert_eq!(framework.get()[0].value, 1);
+
+	// The preview matches what sending it for real does.
+	framework.send(&mut Increment(10));
+	assert!(framework.get().iter().zip(&preview).all(|(a, b)| a.value == b.value));
+}
+
+#[derive(Actor)]
+#[actor(order_by = "name")]
+struct Declared {
+	alpha: Leaf,
+	beta: Leaf,
+}
+
+// The same as `Declared`, with its fields reordered.
+#[derive(Actor)]
+#[actor(order_by = "name")]
+struct Reordered {
+	beta: Leaf,
+	alpha: Leaf,
+}
+
+#[test]
+fn order_by_name() {
+	let mut framework = Framework::new((
+		Declared {
+			alpha: Leaf { counter: 1 },
+			beta: Leaf { counter: 2 },
+		},
+		Reordered {
+			beta: Leaf { counter: 2 },
+			alpha: Leaf { counter: 1 },
+		},
+	));
+
+	let mut ping = Ping(Vec::new());
+	framework.send(&mut ping);
+	assert_eq!(ping.0, [1, 2, 1, 2]);
+	assert_eq!(Reordered::FIELDS, ["alpha", "beta"]);
+}
+
+#[test]
+fn rev() {
+	let mut framework = Framework::new(Rev((Leaf { counter: 1 }, Leaf { counter: 2 }, Leaf { counter: 3 })));
+
+	// Paths still use the indices of the tuple.
+	let mut ping = Ping(Vec::new());
+	assert!(framework.send_to_path("0", &mut ping));
+	assert_eq!(ping.0, [1]);
+
+	// Every leaf got the `Increment` that the first one broadcast.
+	let mut ping = Ping(Vec::new());
+	framework.send(&mut ping);
+	assert_eq!(ping.0, [4, 3, 2]);
+}
+
+#[derive(Actor)]
+#[actor(enable_field = "active")]
+struct Pausable {
+	active: bool,
+	counter: u16,
+	leaf: Leaf,
+}
+
+receive! {
+	Increment => Pausable = (&mut self, message, _) {
+		self.counter += message.0;
+	}
+}
+
+#[test]
+fn enable_field() {
+	let mut framework = Framework::new((
+		Leaf { counter: 0 },
+		Pausable {
+			active: true,
+			counter: 0,
+			leaf: Leaf { counter: 0 },
+		},
+	));
+	let counters = |framework: &Framework<(Leaf, Pausable)>| {
+		let (leaf, pausable) = framework.get();
+		(leaf.counter, pausable.counter, pausable.leaf.counter)
+	};
+
+	assert_eq!(framework.send(&mut Increment(1)), 3);
+	assert_eq!(counters(&framework), (1, 1, 1));
+
+	// Disabling an actor skips its sub-actors too.
+	framework.get_mut().1.active = false;
+	assert_eq!(framework.send(&mut Increment(1)), 1);
+	assert!(!framework.send_to_path("1.leaf", &mut Increment(1)));
+	assert_eq!(counters(&framework), (2, 1, 1));
+
+	framework.get_mut().1.active = true;
+	assert!(framework.send_to_path("1.leaf", &mut Increment(1)));
+	assert_eq!(counters(&framework), (2, 1, 2));
+}
+
+#[test]
+fn weak() {
+	let alive = Rc::new(RefCell::new(Leaf { counter: 0 }));
+	let dead = Rc::new(RefCell::new(Leaf { counter: 0 }));
+	let mut framework = Framework::new(vec![Rc::downgrade(&alive), Rc::downgrade(&dead)]);
+	drop(dead);
+
+	framework.send(&mut Increment(1));
+	assert_eq!(alive.borrow().counter, 1);
+	assert!(framework.get()[1].upgrade().is_none());
+}
+
+#[test]
+fn dispatch_message() {
+	// Two trees that aren't owned by a `Framework`.
+	let mut first = Box::new(Leaf { counter: 0 });
+	let mut second = Box::new(Leaf { counter: 5 });
+
+	for root in [&mut *first, &mut *second] {
+		let handled = unsafe { send::dispatch_message(root, &mut Increment(1)) };
+		assert_eq!(handled, 1);
+	}
+	assert_eq!(first.counter, 1);
+	assert_eq!(second.counter, 6);
+}
+
+#[test]
+fn dispatch_into() {
+	// The tree is owned by something else, and only borrowed for the dispatch.
+	struct App {
+		title: &'static str,
+		tree: Child,
+	}
+	let mut app = App {
+		title: "app",
+		tree: Child {
+			counter: 2,
+			child: ChildChild { counter: 2 },
+		},
+	};
+
+	let tree = &mut app.tree;
+	assert_eq!(send::dispatch_into(tree, &mut Increment(1)), 2);
+	// `ChildChild` broadcasts a `Decrement` to the borrowed tree, which is the root here.
+	assert_eq!((app.tree.counter, app.tree.child.counter), (2, 3));
+	assert_eq!(app.title, "app");
+}
+
+#[derive(Actor)]
+struct Ui {
+	redraws: u16,
+}
+
+#[derive(Actor)]
+struct Sim {
+	steps: u16,
+}
+
+#[derive(Actor)]
+struct Audio {
+	beeps: u16,
+}
+
+struct Step;
+
+struct Stepped;
+
+receive! {
+	Step => Sim = (&mut self, _, context) {
+		self.steps += 1;
+		context.broadcast(self, &mut Stepped);
+	}
+}
+
+receive! {
+	Stepped => Ui = (&mut self, _, _) {
+		self.redraws += 1;
+	}
+}
+
+receive! {
+	Stepped => Audio = (&mut self, _, _) {
+		self.beeps += 1;
+	}
+}
+
+#[test]
+fn multiple_roots() {
+	let mut framework = Framework::new((Ui { redraws: 0 }, Sim { steps: 0 }, Audio { beeps: 0 }));
+
+	framework.send(&mut Step);
+	assert_eq!(framework.root::<0>().redraws, 1);
+	assert_eq!(framework.root::<1>().steps, 1);
+	assert_eq!(framework.root::<2>().beeps, 1);
+
+	// The broadcast from `Sim` still reaches the other roots.
+	framework.send_to_root::<1, _>(&mut Step);
+	assert_eq!(framework.root::<0>().redraws, 2);
+	assert_eq!(framework.root::<2>().beeps, 2);
+
+	framework.send_to_root::<0, _>(&mut Step);
+	assert_eq!(framework.root::<1>().steps, 2);
+
+	framework.root_mut::<2>().beeps = 0;
+	assert_eq!(framework.get().2.beeps, 0);
+}
+
+#[derive(Actor)]
+struct Tagged<T> {
+	value: Leaf,
+	_marker: PhantomData<T>,
+	_unit: (),
+}
+
+#[derive(Actor)]
+enum TaggedEnum<T> {
+	Tagged(Leaf, std::marker::PhantomData<fn() -> T>),
+	Unit { _unit: (), value: Leaf },
+}
+
+#[test]
+fn markers() {
+	let mut framework = Framework::new((
+		Tagged::<*const u8> {
+			value: Leaf { counter: 0 },
+			_marker: PhantomData,
+			_unit: (),
+		},
+		TaggedEnum::<String>::Tagged(Leaf { counter: 0 }, PhantomData),
+		TaggedEnum::<String>::Unit {
+			_unit: (),
+			value: Leaf { counter: 0 },
+		},
+	));
+
+	assert_eq!(framework.send(&mut Increment(1)), 3);
+	assert_eq!(framework.root::<0>().value.counter, 1);
+}
+
+#[derive(Actor)]
+#[actor(no_meta)]
+struct Unnamed;
+
+impl ActorMeta for Unnamed {
+	const ACTOR_NAME: &'static str = "Custom";
+	const FIELDS: &'static [&'static str] = &[];
+}
+
+#[test]
+fn actor_meta() {
+	assert_eq!(Child::ACTOR_NAME, "Child");
+	assert_eq!(Root::FIELDS, ["data", "counter", "child"]);
+	// Generic parameters and skipped fields are left out.
+	assert_eq!(Tagged::<u8>::ACTOR_NAME, "Tagged");
+	assert_eq!(Tagged::<u8>::FIELDS, ["value"]);
+	assert!(TaggedEnum::<u8>::FIELDS.is_empty());
+	assert_eq!(Unnamed::ACTOR_NAME, "Custom");
+}
+
+#[derive(Actor)]
+struct Body {
+	mass: u16,
+	#[actor(recursive)]
+	moons: Vec<Body>,
+}
+
+#[derive(Actor)]
+struct Probe;
+
+struct Mass;
+
+impl Query for Mass {
+	type Response = u16;
+}
+
+receive! {
+	Mass => Body = respond (&mut self, _, _) {
+		self.mass
+	}
+}
+
+// Only responds some of the time.
+impl<R> Responder<Mass, R> for Probe {
+	fn respond(&mut self, _: &mut Mass, _: Context<Self, R>) -> Option<u16> { None }
+}
+
+#[test]
+fn send_fold() {
+	let mut framework = Framework::new(Body {
+		mass: 100,
+		moons: vec![
+			Body {
+				mass: 3,
+				moons: Vec::new(),
+			},
+			Body {
+				mass: 2,
+				moons: Vec::new(),
+			},
+		],
+	});
+
+	assert_eq!(framework.send_fold(&mut Mass, 0, |total, mass| total + mass), 105);
+
+	// Responses are folded in traversal order.
+	let order = framework.send_fold(&mut Mass, Vec::new(), |mut order, mass| {
+		order.push(mass);
+		order
+	});
+	assert_eq!(order, [3, 2, 100]);
+
+	let mut framework = Framework::new((Probe, Probe));
+	assert_eq!(framework.send_fold(&mut Mass, 0, |total, mass| total + mass), 0);
+}
+
+#[derive(Actor)]
+struct Scale {
+	masses: Vec<u16>,
+}
+
+struct Weigh;
+
+receive! {
+	Weigh => Scale = (&mut self, _, context) {
+		self.masses = context.broadcast_fold(self, &mut Mass, Vec::new(), |mut masses, mass| {
+			masses.push(mass);
+			masses
+		});
+	}
+}
+
+#[test]
+fn broadcast_fold() {
+	let mut framework = Framework::new((
+		Scale { masses: Vec::new() },
+		Body {
+			mass: 100,
+			moons: vec![Body {
+				mass: 3,
+				moons: Vec::new(),
+			}],
+		},
+	));
+
+	framework.send(&mut Weigh);
+	assert_eq!(framework.get().0.masses, [3, 100]);
+}
+
+#[derive(Actor)]
+struct Parrot {
+	squawks: u16,
+}
+
+struct Squawk;
+
+receive! {
+	Squawk => Parrot = (&mut self, squawk, context) {
+		self.squawks += 1;
+		context.broadcast_others(self, squawk);
+	}
+}
+
+#[test]
+fn reentrancy_limit() {
+	// `ChildChild` broadcasts while handling `Increment`, which is nested once.
+	let mut framework = framework();
+	framework.set_reentrancy_limit(1);
+	framework.send(&mut Increment(1));
+	assert_eq!(framework.get().child.child.counter, 3);
+}
+
+#[test]
+#[should_panic(expected = "broadcast re-entrancy limit exceeded (16); possible message loop")]
+fn reentrancy_loop() {
+	let mut framework = Framework::new((Parrot { squawks: 0 }, Parrot { squawks: 0 }));
+	framework.set_reentrancy_limit(16);
+	framework.send(&mut Squawk);
+}
+
+#[test]
+fn send_named() {
+	let mut framework = Framework::new((
+		Leaf { counter: 0 },
+		Cursor {
+			position: (0, 0),
+			closed: false,
+		},
+	));
+	framework.registry_mut().register("increment", |args| match args {
+		[Value::Int(amount)] => Ok(Increment(*amount as u16)),
+		_ => Err(MessageError::Arguments("expected an integer".to_string())),
+	});
+	framework.registry_mut().register("move", |args| match args {
+		[x, y] => Ok(Moved {
+			x: x.as_int().unwrap_or(0) as i32,
+			y: y.as_int().unwrap_or(0) as i32,
+		}),
+		_ => Err(MessageError::Arguments("expected 2 coordinates".to_string())),
+	});
+
+	let command = "increment 3";
+	let mut tokens = command.split(' ');
+	let name = tokens.next().unwrap();
+	let args: Vec<_> = tokens.map(Value::parse).collect();
+	assert_eq!(framework.send_named(name, &args), Ok(1));
+	assert_eq!(framework.send_named("move", &[Value::from(4), Value::from(5)]), Ok(1));
+	assert_eq!((framework.get().0.counter, framework.get().1.position), (3, (4, 5)));
+
+	assert_eq!(
+		framework.send_named("increment", &[Value::from("many")]),
+		Err(MessageError::Arguments("expected an integer".to_string()))
+	);
+	assert_eq!(
+		framework.send_named("jump", &[]),
+		Err(MessageError::Unknown("jump".to_string()))
+	);
+
+	let mut names: Vec<_> = framework.registry().names().collect();
+	names.sort();
+	assert_eq!(names, ["increment", "move"]);
+}
+
+dispatch_enum! {
+	enum Input {
+		#[derive(Clone, Copy)]
+		Moved { x: i32, y: i32 },
+		Scrolled(i32, i32),
+		Closed
+	}
+}
+
+#[derive(Actor)]
+struct Cursor {
+	position: (i32, i32),
+	closed: bool,
+}
+
+receive! {
+	Moved => Cursor = (&mut self, moved, _) {
+		self.position = (moved.x, moved.y);
+	}
+
+	Scrolled => Cursor = (&mut self, scrolled, _) {
+		self.position.0 += scrolled.0;
+		self.position.1 += scrolled.1;
+	}
+
+	Closed => Cursor = (&mut self, _, _) {
+		self.closed = true;
+	}
+}
+
+#[test]
+fn send_enum() {
+	let mut framework = Framework::new((
+		Cursor {
+			position: (0, 0),
+			closed: false,
+		},
+		Leaf { counter: 0 },
+	));
+
+	let moved = Moved { x: 1, y: 2 };
+	assert_eq!(framework.send_enum(Input::from(moved)), 1);
+	assert_eq!(framework.send_enum(Input::Scrolled(Scrolled(3, 4))), 1);
+	assert_eq!(framework.get().0.position, (moved.x + 3, moved.y + 4));
+
+	framework.send_enum(Input::Closed(Closed));
+	assert!(framework.get().0.closed);
+}
+
+#[derive(Actor, Clone)]
+struct Journal {
+	entries: Vec<(u64, u16)>,
+}
+
+#[derive(Actor)]
+struct Witness {
+	seen: Vec<Option<u64>>,
+}
+
+struct Logged(u16);
+
+struct Echo;
+
+receive! {
+	Meta<Logged> => Journal = (&mut self, logged, context) {
+		self.entries.push((logged.sequence, logged.0));
+		context.broadcast(self, &mut Echo);
+	}
+}
+
+receive! {
+	Echo => Witness = (&mut self, _, context) {
+		self.seen.push(context.meta().map(|meta| meta.sequence));
+	}
+}
+
+#[test]
+fn send_meta() {
+	let mut framework = Framework::new((Journal { entries: Vec::new() }, Witness { seen: Vec::new() }));
+
+	let first = framework.send_meta(Logged(1));
+	let second = framework.send_meta(Logged(2));
+	assert_eq!((first.sequence, second.sequence), (0, 1));
+	assert!(first.time <= second.time);
+	assert_eq!(framework.get().0.entries, [(0, 1), (1, 2)]);
+
+	// Broadcasts sent while handling it can read the metadata too, but other messages can't.
+	framework.send(&mut Echo);
+	assert_eq!(framework.get().1.seen, [Some(0), Some(1), None]);
+}
+
+#[test]
+fn pause_resume() {
+	let mut framework = Framework::new(Journal { entries: Vec::new() });
+	framework.send_meta(Logged(1));
+
+	let state = framework.pause();
+	assert_eq!(state.root().entries, [(0, 1)]);
+	framework.send_meta(Logged(2));
+	framework.send_meta(Logged(3));
+
+	// Replaying from the state gives the same sequence numbers again.
+	framework.resume(state.clone());
+	framework.send_meta(Logged(4));
+	assert_eq!(framework.get().entries, [(0, 1), (1, 4)]);
+	framework.resume(state);
+	assert_eq!(framework.get().entries, [(0, 1)]);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn snapshot() {
+	let mut framework = framework();
+	framework.send(&mut Increment(1));
+
+	let mut saved = Vec::new();
+	framework
+		.snapshot(&mut serde_json::Serializer::new(&mut saved))
+		.unwrap();
+	let loaded = Framework::<Root>::load(&mut serde_json::Deserializer::from_slice(&saved)).unwrap();
+	let counters = |root: &Root| {
+		(
+			root.data.data,
+			root.counter,
+			root.child.counter,
+			root.child.child.counter,
+		)
+	};
+	assert_eq!(counters(loaded.get()), counters(framework.get()));
+
+	// Loaded actors are mounted.
+	let json = r#"[{ "mounted": false, "counter": 3 }]"#;
+	let loaded = Framework::<Vec<Entity>>::load(&mut serde_json::Deserializer::from_str(json)).unwrap();
+	assert!(loaded.get()[0].mounted);
+	assert_eq!(loaded.get()[0].counter, 3);
+}
+
+#[derive(Actor)]
+struct Bell;
+
+struct Chime(Vec<&'static str>);
+
+receive! {
+	Chime => Bell = (&mut self, chime, _) {
+		chime.0.push("bell");
+	}
+}
+
+#[test]
+fn on() {
+	let mut framework = Framework::new((Bell, Bell));
+	// SAFETY: `Chime` and `Increment` have no lifetime parameters.
+	unsafe {
+		framework.on::<Chime>(Box::new(|chime| chime.0.push("first")));
+		framework.on::<Chime>(Box::new(|chime| chime.0.push("second")));
+	}
+
+	// Registered handlers run after the actors, in the order they were registered.
+	let mut chime = Chime(Vec::new());
+	assert_eq!(framework.send(&mut chime), 4);
+	assert_eq!(chime.0, ["bell", "bell", "first", "second"]);
+
+	let mut framework = Framework::new(Bell);
+	unsafe { framework.on::<Increment>(Box::new(|increment| increment.0 += 1)) };
+	let mut increment = Increment(1);
+	assert_eq!(framework.send(&mut increment), 1);
+	assert_eq!(increment.0, 2);
+}
+
+#[derive(Actor)]
+struct Courier {
+	relayed: u16,
+}
+
+struct Relay {
+	to_self: bool,
+}
+
+impl Receiver<Relay, (Courier, Leaf)> for Courier {
+	fn receive(&mut self, relay: &mut Relay, context: Context<Self, (Courier, Leaf)>) {
+		self.relayed += 1;
+		if relay.to_self {
+			context.send_sibling(self, &mut Increment(1), |root| &mut root.0);
+		} else {
+			context.send_sibling(self, &mut Increment(1), |root| &mut root.1);
+		}
+	}
+}
+
+#[test]
+fn send_sibling() {
+	let mut framework = Framework::new((Courier { relayed: 0 }, Leaf { counter: 0 }));
+	framework.send(&mut Relay { to_self: false });
+	assert_eq!((framework.get().0.relayed, framework.get().1.counter), (1, 1));
+}
+
+#[test]
+#[should_panic(expected = "the target of `send_sibling` overlaps the sending `Actor`")]
+fn send_sibling_overlapping() {
+	Framework::new((Courier { relayed: 0 }, Leaf { counter: 0 })).send(&mut Relay { to_self: true });
+}
+
+#[derive(Actor)]
+struct Dispatcher {
+	dispatched: u16,
+}
+
+struct Fanout;
+
+struct Notice(Vec<&'static str>);
+
+impl Receiver<Fanout, (Dispatcher, Leaf, Entity)> for Dispatcher {
+	fn receive(&mut self, _: &mut Fanout, context: Context<Self, (Dispatcher, Leaf, Entity)>) {
+		self.dispatched += 1;
+		let mut ping = Notice(Vec::new());
+		send_to_each!(context, self, &mut ping, |root| &mut root.2, |root| &mut root.1);
+		assert_eq!(ping.0, ["entity", "leaf"]);
+	}
+}
+
+receive! {
+	Notice => Leaf = (&mut self, ping, _) { ping.0.push("leaf") }
+	Notice => Entity = (&mut self, ping, _) { ping.0.push("entity") }
+}
+
+#[test]
+fn send_to_each() {
+	let mut framework = Framework::new((
+		Dispatcher { dispatched: 0 },
+		Leaf { counter: 0 },
+		Entity {
+			mounted: false,
+			counter: 0,
+		},
+	));
+	assert_eq!(framework.send(&mut Fanout), 1);
+	assert_eq!(framework.get().0.dispatched, 1);
+}
+
+#[test]
+fn trace() {
+	let mut framework = Framework::new((Leaf { counter: 0 }, Bell));
+	framework.send(&mut Increment(1));
+	assert_eq!(framework.recent().count(), 0);
+
+	framework.enable_trace(2);
+	framework.send(&mut Increment(1));
+	let delivery = |actor, message| Delivery { actor, message };
+	assert_eq!(
+		framework.recent().collect::<Vec<_>>(),
+		[delivery("tests::Leaf", "tests::Increment")]
+	);
+
+	// Only the latest deliveries are kept.
+	framework.send(&mut Chime(Vec::new()));
+	framework.send(&mut Increment(1));
+	assert_eq!(
+		framework.recent().collect::<Vec<_>>(),
+		[
+			delivery("tests::Bell", "tests::Chime"),
+			delivery("tests::Leaf", "tests::Increment"),
+		]
+	);
+
+	framework.enable_trace(0);
+	framework.send(&mut Increment(1));
+	assert_eq!(framework.recent().count(), 0);
+}
+
+#[derive(Actor)]
+struct Terrain {
+	heights: Vec<u32>,
+	altimeters: Vec<Altimeter>,
+}
+
+#[derive(Actor)]
+struct Altimeter {
+	peak: Cell<u32>,
+}
+
+struct Heights<'a>(&'a [u32]);
+
+impl<'a> ReceiverRef<Heights<'a>> for Altimeter {
+	fn receive_ref(&self, heights: &Heights<'a>) { self.peak.set(heights.0.iter().copied().max().unwrap_or(0)); }
+}
+
+#[test]
+fn view_broadcast() {
+	let mut framework = Framework::new(Terrain {
+		heights: vec![3, 14, 1, 5],
+		altimeters: vec![Altimeter { peak: Cell::new(0) }, Altimeter { peak: Cell::new(0) }],
+	});
+
+	let viewed = framework.view_broadcast(|terrain| &terrain.heights, |heights| Heights(heights));
+	assert_eq!(viewed, 2);
+	assert!(framework
+		.get()
+		.altimeters
+		.iter()
+		.all(|altimeter| altimeter.peak.get() == 14));
+
+	// Views nobody reads aren't delivered.
+	assert_eq!(
+		framework.view_broadcast(|terrain| &terrain.heights, |heights| heights.len()),
+		0
+	);
+	// A `Wrap` reads the view through the `Altimeter` it wraps.
+	let mut framework = Framework::new((vec![2, 7], Wrap(Altimeter { peak: Cell::new(0) })));
+	assert_eq!(framework.view_broadcast(|root| &root.0, |heights| Heights(heights)), 1);
+	assert_eq!(framework.get().1 .0.peak.get(), 7);
+}
+
+#[derive(Actor)]
+struct Widget {
+	start: u16,
+	end: u16,
+	hits: u16,
+}
+
+#[derive(Actor)]
+struct Backdrop {
+	hits: u16,
+}
+
+struct Press(u16);
+
+impl Query for Press {
+	type Response = bool;
+}
+
+receive! {
+	Press => Widget = respond (&mut self, click, _) {
+		self.hits += 1;
+		(self.start..self.end).contains(&click.0)
+	}
+
+	Press => Backdrop = respond (&mut self, _, _) {
+		self.hits += 1;
+		true
+	}
+}
+
+#[test]
+fn send_first() {
+	let widget = |start, end| Widget { start, end, hits: 0 };
+	let mut framework = Framework::new((vec![widget(0, 10), widget(5, 15)], Backdrop { hits: 0 }));
+
+	// Both widgets contain 7, but the first one wins.
+	assert!(framework.send_first(&mut Press(7)).unwrap().ends_with("Widget"));
+	assert_eq!(framework.get().0[0].hits, 1);
+	assert_eq!(framework.get().0[1].hits, 0);
+	assert_eq!(framework.get().1.hits, 0);
+
+	assert!(framework.send_first(&mut Press(20)).unwrap().ends_with("Backdrop"));
+	assert_eq!(framework.get().0[1].hits, 1);
+	assert_eq!(framework.get().1.hits, 1);
+
+	let mut framework = Framework::new(vec![widget(0, 10)]);
+	assert_eq!(framework.send_first(&mut Press(20)), None);
+}
+
+#[derive(Actor)]
+struct Family {
+	data: Sibling,
+	counter: u16,
+	child: Sibling,
+	children: Vec<Sibling>,
+}
+
+#[derive(Actor)]
+struct Sibling {
+	notified: u16,
+	#[actor(recursive)]
+	nested: Option<Box<Sibling>>,
+}
+
+struct Notify;
+
+struct Notified;
+
+receive! {
+	Notify => Sibling = (&mut self, _, context) {
+		context.send_siblings(self, &mut Notified);
+	}
+
+	Notified => Sibling = (&mut self, _, _) {
+		self.notified += 1;
+	}
+}
+
+receive! {
+	Notified => Family = (&mut self, _, _) {
+		self.counter += 1;
+	}
+}
+
+fn sibling(nested: Option<Sibling>) -> Sibling {
+	Sibling {
+		notified: 0,
+		nested: nested.map(Box::new),
+	}
+}
+
+#[test]
+fn send_siblings() {
+	let mut framework = Framework::new(Family {
+		data: sibling(None),
+		counter: 0,
+		child: sibling(Some(sibling(None))),
+		children: vec![sibling(None), sibling(None)],
+	});
+
+	// `child` notifies `data` and the elements of `children`, but not itself, its parent, or its sub-actors.
+	framework.send_to(&mut Notify, |family| &mut family.child);
+	let family = framework.get();
+	assert_eq!(family.counter, 0);
+	assert_eq!(family.data.notified, 1);
+	assert_eq!(family.child.notified, 0);
+	assert_eq!(family.child.nested.as_ref().unwrap().notified, 0);
+	assert!(family.children.iter().all(|sibling| sibling.notified == 1));
+
+	// An only child has no siblings.
+	framework.send_to(&mut Notify, |family| &mut **family.child.nested.as_mut().unwrap());
+	let family = framework.get();
+	assert_eq!(family.child.notified, 0);
+	assert_eq!(family.data.notified, 1);
+
+	// Roots are siblings of each other.
+	let mut framework = Framework::new((sibling(None), sibling(Some(sibling(None)))));
+	framework.send_to_root::<0, _>(&mut Notify);
+	assert_eq!(framework.root::<0>().notified, 0);
+	assert_eq!(framework.root::<1>().notified, 1);
+	assert_eq!(framework.root::<1>().nested.as_ref().unwrap().notified, 0);
+}
+
+mod foreign {
+	// Pretend this is from another crate.
+	pub struct Tree<T> {
+		pub value: T,
+		pub children: Vec<T>,
+	}
+}
+
+struct Wrapped(foreign::Tree<Leaf>);
+
+send::impl_actor!(Wrapped => |wrapped| [&mut wrapped.0.value, &mut wrapped.0.children]);
+
+struct GenericWrapped<T>(foreign::Tree<T>);
+
+send::impl_actor!(%(T) GenericWrapped<T> => |wrapped| [&mut wrapped.0.children]);
+
+receive! {
+	Increment => Wrapped = (&mut self, _, _) {
+		self.0.value.counter += 10;
+	}
+}
+
+#[test]
+fn impl_actor() {
+	let leaves = || foreign::Tree {
+		value: Leaf { counter: 0 },
+		children: vec![Leaf { counter: 0 }, Leaf { counter: 0 }],
+	};
+	let mut framework = Framework::new((Wrapped(leaves()), GenericWrapped(leaves())));
+
+	assert_eq!(framework.send(&mut Increment(1)), 6);
+	let (wrapped, generic) = framework.get();
+	assert_eq!(wrapped.0.value.counter, 11);
+	assert!(wrapped.0.children.iter().all(|leaf| leaf.counter == 1));
+	assert_eq!(generic.0.value.counter, 0);
+	assert!(generic.0.children.iter().all(|leaf| leaf.counter == 1));
+}
+
+struct Tracked<T> {
+	inner: T,
+	writes: Cell<u16>,
+}
+
+impl<T> Deref for Tracked<T> {
+	type Target = T;
+
+	fn deref(&self) -> &T { &self.inner }
+}
+
+impl<T> DerefMut for Tracked<T> {
+	fn deref_mut(&mut self) -> &mut T {
+		self.writes.set(self.writes.get() + 1);
+		&mut self.inner
+	}
+}
+
+send::impl_actor_via_deref!(%(T) Tracked<T>);
+
+#[test]
+fn impl_actor_via_deref() {
+	let mut framework = Framework::new(Tracked {
+		inner: (Leaf { counter: 0 }, Leaf { counter: 0 }),
+		writes: Cell::new(0),
+	});
+
+	assert_eq!(framework.send(&mut Increment(1)), 2);
+	assert!(framework.send_to_path("1", &mut Increment(1)));
+	let tracked = framework.get();
+	assert_eq!((tracked.0.counter, tracked.1.counter), (1, 2));
+	assert_eq!(tracked.writes.get(), 2);
+}
+
+struct Gem(u16);
+
+struct Coin(u16);
+
+struct Pouch {
+	gem: Gem,
+	coins: Vec<Coin>,
+	label: &'static str,
+}
+
+struct Purse(Pouch, Gem);
+
+send::leaf_actors!(Gem, Coin);
+
+send::container_actors! {
+	Pouch => [gem, coins],
+	Purse => [0, 1],
+}
+
+receive! {
+	Increment => Gem = (&mut self, message, _) {
+		self.0 += message.0;
+	}
+
+	Increment => Coin = (&mut self, message, _) {
+		self.0 += message.0;
+	}
+
+	Increment => Purse = (&mut self, _, _) {
+		self.0.label = "counted";
+	}
+}
+
+#[test]
+fn leaf_and_container_actors() {
+	let mut framework = Framework::new(Purse(
+		Pouch {
+			gem: Gem(0),
+			coins: vec![Coin(0), Coin(0)],
+			label: "",
+		},
+		Gem(0),
+	));
+
+	// `Pouch` doesn't handle `Increment` itself, but forwards it.
+	assert_eq!(framework.send(&mut Increment(1)), 5);
+	assert!(framework.send_to_path("0.coins.1", &mut Increment(1)));
+	assert!(framework.send_to_path("1", &mut Increment(1)));
+	assert!(!framework.send_to_path("0.label", &mut Increment(1)));
+	let purse = framework.get();
+	assert_eq!(purse.0.gem.0, 1);
+	assert_eq!(purse.0.coins.iter().map(|coin| coin.0).collect::<Vec<_>>(), [1, 2]);
+	assert_eq!((purse.0.label, purse.1 .0), ("counted", 2));
+}
+
+#[derive(Actor)]
+struct Shutdown {
+	token: CancelToken,
+}
+
+receive! {
+	Increment => Shutdown = (&mut self, _, _) {
+		// Stands in for a shutdown request from another thread.
+		self.token.cancel();
+	}
+}
+
+#[test]
+fn send_cancelable() {
+	let token = CancelToken::new();
+	let mut framework = Framework::new((
+		Leaf { counter: 0 },
+		Shutdown { token: token.clone() },
+		Leaf { counter: 0 },
+	));
+
+	assert_eq!(framework.send_cancelable(&mut Increment(1), &token), 2);
+	assert!(token.is_cancelled());
+	assert_eq!(framework.get().0.counter, 1);
+	assert_eq!(framework.get().2.counter, 0);
+
+	assert_eq!(framework.send_cancelable(&mut Increment(1), &token), 0);
+	assert_eq!(framework.get().0.counter, 1);
+
+	assert_eq!(framework.send_cancelable(&mut Increment(1), &CancelToken::new()), 3);
+	assert_eq!(framework.get().2.counter, 1);
+}
+
+#[test]
+fn send_observed() {
+	let mut framework = Framework::new((
+		Leaf { counter: 0 },
+		Panel {
+			size: 1,
+			children: vec![Panel {
+				size: 2,
+				children: Vec::new(),
+			}],
+		},
+	));
+
+	// The panels are visited even though they don't handle the message.
+	let mut visited = Vec::new();
+	let handled = framework.send_observed(&mut Increment(1), |name| {
+		visited.push(name.rsplit("::").next().unwrap())
+	});
+	assert_eq!(handled, 1);
+	assert_eq!(visited, ["Leaf", "Panel", "Panel"]);
+}
+
+#[derive(Actor, Clone)]
+struct Gauge {
+	value: u16,
+}
+
+struct Reading(u16);
+
+receive! {
+	Reading => Gauge = (&mut self, reading, context) {
+		if self.value != reading.0 {
+			self.value = reading.0;
+			context.mark_dirty(self);
+		}
+	}
+}
+
+#[test]
+fn send_dirty() {
+	let mut framework = Framework::new((Gauge { value: 1 }, Leaf { counter: 0 }, (Gauge { value: 2 },)));
+
+	// Only the gauges that changed are dirty, even though both handled the reading.
+	let dirty = framework.send_dirty(&mut Reading(2));
+	let dirty: Vec<_> = dirty.iter().map(|name| name.rsplit("::").next().unwrap()).collect();
+	assert_eq!(dirty, ["Gauge"]);
+	assert!(framework.send_dirty(&mut Reading(2)).is_empty());
+	assert_eq!(framework.send_dirty(&mut Reading(3)).len(), 2);
+
+	// Marks outside of `send_dirty` are ignored.
+	framework.send(&mut Reading(4));
+	assert!(framework.send_dirty(&mut Increment(1)).is_empty());
+}
+
+static DEFAULT_GAUGE: Gauge = Gauge { value: 1 };
+
+#[test]
+fn send_dirty_copy_on_write() {
+	let shared = CowArc::new(Gauge { value: 1 });
+	let mut framework = Framework::new((Cow::Borrowed(&DEFAULT_GAUGE), shared.clone()));
+
+	// The copies receive the message where they end up, so they are still there to be found dirty.
+	assert_eq!(framework.send_dirty(&mut Reading(2)).len(), 2);
+	let (default, copy) = framework.get();
+	assert!(matches!(default, Cow::Owned(Gauge { value: 2 })));
+	assert_eq!((copy.value, shared.value), (2, 1));
+}
+
+#[test]
+fn route() {
+	let mut framework = framework();
+	framework.route::<Increment, Child>();
+
+	assert_eq!(framework.send(&mut Increment(1)), 1);
+	assert_eq!(framework.get().counter, 2);
+	assert_eq!(framework.get().child.counter, 3);
+	assert_eq!(framework.get().child.child.counter, 2);
+
+	framework.route::<Increment, Root>();
+	assert_eq!(framework.send(&mut Increment(1)), 2);
+	assert_eq!(framework.get().counter, 3);
+	assert_eq!(framework.get().child.counter, 4);
+	assert_eq!(framework.get().child.child.counter, 2);
+
+	// Messages without routes go everywhere.
+	assert_eq!(framework.send(&mut Decrement(1)), 1);
+	assert_eq!(framework.get().child.counter, 3);
+}
+
+#[cfg(feature = "dedup")]
+#[derive(Actor)]
+struct Path {
+	shared: std::rc::Weak<RefCell<Leaf>>,
+}
+
+#[cfg(feature = "dedup")]
+#[test]
+fn dedup() {
+	// A diamond: the same leaf is reachable through both paths.
+	let shared = Rc::new(RefCell::new(Leaf { counter: 0 }));
+	let mut framework = Framework::new((
+		Path {
+			shared: Rc::downgrade(&shared),
+		},
+		Path {
+			shared: Rc::downgrade(&shared),
+		},
+	));
+
+	assert_eq!(framework.send(&mut Increment(1)), 1);
+	assert_eq!(shared.borrow().counter, 1);
+
+	// Queued broadcasts are deduplicated too.
+	let mut ping = Ping(Vec::new());
+	framework.send(&mut ping);
+	assert_eq!(ping.0, [1]);
+	assert_eq!(shared.borrow().counter, 2);
+}
+
+struct NameVisitor(Vec<&'static str>);
+
+impl ActorVisitor<(), ()> for NameVisitor {
+	fn visit<A>(&mut self, _: &mut A)
+	where
+		A: Actor + Receiver<(), ()>,
+	{
+		self.0.push(std::any::type_name::<A>().rsplit("::").next().unwrap());
+	}
+}
+
+#[test]
+fn visit_named() {
+	let mut framework = framework();
+	let mut visitor = NameVisitor(Vec::new());
+	framework.get_mut().accept(&mut visitor);
+	assert_eq!(visitor.0, ["ChildChild", "Child", "Root"]);
+
+	// The name is ignored by default.
+	visitor.visit_named(&mut Leaf { counter: 0 }, "Renamed");
+	assert_eq!(visitor.0, ["ChildChild", "Child", "Root", "Leaf"]);
+}
+
+#[cfg(feature = "indexmap")]
+#[derive(Actor, Hash, PartialEq, Eq)]
+struct Key(u16);
+
+#[cfg(feature = "indexmap")]
+receive! {
+	Ping => Key = (&mut self, ping, _) {
+		ping.0.push(self.0);
+	}
+}
+
+#[cfg(feature = "indexmap")]
+#[test]
+fn indexmap() {
+	use indexmap::{IndexMap, IndexSet};
+
+	let mut map = IndexMap::new();
+	for (key, counter) in [("c", 2), ("a", 0), ("b", 1)] {
+		map.insert(key, Leaf { counter });
+	}
+	let mut framework = Framework::new((map, [Key(3), Key(1), Key(2)].into_iter().collect::<IndexSet<_>>()));
+
+	// Both are visited in insertion order.
+	let mut ping = Ping(Vec::new());
+	framework.send(&mut ping);
+	assert_eq!(ping.0, [2, 0, 1, 3, 1, 2]);
+
+	// Each leaf queued an `Increment(1)` for every leaf.
+	assert!(framework.send_to_path("0.1", &mut Increment(5)));
+	assert!(!framework.send_to_path("0.3", &mut Increment(5)));
+	assert_eq!(framework.get().0["a"].counter, 8);
+}
+
+#[derive(Actor)]
+struct Window {
+	sizes: Vec<u16>,
+	panel: Panel,
+	other: Panel,
+}
+
+// Only responds to queries, which `Framework::send` doesn't deliver.
+#[derive(Actor)]
+#[actor(sparse)]
+struct Panel {
+	size: u16,
+	#[actor(recursive)]
+	children: Vec<Panel>,
+}
+
+struct Layout;
+
+struct PreferredSize;
+
+impl Query for PreferredSize {
+	type Response = u16;
+}
+
+receive! {
+	Layout => Window = (&mut self, _, context) {
+		self.sizes = context.send_sub_collect(self, &mut PreferredSize, |window| &mut window.panel);
+	}
+}
+
+receive! {
+	PreferredSize => Panel = respond (&mut self, _, _) {
+		self.size
+	}
+}
+
+#[test]
+fn send_sub_collect() {
+	let panel = |size, children| Panel { size, children };
+	let mut framework = Framework::new(Window {
+		sizes: Vec::new(),
+		panel: panel(1, vec![panel(2, vec![panel(3, Vec::new())]), panel(4, Vec::new())]),
+		other: panel(5, Vec::new()),
+	});
+
+	framework.send(&mut Layout);
+	// Children respond before their parents, and `other` isn't asked.
+	assert_eq!(framework.get().sizes, [3, 2, 4, 1]);
+}
+
+#[derive(Actor)]
+#[actor(handles(Increment))]
+struct Listed {
+	received: u16,
+}
+
+impl<R> Receiver<Increment, R> for Listed {
+	fn receive(&mut self, message: &mut Increment, _: Context<Self, R>) { self.received += message.0; }
+}
+
+#[derive(Actor)]
+#[actor(sparse)]
+struct Holder {
+	hand_written: HandWritten,
+}
+
+#[test]
+fn send_sparse() {
+	// `Panel` is recursive. `Window` isn't `sparse`, so it might have a `Receiver` written by hand.
+	assert!(!<Panel as Covers<Layout, Window>>::covers(&mut Vec::new()));
+	assert!(<Window as Covers<Layout, Window>>::covers(&mut Vec::new()));
+	assert!(<Listed as Covers<Increment, ()>>::covers(&mut Vec::new()));
+	assert!(!<Listed as Covers<Decrement, ()>>::covers(&mut Vec::new()));
+	assert!(<Holder as Covers<Decrement, ()>>::covers(&mut Vec::new()));
+
+	// The shared `Panel` would be borrowed if it was visited.
+	let shared = Rc::new(RefCell::new(Panel {
+		size: 1,
+		children: Vec::new(),
+	}));
+	let mut framework = Framework::new((Rc::downgrade(&shared), Listed { received: 0 }));
+	unsafe { framework.on::<Layout>(Box::new(|_| {})) };
+	let _borrowed = shared.borrow();
+	assert_eq!(framework.send(&mut Layout), 1);
+	assert_eq!(framework.send(&mut Increment(2)), 1);
+	assert_eq!(framework.get().1.received, 2);
+
+	// Sub-`Actor`s that aren't `sparse` are always visited.
+	let mut framework = Framework::new(Holder {
+		hand_written: HandWritten { received: 0 },
+	});
+	assert_eq!(framework.send(&mut Increment(3)), 1);
+	assert_eq!(framework.get().hand_written.received, 3);
+}
+
+struct Clock {
+	frame: u16,
+	log: Vec<(u16, u16)>,
+}
+
+#[derive(Actor)]
+struct Ticker {
+	id: u16,
+}
+
+struct Tick;
+
+receive! {
+	Tick => Ticker = (&mut self, _, mut context) {
+		let clock = context.resources::<Clock>();
+		clock.log.push((clock.frame, self.id));
+		context.send_to_self(self, &mut Increment(1));
+	}
+
+	Increment => Ticker = (&mut self, _, mut context) {
+		context.resources::<Clock>().frame += 1;
+	}
+}
+
+#[test]
+fn resources() {
+	let clock = Clock {
+		frame: 0,
+		log: Vec::new(),
+	};
+	let mut framework = Framework::with_resources(vec![Ticker { id: 1 }, Ticker { id: 2 }], clock);
+
+	framework.send(&mut Tick);
+	assert_eq!(framework.resources().log, [(0, 1), (1, 2)]);
+	framework.resources_mut().frame = 10;
+	framework.send(&mut Tick);
+	assert_eq!(framework.resources().frame, 12);
+}
+
+#[test]
+#[should_panic(expected = "`X` is not the type of the resources")]
+fn wrong_resources() { Framework::new(Ticker { id: 1 }).send(&mut Tick); }
+
+struct Describe(Vec<String>);
+
+receive! {
+	Describe => Ticker = (&mut self, describe, mut context) {
+		use std::fmt::Write;
+
+		let buffer = context.scratch::<String>();
+		buffer.clear();
+		write!(buffer, "ticker {}", self.id).unwrap();
+		describe.0.push(buffer.clone());
+	}
+}
+
+#[test]
+fn send_with_scratch() {
+	let mut framework = Framework::new(vec![Ticker { id: 1 }, Ticker { id: 22 }]);
+
+	let mut buffer = String::new();
+	let mut describe = Describe(Vec::new());
+	assert_eq!(framework.send_with_scratch(&mut describe, &mut buffer), 2);
+	assert_eq!(describe.0, ["ticker 1", "ticker 22"]);
+	// The last handler left its contents behind.
+	assert_eq!(buffer, "ticker 22");
+}
+
+#[test]
+#[should_panic(expected = "`scratch` requires a `Context` from `Framework::send_with_scratch`")]
+fn missing_scratch() { Framework::new(Ticker { id: 1 }).send(&mut Describe(Vec::new())); }
+
+#[derive(Actor)]
+struct Room {
+	lights: Gate<Entity>,
+	toggled: bool,
+}
+
+struct Toggle(bool);
+
+receive! {
+	Toggle => Room = (&mut self, toggle, context) {
+		self.toggled = if toggle.0 {
+			context.open_gate(self, |room| &mut room.lights)
+		} else {
+			context.close_gate(self, |room| &mut room.lights)
+		};
+	}
+}
+
+#[test]
+fn gate() {
+	let lights = Entity {
+		mounted: false,
+		counter: 0,
+	};
+	let mut framework = Framework::new(Room {
+		lights: Gate::new(lights, false),
+		toggled: false,
+	});
+
+	// Closed gates aren't visited.
+	framework.send(&mut Increment(1));
+	assert_eq!(framework.get().lights.get().counter, 0);
+
+	framework.send(&mut Toggle(true));
+	assert!(framework.get().toggled);
+	assert!(framework.get().lights.get().mounted);
+	framework.send(&mut Increment(1));
+	assert_eq!(framework.get().lights.get().counter, 1);
+
+	framework.send(&mut Toggle(true));
+	assert!(!framework.get().toggled);
+
+	framework.send(&mut Toggle(false));
+	assert!(framework.get().toggled);
+	assert!(!framework.get().lights.is_open());
+	assert!(!framework.get().lights.get().mounted);
+}
+
+#[test]
+fn send_to_path() {
+	let mut framework = framework();
+
+	assert!(framework.send_to_path("child.child", &mut Increment(1)));
+	assert_eq!(framework.get().child.child.counter, 3);
+	assert_eq!(framework.get().child.counter, 1);
+
+	assert!(framework.send_to_path("", &mut Increment(1)));
+	assert_eq!(framework.get().counter, 3);
+
+	// `data` isn't an actor.
+	assert!(!framework.send_to_path("data", &mut Increment(1)));
+	assert!(!framework.send_to_path("child.parent", &mut Increment(1)));
+	assert!(!framework.send_to_path("child.child.child", &mut Increment(1)));
+
+	let mut framework = Framework::new((
+		vec![Leaf { counter: 0 }, Leaf { counter: 0 }],
+		TaggedEnum::<()>::Unit {
+			_unit: (),
+			value: Leaf { counter: 0 },
+		},
+	));
+	assert!(framework.send_to_path("0.1", &mut Increment(1)));
+	assert!(framework.send_to_path("1.value", &mut Increment(2)));
+	assert!(!framework.send_to_path("0.2", &mut Increment(1)));
+	assert!(!framework.send_to_path("1._unit", &mut Increment(1)));
+	assert_eq!(framework.get().0[0].counter, 0);
+	assert_eq!(framework.get().0[1].counter, 1);
+	let TaggedEnum::Unit { value, .. } = &framework.get().1 else {
+		unreachable!()
+	};
+	assert_eq!(value.counter, 2);
+}
+
+#[test]
+fn for_each_mut() {
+	let child = |counter| Child {
+		counter,
+		child: ChildChild { counter: 100 },
+	};
+	let mut framework = Framework::new((
+		child(1),
+		vec![child(2), child(3)],
+		Root {
+			data: Data { data: 0 },
+			counter: 100,
+			child: child(4),
+		},
+	));
+
+	let mut sum = 0;
+	framework.for_each_mut(|child: &mut Child| {
+		sum += child.counter;
+		child.counter = 0;
+	});
+	assert_eq!(sum, 10);
+	assert_eq!(framework.get().2.child.counter, 0);
+	assert_eq!(framework.get().2.child.child.counter, 100);
+}
+
+#[derive(Actor)]
+struct Stepper {
+	id: u16,
+}
+
+struct Time;
+
+struct Physics;
+
+struct Render;
+
+receive! {
+	Time => Stepper = (&mut self, _, mut context) {
+		context.resources::<Vec<(u16, &str)>>().push((self.id, "time"));
+	}
+
+	Physics => Stepper = match (&mut self, mut context) {
+		_ if self.id == 1 => {
+			context.resources::<Vec<(u16, &str)>>().push((self.id, "physics"));
+		}
+	}
+
+	Render => Stepper = (&mut self, _, mut context) {
+		context.resources::<Vec<(u16, &str)>>().push((self.id, "render"));
+	}
+}
+
+#[test]
+fn send_tuple() {
+	let mut framework =
+		Framework::with_resources(vec![Stepper { id: 1 }, Stepper { id: 2 }], Vec::<(u16, &str)>::new());
+
+	assert_eq!(framework.send_tuple(&mut (Time, Physics, Render)), 6);
+	assert_eq!(
+		framework.resources(),
+		&[(1, "time"), (1, "physics"), (1, "render"), (2, "time"), (2, "render"),]
+	);
+}
+
+// Renders what it simulated in the same tick, whatever the order of the messages.
+#[derive(Actor)]
+#[actor(handle_order(Physics, Render))]
+struct OrderedStepper {
+	id: u16,
+}
+
+receive! {
+	Time => OrderedStepper = (&mut self, _, mut context) {
+		context.resources::<Vec<(u16, &str)>>().push((self.id, "time"));
+	}
+
+	Physics => OrderedStepper = (&mut self, _, mut context) {
+		context.resources::<Vec<(u16, &str)>>().push((self.id, "physics"));
+	}
+
+	Render => OrderedStepper = (&mut self, _, mut context) {
+		context.resources::<Vec<(u16, &str)>>().push((self.id, "render"));
+	}
+}
+
+#[test]
+fn handle_order() {
+	let mut framework =
+		Framework::with_resources((Stepper { id: 1 }, OrderedStepper { id: 2 }), Vec::<(u16, &str)>::new());
+
+	// `Time` has no position, so it comes after the others, and `Stepper` keeps the tuple order.
+	assert_eq!(framework.send_tuple(&mut (Time, Render, Physics)), 6);
+	assert_eq!(
+		framework.resources(),
+		&[
+			(1, "time"),
+			(1, "render"),
+			(1, "physics"),
+			(2, "physics"),
+			(2, "render"),
+			(2, "time")
+		]
+	);
+}
+
+#[derive(Actor)]
+struct Studio {
+	button: Button,
+	rack: Vec<AudioEngine>,
+}
+
+#[derive(Actor)]
+struct Button {
+	found: usize,
+}
+
+#[derive(Actor)]
+struct AudioEngine {
+	beeps: u16,
+}
+
+struct Click {
+	all: bool,
+}
+
+struct Beep;
+
+receive! {
+	Click => Button = (&mut self, click, context) {
+		self.found = if click.all {
+			context.send_to_all_type::<_, AudioEngine>(self, &mut Beep)
+		} else {
+			context.send_to_first_type::<_, AudioEngine>(self, &mut Beep) as usize
+		};
+	}
+}
+
+receive! {
+	Beep => AudioEngine = (&mut self, _, _) {
+		self.beeps += 1;
+	}
+}
+
+#[test]
+fn send_to_type() {
+	let mut framework = Framework::new(Studio {
+		button: Button { found: 0 },
+		rack: vec![AudioEngine { beeps: 0 }, AudioEngine { beeps: 0 }],
+	});
+
+	framework.send(&mut Click { all: false });
+	assert_eq!(framework.get().button.found, 1);
+	assert_eq!(framework.get().rack[0].beeps, 1);
+	assert_eq!(framework.get().rack[1].beeps, 0);
+
+	framework.send(&mut Click { all: true });
+	assert_eq!(framework.get().button.found, 2);
+	assert_eq!(framework.get().rack[0].beeps, 2);
+	assert_eq!(framework.get().rack[1].beeps, 1);
+
+	framework.get_mut().rack.clear();
+	framework.send(&mut Click { all: false });
+	assert_eq!(framework.get().button.found, 0);
+}
+
+#[test]
+fn send_budgeted() {
+	let mut framework = Framework::new((0..3).map(|_| Leaf { counter: 0 }).collect::<Vec<_>>());
+	let counters =
+		|framework: &Framework<Vec<Leaf>>| framework.get().iter().map(|leaf| leaf.counter).collect::<Vec<_>>();
+
+	// An exhausted budget still makes progress, one actor at a time.
+	let mut resume = framework.send_budgeted(&mut Increment(1), Duration::ZERO);
+	assert!(!resume.is_done());
+	assert_eq!(counters(&framework), [1, 0, 0]);
+	resume = framework.send_resume(&mut Increment(1), Duration::ZERO, resume);
+	assert_eq!(counters(&framework), [1, 1, 0]);
+	resume = framework.send_resume(&mut Increment(1), Duration::ZERO, resume);
+	assert!(resume.is_done());
+	assert_eq!(counters(&framework), [1, 1, 1]);
+	assert!(framework
+		.send_resume(&mut Increment(1), Duration::ZERO, resume)
+		.is_done());
+	assert_eq!(counters(&framework), [1, 1, 1]);
+
+	let resume = framework.send_budgeted(&mut Increment(1), Duration::from_secs(60));
+	assert!(resume.is_done());
+	assert_eq!(counters(&framework), [2, 2, 2]);
+}
+
+#[test]
+fn intercept() {
+	let mut framework = Framework::new(Bell);
+	// SAFETY: `Chime` has no lifetime parameters.
+	unsafe {
+		framework.on::<Chime>(Box::new(|chime| chime.0.push("handler")));
+		framework.intercept::<Chime>(Box::new(|chime| {
+			chime.0.push("first");
+			chime.0.len() < 3
+		}));
+		framework.intercept::<Chime>(Box::new(|chime| {
+			chime.0.push("second");
+			true
+		}));
+	}
+
+	// Interceptors run before the actors, in the order they were registered.
+	let mut chime = Chime(Vec::new());
+	assert_eq!(framework.send(&mut chime), 2);
+	assert_eq!(chime.0, ["first", "second", "bell", "handler"]);
+
+	// Canceling skips the remaining interceptors, the actors and the handlers.
+	let mut chime = Chime(vec!["a", "b"]);
+	assert_eq!(framework.send(&mut chime), 0);
+	assert_eq!(chime.0, ["a", "b", "first"]);
+}
+
+#[test]
+fn is_actor() {
+	const _: () = assert!(is_actor!(Entity));
+	const _: () = assert!(is_actor!(Arena<Entity>));
+	const _: () = assert!(is_actor!((u16, Entity)));
+	const _: () = assert!(!is_actor!(u16));
+	const _: () = assert!(!is_actor!(Vec<&str>));
+	const _: () = assert!(!is_actor!(Chime));
+
+	assert!(is_actor!(Slots<Entity, 2>));
+	assert!(!is_actor!(Option<u16>));
+}
+
+#[cfg(feature = "rayon")]
+struct Drift(u16);
+
+#[cfg(feature = "rayon")]
+impl send::ParMessage for Drift {}
+
+#[cfg(feature = "rayon")]
+impl send::ParReceiver<Drift> for Entity {
+	fn receive_par(&mut self, message: &Drift) { self.counter += message.0 }
+}
+
+#[test]
+#[cfg(feature = "rayon")]
+fn send_par() {
+	let entities: Vec<_> = (0..1000)
+		.map(|counter| Entity {
+			mounted: false,
+			counter,
+		})
+		.collect();
+	let mut framework = Framework::new((Bell, entities));
+
+	assert_eq!(framework.send_par(&Drift(2), |root| &mut root.1), 1000);
+	assert!(framework
+		.get()
+		.1
+		.iter()
+		.enumerate()
+		.all(|(i, entity)| entity.counter == i as u16 + 2));
+}
+
+#[derive(Actor)]
+#[cfg(feature = "rand")]
+struct Die {
+	rolled: Vec<u64>,
+}
+
+#[cfg(feature = "rand")]
+struct Roll;
+
+#[cfg(feature = "rand")]
+receive! {
+	Roll => Die = (&mut self, _, context) {
+		use send::rand_core::RngCore;
+
+		let mut rng = context.rng(self);
+		self.rolled.push(rng.next_u64());
+	}
+}
+
+#[test]
+#[cfg(feature = "rand")]
+fn rng() {
+	let roll = |seed| {
+		let mut framework = Framework::new((Die { rolled: Vec::new() }, Die { rolled: Vec::new() }));
+		framework.set_seed(seed);
+		framework.send(&mut Roll);
+		framework.send(&mut Roll);
+		let (first, second) = framework.get();
+		(first.rolled.clone(), second.rolled.clone())
+	};
+
+	let (first, second) = roll(7);
+	// Every actor and every dispatch gets its own sequence.
+	assert_ne!(first, second);
+	assert_ne!(first[0], first[1]);
+	// The same seed and messages give the same sequences.
+	assert_eq!(roll(7), (first.clone(), second));
+	assert_ne!(roll(8).0, first);
+}
+
+#[derive(Actor)]
+struct Solver {
+	passes: u16,
+	settle_after: u16,
+}
+
+struct Perturb;
+
+receive! {
+	Perturb => Solver = (&mut self, _, context) {
+		self.passes += 1;
+		if self.passes < self.settle_after {
+			context.request_repeat();
+		}
+	}
+}
+
+#[test]
+fn request_repeat() {
+	let mut framework = Framework::new((
+		Solver {
+			passes: 0,
+			settle_after: 3,
+		},
+		Solver {
+			passes: 0,
+			settle_after: 1,
+		},
+	));
+	assert_eq!(framework.send(&mut Perturb), 2);
+	assert_eq!(framework.get().0.passes, 3);
+	assert_eq!(framework.get().1.passes, 3);
+
+	// Requests don't carry over to the next message.
+	framework.get_mut().0.settle_after = 0;
+	framework.send(&mut Perturb);
+	assert_eq!(framework.get().0.passes, 4);
+}
+
+#[test]
+#[should_panic(expected = "send repeat limit exceeded (4)")]
+fn request_repeat_limit() {
+	let mut framework = Framework::new(Solver {
+		passes: 0,
+		settle_after: u16::MAX,
+	});
+	framework.set_repeat_limit(4);
+	framework.send(&mut Perturb);
+}
+
+#[derive(Actor)]
+struct Stopwatch {
+	times: Vec<u64>,
+}
+
+struct Sample;
+
+struct Resample;
+
+receive! {
+	Sample => Stopwatch = (&mut self, _, context) {
+		self.times.push(context.now());
+		context.queue_broadcast(Resample);
+	}
+	Resample => Stopwatch = (&mut self, _, context) {
+		self.times.push(context.now());
+	}
+}
+
+#[test]
+fn now() {
+	let mut framework = Framework::new(Stopwatch { times: Vec::new() });
+	framework.send(&mut Sample);
+	assert_eq!(framework.tick(), 1);
+	framework.send(&mut Sample);
+	framework.set_time(10);
+	framework.send(&mut Sample);
+
+	// Broadcasts see the time of the dispatch that queued them.
+	assert_eq!(framework.get().times, [0, 0, 1, 1, 10, 10]);
+	assert_eq!(framework.time(), 10);
+}
+
+#[test]
+fn event_adapter() {
+	let mut framework = Framework::new((Bell, Bell));
+	let mut chimes = EventAdapter::new();
+	chimes.push(Chime(vec!["first"]));
+	chimes.extend([Chime(vec!["second"])]);
+	assert_eq!(chimes.len(), 2);
+
+	chimes.flush(&mut framework);
+	assert!(chimes.is_empty());
+
+	let mut batch = [Chime(Vec::new()), Chime(Vec::new())];
+	framework.send_batch(&mut batch);
+	assert_eq!(batch[0].0, ["bell", "bell"]);
+	assert_eq!(batch[1].0, ["bell", "bell"]);
+}
+
+#[test]
+fn downcast_root() {
+	fn reset<R: Actor + 'static>(framework: &mut Framework<R>) -> bool {
+		match framework.downcast_root_mut::<Root>() {
+			Some(root) => {
+				root.counter = 0;
+				true
+			},
+			None => false,
+		}
+	}
+
+	let mut framework = framework();
+	framework.send(&mut Increment(1));
+	assert!(framework.downcast_root_ref::<Child>().is_none());
+	assert!(reset(&mut framework));
+	assert_eq!(framework.downcast_root_ref::<Root>().unwrap().counter, 0);
+
+	assert!(!reset(&mut Framework::new(Bell)));
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Job(u16);
+
+#[test]
+fn channel_actor() {
+	let (sender, receiver) = std::sync::mpsc::channel::<Job>();
+	// SAFETY: `Job` has no lifetime parameters.
+	let channel = unsafe { ChannelActor::new(sender) };
+	let mut framework = Framework::new((channel, Leaf { counter: 0 }));
+
+	let worker = std::thread::spawn(move || receiver.iter().take(2).collect::<Vec<_>>());
+	assert_eq!(framework.send(&mut Job(1)), 1);
+	framework.send(&mut Job(2));
+	// Other messages aren't forwarded.
+	assert_eq!(framework.send(&mut Increment(1)), 1);
+	assert_eq!(worker.join().unwrap(), [Job(1), Job(2)]);
+
+	// The receiver was dropped with the worker.
+	assert!(framework.get().0.is_connected());
+	framework.send(&mut Job(3));
+	assert!(!framework.get().0.is_connected());
+}
+
+struct Validate;
+
+impl Query for Validate {
+	type Response = Result<(), String>;
+}
+
+receive! {
+	Validate => Leaf = respond (&mut self, _, _) {
+		if self.counter > 0 { Ok(()) } else { Err("empty leaf".to_string()) }
+	}
+
+	Validate => Entity = respond (&mut self, _, _) {
+		if self.mounted { Ok(()) } else { Err(format!("unmounted entity with {}", self.counter)) }
+	}
+}
+
+#[test]
+fn send_collecting_errors() {
+	let entity = |mounted, counter| Entity { mounted, counter };
+	let mut framework = Framework::new((
+		Leaf { counter: 0 },
+		[entity(false, 1), entity(true, 2), entity(false, 3)],
+	));
+	assert_eq!(
+		framework.send_collecting_errors(&mut Validate),
+		["empty leaf", "unmounted entity with 1", "unmounted entity with 3"]
+	);
+
+	let mut framework = Framework::new((Leaf { counter: 1 }, [entity(true, 1)]));
+	assert!(framework.send_collecting_errors(&mut Validate).is_empty());
+}
+
+#[derive(Actor)]
+struct Thermostat {
+	target: i16,
+}
+
+#[derive(Debug)]
+struct Warm(i16);
+
+#[derive(Debug)]
+struct Chill(i16);
+
+#[derive(Debug)]
+struct Setpoint;
+
+impl Query for Setpoint {
+	type Response = i16;
+}
+
+receive_enum! {
+	#[derive(Debug)]
+	enum ThermostatMessage;
+
+	Warm => Thermostat = (&mut self, warm, _) { self.target += warm.0 }
+	Chill => Thermostat = (&mut self, chill, _) { self.target -= chill.0 }
+	Setpoint => Thermostat = respond (&mut self, _, _) { self.target }
+}
+
+#[test]
+fn receive_enum() {
+	assert_eq!(ThermostatMessage::NAMES, ["Warm", "Chill", "Setpoint"]);
+
+	let mut framework = Framework::new(Thermostat { target: 20 });
+	let messages = [ThermostatMessage::from(Warm(3)), Chill(1).into(), Setpoint.into()];
+	let names: Vec<_> = messages.iter().map(ThermostatMessage::name).collect();
+	assert_eq!(names, ThermostatMessage::NAMES);
+
+	for message in messages {
+		framework.send_enum(message);
+	}
+	assert_eq!(framework.send_fold(&mut Setpoint, 0, |_, target| target), 22);
+}
+
+struct Damage(u16);
+
+receive! {
+	Damage => Entity = (&mut self, damage, context) {
+		self.counter = self.counter.saturating_sub(damage.0);
+		if self.counter == 0 {
+			context.remove_self(self);
+		}
+	}
+}
+
+#[test]
+fn remove_self() {
+	let entities = [3, 1, 5, 1].map(|counter| Entity { mounted: true, counter });
+	let mut framework = Framework::new((
+		Vec::from(entities),
+		Wrap(HashMap::from([(
+			"boss",
+			Entity {
+				mounted: true,
+				counter: 2,
+			},
+		)])),
+	));
+
+	// Every entity still receives the message, even after the ones before it removed themselves.
+	framework.send(&mut Damage(1));
+	let counters: Vec<_> = framework.get().0.iter().map(|entity| entity.counter).collect();
+	assert_eq!(counters, [2, 4]);
+	assert_eq!(framework.get().1 .0["boss"].counter, 1);
+
+	framework.send(&mut Damage(2));
+	let counters: Vec<_> = framework.get().0.iter().map(|entity| entity.counter).collect();
+	assert_eq!(counters, [2]);
+	assert!(framework.get().1 .0.is_empty());
+}
+
+#[test]
+fn remove_self_collections() {
+	let entity = |counter| Entity { mounted: true, counter };
+	let mut arena = Arena::new();
+	let doomed = arena.insert(entity(1));
+	let survivor = arena.insert(entity(2));
+	let mut slots = Slots::<Entity, 3>::new();
+	assert!(slots.insert(entity(1)).is_ok());
+	assert!(slots.insert(entity(2)).is_ok());
+	let mut framework = Framework::new((LinkedList::from([entity(1), entity(2), entity(1)]), arena, slots));
+
+	framework.send(&mut Damage(1));
+	let (list, arena, slots) = framework.get();
+	assert_eq!(list.iter().map(|entity| entity.counter).collect::<Vec<_>>(), [1]);
+	assert!(!arena.contains(doomed));
+	assert_eq!((arena.len(), arena.get(survivor).unwrap().counter), (1, 1));
+	assert_eq!(
+		slots
+			.iter()
+			.map(|(index, entity)| (index, entity.counter))
+			.collect::<Vec<_>>(),
+		[(1, 1)]
+	);
+}
+
+#[test]
+#[should_panic(
+	expected = "1 `Actor`s called `remove_self`, but they aren't elements of a collection that can remove them"
+)]
+fn remove_self_unsupported() {
+	let mut framework = Framework::new(BTreeMap::from([(
+		0,
+		Entity {
+			mounted: true,
+			counter: 1,
+		},
+	)]));
+	framework.send(&mut Damage(1));
+}
+
+const WEATHER: TopicId = TopicId::new("weather");
+const TRAFFIC: TopicId = TopicId::new("traffic");
+
+#[derive(Actor)]
+#[actor(topics = "topics")]
+struct Newsreader {
+	topics: Topics,
+	counter: u16,
+	leaf: Leaf,
+}
+
+struct Follow(TopicId);
+
+struct Forecast;
+
+receive! {
+	Increment => Newsreader = (&mut self, message, _) {
+		self.counter += message.0;
+	}
+	Follow => Newsreader = (&mut self, message, _) {
+		self.topics.subscribe(message.0);
+	}
+	Forecast => Newsreader = (&mut self, _, context) {
+		context.publish(self, WEATHER, &mut Increment(10));
+	}
+}
+
+#[test]
+fn publish() {
+	let mut framework = Framework::new((
+		Leaf { counter: 0 },
+		Newsreader {
+			topics: [WEATHER].into_iter().collect(),
+			counter: 0,
+			leaf: Leaf { counter: 0 },
+		},
+		Newsreader {
+			topics: Topics::new(),
+			counter: 0,
+			leaf: Leaf { counter: 0 },
+		},
+	));
+	let counters = |framework: &Framework<(Leaf, Newsreader, Newsreader)>| {
+		let (leaf, a, b) = framework.get();
+		(leaf.counter, a.counter, a.leaf.counter, b.counter)
+	};
+
+	// Sub-actors aren't subscribed just because their parent is.
+	assert_eq!(framework.publish(WEATHER, &mut Increment(1)), 1);
+	assert_eq!(framework.publish(TRAFFIC, &mut Increment(1)), 0);
+	assert_eq!(counters(&framework), (0, 1, 0, 0));
+
+	framework.send(&mut Follow(TRAFFIC));
+	assert_eq!(framework.publish(TRAFFIC, &mut Increment(1)), 2);
+	assert_eq!(counters(&framework), (0, 2, 0, 1));
+
+	framework.get_mut().1.topics.unsubscribe(WEATHER);
+	framework.get_mut().2.topics.subscribe(WEATHER);
+	framework.publish(TRAFFIC, &mut Forecast);
+	assert_eq!(counters(&framework), (0, 2, 0, 21));
+}
+
+#[derive(Actor)]
+#[actor(top_down)]
+struct Banner {
+	child: Child,
+}
+
+receive! {
+	Increment => Banner = (&mut self, _, _) {}
+}
+
+#[test]
+fn top_down() {
+	// The `Actor`s that received `Increment`, in order.
+	fn order<R: Actor + 'static>(framework: &mut Framework<R>) -> Vec<&'static str> {
+		order_with(framework, |framework| {
+			framework.send(&mut Increment(1));
+		})
+	}
+
+	fn order_with<R: Actor + 'static>(
+		framework: &mut Framework<R>, send: impl FnOnce(&mut Framework<R>),
+	) -> Vec<&'static str> {
+		framework.enable_trace(16);
+		send(framework);
+		framework
+			.recent()
+			.filter(|delivery| delivery.message == "tests::Increment")
+			.map(|delivery| delivery.actor)
+			.collect()
+	}
+
+	// Fields are visited first by default, so children receive messages before their parents.
+	let mut framework = framework();
+	assert_eq!(
+		order(&mut framework),
+		["tests::ChildChild", "tests::Child", "tests::Root"]
+	);
+
+	framework.set_top_down(true);
+	assert_eq!(
+		order(&mut framework),
+		["tests::Root", "tests::Child", "tests::ChildChild"]
+	);
+
+	// The other ways to send a message are top down too.
+	let top_down = ["tests::Root", "tests::Child", "tests::ChildChild"];
+	let batch = order_with(&mut framework, |framework| framework.send_batch(&mut [Increment(1)]));
+	assert_eq!(batch, top_down);
+	let tuple = order_with(&mut framework, |framework| {
+		framework.send_tuple(&mut (Increment(1),));
+	});
+	assert_eq!(tuple, top_down);
+	let prioritized = order_with(&mut framework, |framework| {
+		framework.send_prioritized(&mut Increment(1))
+	});
+	assert_eq!(prioritized, top_down);
+
+	// So are the actors from `impl_actor!`.
+	let mut framework = Framework::new(Wrapped(foreign::Tree {
+		value: Leaf { counter: 0 },
+		children: Vec::new(),
+	}));
+	framework.set_top_down(true);
+	assert_eq!(order(&mut framework), ["tests::Wrapped", "tests::Leaf"]);
+
+	// And `Wrap`.
+	let mut framework = Framework::new(Wrap(Child {
+		counter: 1,
+		child: ChildChild { counter: 0 },
+	}));
+	framework.set_top_down(true);
+	assert_eq!(
+		order(&mut framework),
+		["send::wrap::Wrap<tests::Child>", "tests::ChildChild"]
+	);
+
+	// Only `Banner` is visited before its fields.
+	let mut framework = Framework::new(Banner {
+		child: Child {
+			counter: 2,
+			child: ChildChild { counter: 2 },
+		},
+	});
+	assert_eq!(
+		order(&mut framework),
+		["tests::Banner", "tests::ChildChild", "tests::Child"]
+	);
+}
+
+#[derive(Actor)]
+struct Racket {
+	side: usize,
+	// The volleys received, with `0` for every `Whistle`.
+	log: Vec<u8>,
+}
+
+struct Serve;
+
+struct Volley(u8);
+
+struct Whistle;
+
+type Court = (Racket, Racket);
+
+fn other_side(side: usize) -> impl FnOnce(&mut Court) -> &mut Racket {
+	move |court| if side == 0 { &mut court.1 } else { &mut court.0 }
+}
+
+impl Receiver<Serve, Court> for Racket {
+	fn receive(&mut self, _: &mut Serve, context: Context<Self, Court>) {
+		if self.side == 0 {
+			context.defer_to(other_side(self.side), Volley(1));
+			context.queue_broadcast(Whistle);
+		}
+	}
+}
+
+impl Receiver<Volley, Court> for Racket {
+	fn receive(&mut self, volley: &mut Volley, context: Context<Self, Court>) {
+		self.log.push(volley.0);
+		if volley.0 < 3 {
+			context.defer_to(other_side(self.side), Volley(volley.0 + 1));
+		}
+	}
+}
+
+receive! {
+	Whistle => Racket = (&mut self, _, _) {
+		self.log.push(0);
+	}
+}
+
+#[test]
+fn defer_to() {
+	let racket = |side| Racket { side, log: Vec::new() };
+	let mut framework = Framework::new((racket(0), racket(1)));
+	framework.send(&mut Serve);
+
+	// The first volley was queued before the whistle, and the ones queued while draining come after both.
+	assert_eq!(framework.get().0.log, [0, 2]);
+	assert_eq!(framework.get().1.log, [1, 0, 3]);
+}
+
+#[test]
+fn boxed_slice() {
+	let child = || Child {
+		counter: 2,
+		child: ChildChild { counter: 2 },
+	};
+	let mut framework = Framework::new(vec![child(), child()].into_boxed_slice());
+	let counters = |children: &[Child]| -> Vec<_> {
+		children
+			.iter()
+			.map(|child| (child.counter, child.child.counter))
+			.collect()
+	};
+	// Every `ChildChild` broadcasts a `Decrement` to both `Child`ren.
+	assert_eq!(framework.send(&mut Increment(1)), 4);
+	assert_eq!(counters(framework.get()), [(1, 3), (1, 3)]);
+	assert!(framework.send_to_path("1.child", &mut Increment(1)));
+	assert_eq!(counters(framework.get()), [(0, 3), (0, 4)]);
+
+	// Shared slices aren't visited, like any other shared `Arc`.
+	let mut framework = Framework::new(Arc::<[Child]>::from([child(), child()]));
+	let shared = framework.get().clone();
+	assert_eq!(framework.send(&mut Increment(1)), 0);
+	drop(shared);
+	assert_eq!(framework.send(&mut Increment(1)), 4);
+}
+
+#[derive(Actor, Default)]
+struct Branch {
+	leaves: Vec<Leaf>,
+	#[actor(recursive)]
+	branches: Vec<Branch>,
+}
+
+#[test]
+fn restructure() {
+	let leaf = |counter| Leaf { counter };
+	let mut framework = Framework::new(Branch {
+		leaves: vec![leaf(0), leaf(1)],
+		branches: vec![Branch {
+			leaves: vec![leaf(2), leaf(0)],
+			branches: Vec::new(),
+		}],
+	});
+
+	// Prune the dead leaves and grow a new branch everywhere. The new branches aren't visited in the same pass.
+	let mut grown = 0;
+	let visited = framework.restructure(|branch: &mut Branch| {
+		branch.leaves.retain(|leaf| leaf.counter > 0);
+		branch.branches.push(Branch::default());
+		grown += 1;
+	});
+	assert_eq!((visited, grown), (2, 2));
+	assert_eq!(framework.actor_count(), 6);
+
+	// The next pass visits them, and still works while the framework is top down.
+	framework.set_top_down(true);
+	assert_eq!(framework.restructure(|branch: &mut Branch| branch.branches.clear()), 4);
+	assert_eq!(framework.actor_count(), 2);
+	assert_eq!(framework.get().leaves[0].counter, 1);
+}
+
+#[test]
+fn restructure_collections() {
+	let leaf = |counter| Leaf { counter };
+	let mut framework = Framework::new((
+		vec![leaf(0), leaf(1)],
+		Branch {
+			leaves: vec![leaf(0)],
+			branches: Vec::new(),
+		},
+	));
+
+	// Every `Vec<Leaf>` is edited once its leaves were visited, including the one at the root.
+	let visited = framework.restructure(|leaves: &mut Vec<Leaf>| {
+		leaves.retain(|leaf| leaf.counter > 0);
+		leaves.push(leaf(5));
+	});
+	assert_eq!(visited, 2);
+	let (leaves, branch) = framework.get();
+	let counters = |leaves: &[Leaf]| leaves.iter().map(|leaf| leaf.counter).collect::<Vec<_>>();
+	assert_eq!((counters(leaves), counters(&branch.leaves)), (vec![1, 5], vec![5]));
+
+	// The new leaves receive messages like the others.
+	assert_eq!(framework.send(&mut Increment(1)), 3);
+}
+
+// Which of the `Actor`s that received it were the root, in order.
+struct Position(Vec<bool>);
+
+receive! {
+	Position => Root = (&mut self, position, context) {
+		position.0.push(context.is_root(self));
+	}
+
+	Position => Child = (&mut self, position, context) {
+		position.0.push(context.is_root(self));
+	}
+}
+
+#[test]
+fn is_root() {
+	let mut position = Position(Vec::new());
+	framework().send(&mut position);
+	assert_eq!(position.0, [false, true]);
+
+	// `Child` is the root of its own `Framework`.
+	let mut position = Position(Vec::new());
+	Framework::new(Child::default()).send(&mut position);
+	assert_eq!(position.0, [true]);
+}
+
+#[test]
+fn send_callback() {
+	let mut framework = framework();
+	framework.enable_trace(4);
+	let called = framework.send_callback(|child: &mut ChildChild, context: Context<ChildChild, Root>| {
+		child.counter *= 10;
+		context.broadcast(child, &mut Decrement(1));
+	});
+	assert_eq!(called, 1);
+	assert_eq!(
+		(framework.get().child.counter, framework.get().child.child.counter),
+		(1, 20)
+	);
+
+	// The callback is recorded like a message, after the broadcast it sent.
+	let recent: Vec<_> = framework.recent().collect();
+	assert_eq!(recent.len(), 2);
+	assert_eq!(
+		(recent[0].actor, recent[0].message),
+		("tests::Child", "tests::Decrement")
+	);
+	assert_eq!(recent[1].actor, "tests::ChildChild");
+	assert!(recent[1].message.contains("send_callback"));
+
+	assert_eq!(framework.send_callback(|_: &mut Leaf, _: Context<Leaf, Root>| {}), 0);
+}
+
+#[derive(Actor, Default)]
+struct Tally {
+	#[counter(saturating)]
+	hits: u8,
+	#[counter(wrapping)]
+	laps: u8,
+	#[counter(saturating, increment = "Increment", decrement = "Decrement")]
+	score: u16,
+}
+
+#[test]
+fn counter() {
+	let mut framework = Framework::new(Tally::default());
+	let counters = |tally: &Tally| (tally.hits, tally.laps, tally.score);
+
+	// Underflow saturates or wraps instead of panicking.
+	assert_eq!(framework.send(&mut send::Decrement(1u8)), 1);
+	assert_eq!(counters(framework.get()), (0, 255, 0));
+	framework.send(&mut send::Increment(1u8));
+	assert_eq!(counters(framework.get()), (1, 0, 0));
+
+	// `score` uses the messages of the tests instead.
+	framework.send(&mut Increment(3));
+	framework.send(&mut Decrement(5));
+	assert_eq!(counters(framework.get()), (1, 0, 0));
+	framework.send(&mut Increment(u16::MAX));
+	framework.send(&mut Increment(1));
+	assert_eq!(counters(framework.get()), (1, 0, u16::MAX));
+}
+
+#[test]
+fn send_pruned() {
+	let mut framework = framework();
+	let counters = |root: &Root| (root.counter, root.child.counter, root.child.child.counter);
+
+	// Pruning `Child` skips it and `ChildChild`.
+	assert_eq!(
+		framework.send_pruned(&mut Increment(1), |child: &Child| child.counter < 5),
+		1
+	);
+	assert_eq!(counters(framework.get()), (3, 2, 2));
+
+	assert_eq!(framework.send_pruned(&mut Increment(1), |_: &Child| false), 3);
+	assert_eq!(counters(framework.get()), (4, 2, 3));
+
+	// Actors from `impl_actor!` and `leaf_actors!` are pruned too.
+	let mut framework = Framework::new(Wrapped(foreign::Tree {
+		value: Leaf { counter: 0 },
+		children: vec![Leaf { counter: 0 }],
+	}));
+	assert_eq!(framework.send_pruned(&mut Increment(1), |_: &Wrapped| true), 0);
+	assert_eq!(framework.get().0.value.counter, 0);
+
+	let mut framework = Framework::new(vec![Coin(1), Coin(2)]);
+	assert_eq!(framework.send_pruned(&mut Increment(1), |coin: &Coin| coin.0 > 1), 1);
+	assert_eq!(framework.get()[0].0, 2);
+	assert_eq!(framework.get()[1].0, 2);
+	// A `Wrap` is pruned along with the `Actor` it wraps, and shared ones aren't copied.
+	let shared = CowArc::new(Setting { value: 1 });
+	let mut framework = Framework::new((
+		Wrap(Setting { value: 1 }),
+		shared.clone(),
+		Cow::Borrowed(&DEFAULT_SETTING),
+	));
+	assert_eq!(framework.send_pruned(&mut Increment(1), |_: &Setting| true), 0);
+	let (wrap, copy, default) = framework.get();
+	assert_eq!(wrap.0.value, 1);
+	assert!(Arc::ptr_eq(copy.as_arc(), shared.as_arc()));
+	assert!(matches!(default, Cow::Borrowed(_)));
+	assert_eq!(framework.send_pruned(&mut Increment(1), |_: &Setting| false), 3);
+}
+
+trait Burnable {}
+
+trait Breakable {}
+
+trait_tags!(dyn Burnable, dyn Breakable);
+
+#[derive(Actor, TraitTagged)]
+#[tagged(Burnable)]
+struct Torch {
+	hits: u16,
+	stones: Vec<Stone>,
+}
+
+#[derive(Actor, TraitTagged)]
+#[tagged(Breakable, Burnable)]
+struct Vase {
+	hits: u16,
+}
+
+#[derive(Actor)]
+struct Stone {
+	hits: u16,
+}
+
+impl Burnable for Torch {}
+
+impl Burnable for Vase {}
+
+impl Breakable for Vase {}
+
+receive! {
+	Increment => Torch = (&mut self, message, _) {
+		self.hits += message.0;
+	}
+
+	Increment => Vase = (&mut self, message, _) {
+		self.hits += message.0;
+	}
+
+	Increment => Stone = (&mut self, message, _) {
+		self.hits += message.0;
+	}
+}
+
+#[test]
+fn send_to_impls() {
+	let mut framework = Framework::new((
+		Torch {
+			hits: 0,
+			stones: vec![Stone { hits: 0 }],
+		},
+		Vase { hits: 0 },
+	));
+	let hits = |framework: &Framework<(Torch, Vase)>| {
+		let (torch, vase) = framework.get();
+		(torch.hits, torch.stones[0].hits, vase.hits)
+	};
+
+	assert_eq!(<Vase as TraitTagged>::trait_tags(), 0b11);
+	assert_eq!(<Stone as TraitTagged>::trait_tags(), 0);
+
+	assert_eq!(framework.send_to_impls::<dyn Burnable, _>(&mut Increment(1)), 2);
+	assert_eq!(hits(&framework), (1, 0, 1));
+	assert_eq!(framework.send_to_impls::<dyn Breakable, _>(&mut Increment(1)), 1);
+	assert_eq!(hits(&framework), (1, 0, 2));
+}